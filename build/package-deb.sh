@@ -212,7 +212,7 @@ if [[ $SKIP_BUILD -eq 0 ]]; then
   pushd "$REPO_ROOT" >/dev/null
   CARGO_HOME="$REPO_ROOT/.cargo-home" \
     CARGO_TARGET_DIR="$WORK_ROOT/cargo-target" \
-    cargo build --release -p chissu-cli -p pam-chissu
+    cargo build --release -p chissu-cli -p chissu-authd -p pam-chissu
   popd >/dev/null
 else
   log "Skipping cargo build (per --skip-build)"
@@ -263,13 +263,15 @@ chmod 755 "$DEBIAN_DIR/rules" "$DEBIAN_DIR/postinst" "$DEBIAN_DIR/prerm" "$DEBIA
 log "Staging artifacts"
 CARGO_BUILD_DIR="$WORK_ROOT/cargo-target"
 BIN_SRC="$CARGO_BUILD_DIR/release/chissu-cli"
+AUTHD_SRC="$CARGO_BUILD_DIR/release/chissu-authd"
 PAM_SRC="$CARGO_BUILD_DIR/release/libpam_chissu.so"
-if [[ ! -f "$BIN_SRC" || ! -f "$PAM_SRC" ]]; then
+if [[ ! -f "$BIN_SRC" || ! -f "$AUTHD_SRC" || ! -f "$PAM_SRC" ]]; then
   echo "Expected release binaries missing; run without --skip-build" >&2
   exit 1
 fi
 
 mkdir -p "$ARTIFACT_DIR/usr/bin" \
+         "$ARTIFACT_DIR/usr/lib/systemd/system" \
          "$ARTIFACT_DIR/$PAM_DEST_REL" \
          "$ARTIFACT_DIR/etc/chissu-pam" \
          "$ARTIFACT_DIR/usr/share/doc/chissu-pam" \
@@ -277,6 +279,9 @@ mkdir -p "$ARTIFACT_DIR/usr/bin" \
          "$ARTIFACT_DIR/usr/share/chissu-pam"
 
 cp "$BIN_SRC" "$ARTIFACT_DIR/usr/bin/chissu-cli"
+cp "$AUTHD_SRC" "$ARTIFACT_DIR/usr/bin/chissu-authd"
+cp "$REPO_ROOT/build/package/assets/usr/lib/systemd/system/chissu-authd.socket" "$ARTIFACT_DIR/usr/lib/systemd/system/chissu-authd.socket"
+cp "$REPO_ROOT/build/package/assets/usr/lib/systemd/system/chissu-authd.service" "$ARTIFACT_DIR/usr/lib/systemd/system/chissu-authd.service"
 cp "$PAM_SRC" "$ARTIFACT_DIR/$PAM_DEST_REL/libpam_chissu.so"
 cp "$REPO_ROOT/build/package/assets/etc/chissu-pam/config.toml" "$ARTIFACT_DIR/etc/chissu-pam/config.toml"
 cp "$REPO_ROOT/build/package/assets/usr/share/chissu-pam/install-common.sh" "$ARTIFACT_DIR/usr/share/chissu-pam/install-common.sh"
@@ -163,7 +163,7 @@ if [[ $SKIP_BUILD -eq 0 ]]; then
   pushd "$REPO_ROOT" >/dev/null
   CARGO_HOME="$REPO_ROOT/.cargo-home" \
     CARGO_TARGET_DIR="$WORK_ROOT/cargo-target" \
-    cargo build --release -p chissu-cli -p pam-chissu
+    cargo build --release -p chissu-cli -p chissu-authd -p pam-chissu
   popd >/dev/null
 else
   log "Skipping cargo build (per --skip-build)"
@@ -173,14 +173,16 @@ fi
 
 CARGO_BUILD_DIR="$WORK_ROOT/cargo-target"
 BIN_SRC="$CARGO_BUILD_DIR/release/chissu-cli"
+AUTHD_SRC="$CARGO_BUILD_DIR/release/chissu-authd"
 PAM_SRC="$CARGO_BUILD_DIR/release/libpam_chissu.so"
-if [[ ! -f "$BIN_SRC" || ! -f "$PAM_SRC" ]]; then
+if [[ ! -f "$BIN_SRC" || ! -f "$AUTHD_SRC" || ! -f "$PAM_SRC" ]]; then
   echo "Release binaries missing; run without --skip-build" >&2
   exit 1
 fi
 
 log "Staging artifacts"
 mkdir -p "$ARTIFACT_DIR/usr/bin" \
+         "$ARTIFACT_DIR/usr/lib/systemd/system" \
          "$ARTIFACT_DIR/usr/lib64/security" \
          "$ARTIFACT_DIR/etc/chissu-pam" \
          "$ARTIFACT_DIR/usr/share/doc/chissu-pam" \
@@ -190,6 +192,9 @@ mkdir -p "$ARTIFACT_DIR/usr/bin" \
          "$ARTIFACT_DIR/usr/share/chissu-pam"
 
 cp "$BIN_SRC" "$ARTIFACT_DIR/usr/bin/chissu-cli"
+cp "$AUTHD_SRC" "$ARTIFACT_DIR/usr/bin/chissu-authd"
+cp "$REPO_ROOT/build/package/assets/usr/lib/systemd/system/chissu-authd.socket" "$ARTIFACT_DIR/usr/lib/systemd/system/chissu-authd.socket"
+cp "$REPO_ROOT/build/package/assets/usr/lib/systemd/system/chissu-authd.service" "$ARTIFACT_DIR/usr/lib/systemd/system/chissu-authd.service"
 cp "$PAM_SRC" "$ARTIFACT_DIR/usr/lib64/security/libpam_chissu.so"
 cp "$REPO_ROOT/build/package/assets/etc/chissu-pam/config.toml" "$ARTIFACT_DIR/etc/chissu-pam/config.toml"
 cp "$REPO_ROOT/build/package/assets/usr/share/doc/chissu-pam/README.RPM" "$ARTIFACT_DIR/usr/share/doc/chissu-pam/README.RPM"
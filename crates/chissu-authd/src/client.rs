@@ -0,0 +1,166 @@
+use std::io::{self, BufReader};
+use std::os::fd::AsRawFd;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chissu_face_core::faces::{VerifyObserver, VerifyOutcome};
+
+use crate::protocol::{peer_uid, read_message, write_message, Reply, VerifyRequest};
+use crate::ClientError;
+
+/// Extra time granted beyond the capture timeout before the client gives up.
+const REPLY_GRACE: Duration = Duration::from_secs(5);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Longest wait for a reply between two cancellation checks.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+pub struct AuthdClient {
+    socket: PathBuf,
+    trusted_uid: u32,
+}
+
+impl AuthdClient {
+    pub fn new(socket: impl Into<PathBuf>) -> Self {
+        Self {
+            socket: socket.into(),
+            trusted_uid: 0,
+        }
+    }
+
+    /// Overrides the uid the daemon must run as (root by default).
+    pub fn with_trusted_uid(mut self, uid: u32) -> Self {
+        self.trusted_uid = uid;
+        self
+    }
+
+    pub fn socket(&self) -> &Path {
+        &self.socket
+    }
+
+    /// Runs a verification in the daemon, relaying its progress events to
+    /// `observer`. Returns [`ClientError::Unavailable`] when no trusted daemon
+    /// is listening so callers can fall back to in-process matching.
+    ///
+    /// Cancellation is checked after each event and at least every
+    /// [`CANCEL_POLL_INTERVAL`] while waiting for one; the connection is then
+    /// closed, which makes the daemon stop capturing on its next frame.
+    pub fn verify(
+        &self,
+        request: &VerifyRequest,
+        observer: &mut dyn VerifyObserver,
+    ) -> Result<VerifyOutcome, ClientError> {
+        let stream = self.connect()?;
+        let reply_window = Duration::from_millis(request.capture_timeout_millis) + REPLY_GRACE;
+        let deadline = Instant::now() + reply_window;
+        stream
+            .set_read_timeout(Some(reply_window))
+            .and_then(|_| stream.set_write_timeout(Some(WRITE_TIMEOUT)))
+            .map_err(protocol_error)?;
+
+        let mut writer = &stream;
+        write_message(&mut writer, request).map_err(protocol_error)?;
+
+        let mut reader = BufReader::new(&stream);
+        loop {
+            wait_for_reply(&reader, deadline, &*observer)?;
+            match read_message::<Reply>(&mut reader).map_err(protocol_error)? {
+                Some(Reply::Event { event }) => {
+                    observer.on_event(&event);
+                    if observer.is_cancelled() {
+                        return Err(ClientError::Cancelled);
+                    }
+                }
+                Some(Reply::Outcome {
+                    success,
+                    best_similarity,
+                    frames_captured,
+                    failure,
+                    frame_scores,
+                    matched_face,
+                }) => {
+                    return Ok(VerifyOutcome {
+                        success,
+                        best_similarity: best_similarity.unwrap_or(request.metric.worst()),
+                        frames_captured,
+                        failure,
+                        frame_scores,
+                        matched_face,
+                    })
+                }
+                Some(Reply::Error {
+                    message,
+                    device_fault: Some(fault),
+                }) => return Err(ClientError::Device { fault, message }),
+                Some(Reply::Error { message, .. }) => return Err(ClientError::Daemon(message)),
+                None => {
+                    return Err(ClientError::Protocol(
+                        "daemon closed the connection without a result".into(),
+                    ))
+                }
+            }
+        }
+    }
+
+    fn connect(&self) -> Result<UnixStream, ClientError> {
+        let stream = UnixStream::connect(&self.socket).map_err(|err| self.unavailable(err))?;
+        let uid = peer_uid(&stream).map_err(|err| self.unavailable(err))?;
+        if uid != self.trusted_uid {
+            return Err(self.unavailable(format!(
+                "socket owned by uid {uid}, expected {}",
+                self.trusted_uid
+            )));
+        }
+        Ok(stream)
+    }
+
+    fn unavailable(&self, reason: impl ToString) -> ClientError {
+        ClientError::Unavailable {
+            path: self.socket.clone(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Waits until `reader` has a reply to read, in slices of at most
+/// [`CANCEL_POLL_INTERVAL`] so a cancellation is noticed while the daemon is
+/// quiet.
+fn wait_for_reply(
+    reader: &BufReader<&UnixStream>,
+    deadline: Instant,
+    observer: &dyn VerifyObserver,
+) -> Result<(), ClientError> {
+    while reader.buffer().is_empty() {
+        if observer.is_cancelled() {
+            return Err(ClientError::Cancelled);
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(ClientError::Protocol(
+                "timed out waiting for the daemon".into(),
+            ));
+        }
+        let mut fd = libc::pollfd {
+            fd: reader.get_ref().as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let millis = remaining.min(CANCEL_POLL_INTERVAL).as_millis().max(1) as libc::c_int;
+        // SAFETY: `fd` is one valid pollfd that outlives the call.
+        let rc = unsafe { libc::poll(&mut fd, 1, millis) };
+        if rc > 0 {
+            return Ok(());
+        }
+        if rc < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(protocol_error(err));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn protocol_error(err: io::Error) -> ClientError {
+    ClientError::Protocol(err.to_string())
+}
@@ -0,0 +1,38 @@
+pub mod client;
+pub mod protocol;
+pub mod server;
+
+use std::io;
+use std::path::PathBuf;
+
+use chissu_config::ConfigError;
+use chissu_face_core::errors::{AppError, DeviceFault};
+use thiserror::Error;
+
+pub use client::AuthdClient;
+pub use protocol::{Reply, RequestedDevice, VerifyRequest, PROTOCOL_VERSION};
+pub use server::Server;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("chissu-authd unavailable at {path}: {reason}", path = path.display())]
+    Unavailable { path: PathBuf, reason: String },
+    #[error("chissu-authd protocol error: {0}")]
+    Protocol(String),
+    #[error("chissu-authd reported: {0}")]
+    Daemon(String),
+    #[error("chissu-authd reported: {message}")]
+    Device { fault: DeviceFault, message: String },
+    #[error("verification cancelled")]
+    Cancelled,
+}
+
+#[derive(Debug, Error)]
+pub enum DaemonError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Core(#[from] AppError),
+    #[error("socket setup failed: {0}")]
+    Socket(#[from] io::Error),
+}
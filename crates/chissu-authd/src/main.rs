@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use chissu_authd::server::{bind_listener, systemd_listener};
+use chissu_authd::{DaemonError, Server};
+use chissu_face_core::faces::{
+    DlibBackend, EnvModelPathResolver, FaceExtractionConfig, ModelPathResolver,
+};
+use tracing::{error, info};
+use tracing_subscriber::EnvFilter;
+
+fn main() -> ExitCode {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            error!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), DaemonError> {
+    let loaded = chissu_config::load_resolved_config()?;
+    if let Some(path) = &loaded.source {
+        info!("loaded configuration from {}", path.display());
+    }
+    let config = loaded.resolved;
+
+    let models = EnvModelPathResolver.resolve(&FaceExtractionConfig {
+        image: PathBuf::new(),
+        landmark_model: config.landmark_model.clone(),
+        encoder_model: config.encoder_model.clone(),
+        output: None,
+        jitters: config.jitters,
+        selection: Default::default(),
+    })?;
+    let backend = DlibBackend::new(&models)?;
+    info!("dlib models loaded");
+
+    let listener = match systemd_listener() {
+        Some(listener) => {
+            info!("using socket passed by systemd");
+            listener
+        }
+        None => {
+            info!("listening on {}", config.authd_socket.display());
+            bind_listener(&config.authd_socket)?
+        }
+    };
+
+    let mut server = Server::new(Box::new(backend), config.authd_camera_idle);
+    server.run(&listener)?;
+    Ok(())
+}
@@ -0,0 +1,201 @@
+//! Newline-delimited JSON exchanged over the daemon socket: the client sends one
+//! [`VerifyRequest`], the daemon streams [`Reply::Event`] lines and finishes with
+//! either [`Reply::Outcome`] or [`Reply::Error`].
+
+use std::io::{self, BufRead, Write};
+use std::mem;
+use std::os::fd::AsRawFd;
+use std::os::unix::net::UnixStream;
+
+use chissu_face_core::device_pin::DeviceTrustPolicy;
+use chissu_face_core::errors::DeviceFault;
+use chissu_face_core::faces::{
+    AntiSpoofSensitivity, FaceEmbeddingRecord, FaceSelectionPolicy, FusionPolicy, LivenessMode,
+    SimilarityMetric, VerifyEvent, VerifyFailure, VerifyOutcome,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Bumped whenever a request field changes how matching behaves, so an older
+/// daemon rejects requests it would otherwise evaluate too leniently.
+pub const PROTOCOL_VERSION: u32 = 11;
+
+/// A camera the daemon may open, with its capture settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestedDevice {
+    pub video_device: String,
+    pub pixel_format: String,
+    pub warmup_frames: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyRequest {
+    pub version: u32,
+    pub user: String,
+    pub service: String,
+    /// Cameras in order of preference; absent or busy ones are skipped.
+    pub devices: Vec<RequestedDevice>,
+    pub similarity_threshold: f64,
+    #[serde(default)]
+    pub metric: SimilarityMetric,
+    pub capture_timeout_millis: u64,
+    pub frame_interval_millis: u64,
+    pub jitters: u32,
+    pub liveness: LivenessMode,
+    pub anti_spoof: AntiSpoofSensitivity,
+    #[serde(default)]
+    pub face_selection: FaceSelectionPolicy,
+    #[serde(default)]
+    pub fusion: FusionPolicy,
+    #[serde(default)]
+    pub device_trust: DeviceTrustPolicy,
+    #[serde(default)]
+    pub require_ir: bool,
+    #[serde(default)]
+    pub stream_integrity: bool,
+    pub enrolled: Vec<Vec<f64>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Reply {
+    Event {
+        event: VerifyEvent,
+    },
+    Outcome {
+        success: bool,
+        /// `None` when no face was scored (JSON cannot carry negative infinity).
+        best_similarity: Option<f64>,
+        frames_captured: usize,
+        failure: Option<VerifyFailure>,
+        #[serde(default)]
+        frame_scores: Vec<Option<f64>>,
+        /// The accepted face, so the client can run an adaptive update.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        matched_face: Option<FaceEmbeddingRecord>,
+    },
+    Error {
+        message: String,
+        /// Set when the camera itself was unavailable, so the client can
+        /// apply its device policy.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        device_fault: Option<DeviceFault>,
+    },
+}
+
+impl Reply {
+    pub fn outcome(outcome: &VerifyOutcome) -> Self {
+        Reply::Outcome {
+            success: outcome.success,
+            best_similarity: outcome
+                .best_similarity
+                .is_finite()
+                .then_some(outcome.best_similarity),
+            frames_captured: outcome.frames_captured,
+            failure: outcome.failure,
+            frame_scores: outcome.frame_scores.clone(),
+            matched_face: outcome.matched_face.clone(),
+        }
+    }
+}
+
+pub fn write_message<T: Serialize>(writer: &mut impl Write, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    writer.write_all(&line)?;
+    writer.flush()
+}
+
+/// Reads one message; `Ok(None)` means the peer closed the connection.
+pub fn read_message<T: DeserializeOwned>(reader: &mut impl BufRead) -> io::Result<Option<T>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    serde_json::from_str(&line)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Returns the uid of the process on the other end of `stream` (`SO_PEERCRED`).
+pub fn peer_uid(stream: &UnixStream) -> io::Result<u32> {
+    let mut cred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = mem::size_of::<libc::ucred>() as libc::socklen_t;
+    // SAFETY: `cred` and `len` are valid for writes and sized for SO_PEERCRED.
+    let rc = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut cred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    if rc == 0 {
+        Ok(cred.uid)
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn outcome_reply_round_trips_without_score() {
+        let outcome = VerifyOutcome {
+            success: false,
+            best_similarity: SimilarityMetric::Euclidean.worst(),
+            frames_captured: 3,
+            failure: Some(VerifyFailure::NoFaceDetected),
+            frame_scores: vec![None, Some(0.25), None],
+            matched_face: None,
+        };
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &Reply::outcome(&outcome)).unwrap();
+        assert_eq!(buffer.last(), Some(&b'\n'));
+
+        let reply: Reply = read_message(&mut Cursor::new(buffer)).unwrap().unwrap();
+        match reply {
+            Reply::Outcome {
+                success,
+                best_similarity,
+                frames_captured,
+                failure,
+                frame_scores,
+                matched_face,
+            } => {
+                assert!(!success);
+                assert_eq!(best_similarity, None);
+                assert_eq!(frames_captured, 3);
+                assert_eq!(failure, Some(VerifyFailure::NoFaceDetected));
+                assert_eq!(frame_scores, vec![None, Some(0.25), None]);
+                assert_eq!(matched_face, None);
+            }
+            other => panic!("unexpected reply {other:?}"),
+        }
+    }
+
+    #[test]
+    fn read_message_reports_eof_and_garbage() {
+        let eof: Option<Reply> = read_message(&mut Cursor::new(Vec::new())).unwrap();
+        assert!(eof.is_none());
+
+        let err = read_message::<Reply>(&mut Cursor::new(b"not json\n".to_vec())).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn peer_uid_matches_current_user() {
+        let (left, _right) = UnixStream::pair().unwrap();
+        // SAFETY: geteuid has no preconditions.
+        let euid = unsafe { libc::geteuid() };
+        assert_eq!(peer_uid(&left).unwrap(), euid);
+    }
+}
@@ -0,0 +1,681 @@
+use std::env;
+use std::fs::{self, DirBuilder, Permissions};
+use std::io::{self, BufReader};
+use std::os::fd::{AsRawFd, FromRawFd};
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use chissu_face_core::capture::{
+    open_first_available, CaptureConfig, CaptureSession, DeviceLocator, FirstAvailable,
+    SkippedDevice,
+};
+use chissu_face_core::errors::{AppError, AppResult};
+use chissu_face_core::faces::{
+    verify_live, FaceEmbeddingBackend, FrameSource, VerifyEvent, VerifyFailure, VerifyObserver,
+    VerifyOutcome, VerifySettings,
+};
+use tracing::{debug, info, warn};
+
+use crate::protocol::{
+    peer_uid, read_message, write_message, Reply, RequestedDevice, VerifyRequest, PROTOCOL_VERSION,
+};
+
+/// First file descriptor passed by systemd socket activation.
+const SD_LISTEN_FDS_START: i32 = 3;
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long to drain a reused stream so frames queued while idle are dropped.
+const STALE_FRAME_DRAIN: Duration = Duration::from_millis(150);
+
+pub trait FrameSourceOpener {
+    fn open(&mut self, config: &CaptureConfig) -> AppResult<Box<dyn FrameSource>>;
+}
+
+pub struct V4lFrameSourceOpener;
+
+impl FrameSourceOpener for V4lFrameSourceOpener {
+    fn open(&mut self, config: &CaptureConfig) -> AppResult<Box<dyn FrameSource>> {
+        let mut session = CaptureSession::open(config)?;
+        for line in session.take_logs() {
+            debug!("{line}");
+        }
+        Ok(Box::new(session))
+    }
+}
+
+struct WarmSource {
+    key: RequestedDevice,
+    source: Box<dyn FrameSource>,
+    last_used: Instant,
+}
+
+pub struct Server {
+    backend: Box<dyn FaceEmbeddingBackend>,
+    opener: Box<dyn FrameSourceOpener>,
+    camera_idle: Duration,
+    allowed_uid: u32,
+    warm: Option<WarmSource>,
+}
+
+impl Server {
+    pub fn new(backend: Box<dyn FaceEmbeddingBackend>, camera_idle: Duration) -> Self {
+        Self {
+            backend,
+            opener: Box::new(V4lFrameSourceOpener),
+            camera_idle,
+            allowed_uid: 0,
+            warm: None,
+        }
+    }
+
+    pub fn with_opener(mut self, opener: Box<dyn FrameSourceOpener>) -> Self {
+        self.opener = opener;
+        self
+    }
+
+    /// Overrides the only uid allowed to submit requests (root by default).
+    pub fn with_allowed_uid(mut self, uid: u32) -> Self {
+        self.allowed_uid = uid;
+        self
+    }
+
+    pub fn run(&mut self, listener: &UnixListener) -> io::Result<()> {
+        loop {
+            let idle_timeout = self.warm.as_ref().map(|warm| {
+                (warm.last_used + self.camera_idle).saturating_duration_since(Instant::now())
+            });
+            if !wait_readable(listener, idle_timeout)? {
+                self.release_idle_camera();
+                continue;
+            }
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    if let Err(err) = self.handle_connection(stream) {
+                        warn!("request failed: {err}");
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    pub fn handle_connection(&mut self, stream: UnixStream) -> io::Result<()> {
+        let uid = peer_uid(&stream)?;
+        if uid != self.allowed_uid {
+            warn!("rejecting connection from uid {uid}");
+            return Ok(());
+        }
+        stream.set_read_timeout(Some(REQUEST_READ_TIMEOUT))?;
+
+        let request: VerifyRequest = match read_message(&mut BufReader::new(&stream))? {
+            Some(request) => request,
+            None => return Ok(()),
+        };
+        let mut writer = &stream;
+        if request.version != PROTOCOL_VERSION {
+            return write_message(
+                &mut writer,
+                &Reply::Error {
+                    message: format!(
+                        "unsupported protocol version {} (expected {PROTOCOL_VERSION})",
+                        request.version
+                    ),
+                    device_fault: None,
+                },
+            );
+        }
+
+        let devices: Vec<&str> = request
+            .devices
+            .iter()
+            .map(|device| device.video_device.as_str())
+            .collect();
+        info!(
+            user = %request.user,
+            service = %request.service,
+            devices = %devices.join(","),
+            "verify request"
+        );
+        let mut relay = EventRelay {
+            stream: &stream,
+            disconnected: false,
+        };
+        let reply = match self.verify(&request, &mut relay) {
+            Ok(outcome) => {
+                info!(
+                    success = outcome.success,
+                    frames = outcome.frames_captured,
+                    best_similarity = outcome.best_similarity,
+                    "verify finished"
+                );
+                Reply::outcome(&outcome)
+            }
+            Err(err) => {
+                warn!("verify failed: {err}");
+                Reply::Error {
+                    message: err.to_string(),
+                    device_fault: err.device_fault(),
+                }
+            }
+        };
+        let mut writer = &stream;
+        write_message(&mut writer, &reply)
+    }
+
+    /// Closes the camera once it has been idle for the configured window.
+    pub fn release_idle_camera(&mut self) {
+        if let Some(warm) = &self.warm {
+            if warm.last_used.elapsed() >= self.camera_idle {
+                debug!(device = %warm.key.video_device, "closing idle camera");
+                self.warm = None;
+            }
+        }
+    }
+
+    fn verify(
+        &mut self,
+        request: &VerifyRequest,
+        relay: &mut dyn VerifyObserver,
+    ) -> AppResult<VerifyOutcome> {
+        let deadline = Instant::now() + Duration::from_millis(request.capture_timeout_millis);
+        let settings = VerifySettings {
+            similarity_threshold: request.similarity_threshold,
+            metric: request.metric,
+            frame_interval: Duration::from_millis(request.frame_interval_millis),
+            jitters: request.jitters,
+            liveness: request.liveness,
+            anti_spoof: request.anti_spoof,
+            selection: request.face_selection,
+            fusion: request.fusion,
+            require_ir: request.require_ir,
+            stream_integrity: request.stream_integrity,
+        };
+        let skipped = self.prepare_source(request)?;
+        let Some(warm) = self.warm.as_mut() else {
+            return Err(AppError::FrameProcessing(
+                "capture source unavailable".into(),
+            ));
+        };
+        info!(device = %warm.key.video_device, skipped = skipped.len(), "camera selected");
+        relay.on_event(&VerifyEvent::DeviceSelected {
+            device: warm.key.video_device.clone(),
+            skipped,
+        });
+        if let Err(rejection) = request.device_trust.check(warm.source.device()) {
+            warn!(device = %warm.key.video_device, "refusing camera: {rejection}");
+            return Ok(VerifyOutcome::rejected(
+                VerifyFailure::UntrustedDevice,
+                request.metric,
+            ));
+        }
+        let result = verify_live(
+            warm.source.as_mut(),
+            self.backend.as_ref(),
+            &request.enrolled,
+            &settings,
+            deadline,
+            relay,
+        );
+        match &result {
+            Ok(_) => warm.last_used = Instant::now(),
+            // The stream may be wedged; reopen it on the next request.
+            Err(_) => self.warm = None,
+        }
+        result
+    }
+
+    /// Makes the first available device of the request the warm source and
+    /// returns the entries skipped on the way.
+    fn prepare_source(&mut self, request: &VerifyRequest) -> AppResult<Vec<SkippedDevice>> {
+        let reusable = match self.warm.as_mut() {
+            Some(warm) if is_first_present(&request.devices, &warm.key) => warm
+                .source
+                .discard_until(Instant::now() + STALE_FRAME_DRAIN, &|| false)
+                .is_ok(),
+            _ => false,
+        };
+        if reusable {
+            return Ok(Vec::new());
+        }
+        self.warm = None;
+        let configs: Vec<CaptureConfig> = request.devices.iter().map(capture_config).collect();
+        let FirstAvailable {
+            index,
+            opened: source,
+            skipped,
+        } = open_first_available(&configs, |config| self.opener.open(config))?;
+        for entry in &skipped {
+            debug!(device = %entry.device, "skipping camera: {}", entry.fault.describe());
+        }
+        self.warm = Some(WarmSource {
+            key: request.devices[index].clone(),
+            source,
+            last_used: Instant::now(),
+        });
+        Ok(skipped)
+    }
+}
+
+fn capture_config(device: &RequestedDevice) -> CaptureConfig {
+    CaptureConfig {
+        device: DeviceLocator::from_option(Some(device.video_device.clone())),
+        pixel_format: device.pixel_format.clone(),
+        width: None,
+        height: None,
+        exposure: None,
+        gain: None,
+        auto_exposure: false,
+        auto_gain: false,
+        warmup_frames: device.warmup_frames,
+        output: None,
+    }
+}
+
+/// Whether `key` is the device the request would open now: it is listed,
+/// and no entry ahead of it has a device node, e.g. because the dock with
+/// the preferred camera was unplugged.
+fn is_first_present(devices: &[RequestedDevice], key: &RequestedDevice) -> bool {
+    match devices.iter().position(|device| device == key) {
+        Some(index) => devices[..index].iter().all(|device| {
+            !DeviceLocator::from_option(Some(device.video_device.clone()))
+                .path()
+                .is_ok_and(|path| path.exists())
+        }),
+        None => false,
+    }
+}
+
+/// Returns the listener handed over by systemd socket activation, if any.
+pub fn systemd_listener() -> Option<UnixListener> {
+    let pid: u32 = env::var("LISTEN_PID").ok()?.parse().ok()?;
+    let fds: i32 = env::var("LISTEN_FDS").ok()?.parse().ok()?;
+    if pid != std::process::id() || fds < 1 {
+        return None;
+    }
+    // SAFETY: systemd guarantees LISTEN_FDS descriptors starting at fd 3 belong to us.
+    Some(unsafe { UnixListener::from_raw_fd(SD_LISTEN_FDS_START) })
+}
+
+/// Binds `path` with root-only permissions, replacing a stale socket file.
+pub fn bind_listener(path: &Path) -> io::Result<UnixListener> {
+    if let Some(parent) = path.parent() {
+        if !parent.exists() {
+            DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(parent)?;
+        }
+    }
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => fs::remove_file(path)?,
+        Ok(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} exists and is not a socket", path.display()),
+            ))
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+    let listener = UnixListener::bind(path)?;
+    fs::set_permissions(path, Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+fn wait_readable(listener: &UnixListener, timeout: Option<Duration>) -> io::Result<bool> {
+    let mut fds = libc::pollfd {
+        fd: listener.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let timeout_ms = timeout
+        .map(|t| t.as_millis().min(i32::MAX as u128) as i32)
+        .unwrap_or(-1);
+    // SAFETY: `fds` is a single valid pollfd for the duration of the call.
+    let rc = unsafe { libc::poll(&mut fds, 1, timeout_ms) };
+    if rc < 0 {
+        let err = io::Error::last_os_error();
+        return if err.kind() == io::ErrorKind::Interrupted {
+            Ok(false)
+        } else {
+            Err(err)
+        };
+    }
+    Ok(rc > 0)
+}
+
+/// Streams progress events to the client and stops matching once it hangs up,
+/// e.g. after the user answered a concurrent password prompt.
+struct EventRelay<'a> {
+    stream: &'a UnixStream,
+    disconnected: bool,
+}
+
+impl VerifyObserver for EventRelay<'_> {
+    fn on_event(&mut self, event: &VerifyEvent) {
+        let mut writer = self.stream;
+        let reply = Reply::Event {
+            event: event.clone(),
+        };
+        if write_message(&mut writer, &reply).is_err() {
+            self.disconnected = true;
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.disconnected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::AuthdClient;
+    use crate::ClientError;
+    use chissu_face_core::device_pin::{DeviceIdentity, DeviceTrustPolicy};
+    use chissu_face_core::errors::DeviceFault;
+    use chissu_face_core::faces::{
+        AntiSpoofSensitivity, BoundingBox, FaceEmbeddingRecord, LivenessMode,
+    };
+    use image::{GrayImage, RgbImage};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    struct EndlessFrames;
+
+    impl FrameSource for EndlessFrames {
+        fn next_frame(&mut self) -> AppResult<GrayImage> {
+            Ok(GrayImage::new(2, 2))
+        }
+
+        fn discard_until(
+            &mut self,
+            _until: Instant,
+            _cancelled: &dyn Fn() -> bool,
+        ) -> AppResult<usize> {
+            Ok(0)
+        }
+    }
+
+    struct CountingOpener {
+        opens: Arc<AtomicUsize>,
+    }
+
+    impl FrameSourceOpener for CountingOpener {
+        fn open(&mut self, _config: &CaptureConfig) -> AppResult<Box<dyn FrameSource>> {
+            self.opens.fetch_add(1, Ordering::SeqCst);
+            Ok(Box::new(EndlessFrames))
+        }
+    }
+
+    struct FixedBackend;
+
+    impl FaceEmbeddingBackend for FixedBackend {
+        fn extract(&self, _image: &RgbImage, _jitters: u32) -> AppResult<Vec<FaceEmbeddingRecord>> {
+            Ok(vec![FaceEmbeddingRecord {
+                bounding_box: BoundingBox {
+                    left: 0,
+                    top: 0,
+                    right: 1,
+                    bottom: 1,
+                },
+                embedding: vec![1.0, 0.0],
+                landmarks: Vec::new(),
+            }])
+        }
+    }
+
+    fn euid() -> u32 {
+        // SAFETY: geteuid has no preconditions.
+        unsafe { libc::geteuid() }
+    }
+
+    fn request(enrolled: Vec<Vec<f64>>) -> VerifyRequest {
+        VerifyRequest {
+            version: PROTOCOL_VERSION,
+            user: "alice".into(),
+            service: "sudo".into(),
+            devices: vec![RequestedDevice {
+                video_device: "/dev/video0".into(),
+                pixel_format: "GREY".into(),
+                warmup_frames: 0,
+            }],
+            similarity_threshold: 0.9,
+            metric: Default::default(),
+            capture_timeout_millis: 50,
+            frame_interval_millis: 0,
+            jitters: 1,
+            liveness: LivenessMode::Off,
+            anti_spoof: AntiSpoofSensitivity::Off,
+            face_selection: Default::default(),
+            fusion: Default::default(),
+            device_trust: Default::default(),
+            require_ir: false,
+            stream_integrity: true,
+            enrolled,
+        }
+    }
+
+    fn test_server(opens: Arc<AtomicUsize>, camera_idle: Duration) -> Server {
+        Server::new(Box::new(FixedBackend), camera_idle)
+            .with_opener(Box::new(CountingOpener { opens }))
+            .with_allowed_uid(euid())
+    }
+
+    #[test]
+    fn client_receives_events_and_outcome_from_daemon() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("run").join("authd.sock");
+        let listener = bind_listener(&socket).unwrap();
+        let mode = fs::metadata(&socket).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let opens = Arc::new(AtomicUsize::new(0));
+        let server_opens = opens.clone();
+        let handle = thread::spawn(move || {
+            let mut server = test_server(server_opens, Duration::from_secs(60));
+            for _ in 0..2 {
+                let (stream, _) = listener.accept().unwrap();
+                server.handle_connection(stream).unwrap();
+            }
+        });
+
+        let client = AuthdClient::new(&socket).with_trusted_uid(euid());
+        let mut events = Vec::new();
+        let outcome = client
+            .verify(
+                &request(vec![vec![1.0, 0.0]]),
+                &mut |event: &VerifyEvent| events.push(event.clone()),
+            )
+            .unwrap();
+        assert!(outcome.success);
+        assert!(matches!(events.last(), Some(VerifyEvent::Matched { .. })));
+
+        let outcome = client
+            .verify(&request(vec![vec![0.0, 1.0]]), &mut |_: &VerifyEvent| {})
+            .unwrap();
+        assert!(!outcome.success);
+        assert_eq!(outcome.failure, Some(VerifyFailure::ThresholdNotReached));
+
+        handle.join().unwrap();
+        assert_eq!(opens.load(Ordering::SeqCst), 1, "camera should stay warm");
+    }
+
+    /// Reports cancellation once `at` has passed.
+    struct CancelAt(Instant);
+
+    impl VerifyObserver for CancelAt {
+        fn on_event(&mut self, _event: &VerifyEvent) {}
+
+        fn is_cancelled(&self) -> bool {
+            Instant::now() >= self.0
+        }
+    }
+
+    #[test]
+    fn client_stops_waiting_on_a_quiet_daemon_when_cancelled() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("authd.sock");
+        // Accepts nothing and never replies, like a daemon stuck on a camera.
+        let _listener = bind_listener(&socket).unwrap();
+        let client = AuthdClient::new(&socket).with_trusted_uid(euid());
+
+        let started = Instant::now();
+        let err = client
+            .verify(
+                &request(Vec::new()),
+                &mut CancelAt(started + Duration::from_millis(100)),
+            )
+            .unwrap_err();
+        assert!(matches!(err, ClientError::Cancelled));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn client_reports_unavailable_without_daemon() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = AuthdClient::new(dir.path().join("missing.sock"));
+        let err = client
+            .verify(&request(Vec::new()), &mut |_: &VerifyEvent| {})
+            .unwrap_err();
+        assert!(matches!(err, ClientError::Unavailable { .. }));
+    }
+
+    #[test]
+    fn client_refuses_daemon_running_as_unexpected_uid() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("authd.sock");
+        let _listener = bind_listener(&socket).unwrap();
+        let client = AuthdClient::new(&socket).with_trusted_uid(euid().wrapping_add(1));
+        let err = client
+            .verify(&request(Vec::new()), &mut |_: &VerifyEvent| {})
+            .unwrap_err();
+        assert!(matches!(err, ClientError::Unavailable { reason, .. } if reason.contains("uid")));
+    }
+
+    #[test]
+    fn server_rejects_unauthorised_peers() {
+        let (client_end, server_end) = UnixStream::pair().unwrap();
+        let mut server = test_server(Arc::new(AtomicUsize::new(0)), Duration::ZERO)
+            .with_allowed_uid(euid().wrapping_add(1));
+        server.handle_connection(server_end).unwrap();
+
+        let reply: Option<Reply> = read_message(&mut BufReader::new(&client_end)).unwrap();
+        assert!(reply.is_none());
+    }
+
+    #[test]
+    fn idle_camera_is_released_and_reopened() {
+        let opens = Arc::new(AtomicUsize::new(0));
+        let mut server = test_server(opens.clone(), Duration::ZERO);
+        let mut noop = |_: &VerifyEvent| {};
+
+        server
+            .verify(&request(vec![vec![1.0, 0.0]]), &mut noop)
+            .unwrap();
+        assert!(server.warm.is_some());
+        server.release_idle_camera();
+        assert!(server.warm.is_none());
+
+        server
+            .verify(&request(vec![vec![1.0, 0.0]]), &mut noop)
+            .unwrap();
+        assert_eq!(opens.load(Ordering::SeqCst), 2);
+    }
+
+    /// Reports every device except `/dev/video0` as absent.
+    struct DockedOpener {
+        opened: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl FrameSourceOpener for DockedOpener {
+        fn open(&mut self, config: &CaptureConfig) -> AppResult<Box<dyn FrameSource>> {
+            let device = config.device.path()?.display().to_string();
+            if device != "/dev/video0" {
+                return Err(AppError::Io(io::Error::from_raw_os_error(libc::ENOENT)));
+            }
+            self.opened.lock().unwrap().push(device);
+            Ok(Box::new(EndlessFrames))
+        }
+    }
+
+    #[test]
+    fn absent_devices_fall_back_and_the_fallback_stays_warm() {
+        let dir = tempfile::tempdir().unwrap();
+        let opened = Arc::new(Mutex::new(Vec::new()));
+        let mut server = Server::new(Box::new(FixedBackend), Duration::from_secs(60)).with_opener(
+            Box::new(DockedOpener {
+                opened: opened.clone(),
+            }),
+        );
+        let mut docked = request(vec![vec![1.0, 0.0]]);
+        let dock_camera = dir.path().join("dock-ir").display().to_string();
+        docked.devices.insert(
+            0,
+            RequestedDevice {
+                video_device: dock_camera.clone(),
+                pixel_format: "GREY".into(),
+                warmup_frames: 0,
+            },
+        );
+
+        let mut events = Vec::new();
+        let outcome = server
+            .verify(&docked, &mut |event: &VerifyEvent| {
+                events.push(event.clone())
+            })
+            .unwrap();
+        assert!(outcome.success);
+        assert_eq!(
+            events.first(),
+            Some(&VerifyEvent::DeviceSelected {
+                device: "/dev/video0".into(),
+                skipped: vec![SkippedDevice {
+                    device: dock_camera,
+                    fault: DeviceFault::Absent,
+                }],
+            })
+        );
+
+        server.verify(&docked, &mut |_: &VerifyEvent| {}).unwrap();
+        assert_eq!(opened.lock().unwrap().len(), 1, "fallback should stay warm");
+    }
+
+    #[test]
+    fn pinned_request_is_refused_without_device_identity() {
+        let mut server = test_server(Arc::new(AtomicUsize::new(0)), Duration::from_secs(60));
+        let mut pinned = request(vec![vec![1.0, 0.0]]);
+        pinned.device_trust = DeviceTrustPolicy {
+            pinned: Some(DeviceIdentity {
+                driver: "uvcvideo".into(),
+                card: "IR Camera".into(),
+                bus_info: "usb-0000:00:14.0-5".into(),
+            }),
+            denied_drivers: Vec::new(),
+        };
+
+        let outcome = server.verify(&pinned, &mut |_: &VerifyEvent| {}).unwrap();
+        assert!(!outcome.success);
+        assert_eq!(outcome.frames_captured, 0);
+        assert_eq!(outcome.failure, Some(VerifyFailure::UntrustedDevice));
+    }
+
+    #[test]
+    fn protocol_version_mismatch_is_reported() {
+        let (client_end, server_end) = UnixStream::pair().unwrap();
+        let mut stale = request(Vec::new());
+        stale.version = PROTOCOL_VERSION + 1;
+        write_message(&mut &client_end, &stale).unwrap();
+
+        let mut server = test_server(Arc::new(AtomicUsize::new(0)), Duration::ZERO);
+        server.handle_connection(server_end).unwrap();
+
+        let reply: Reply = read_message(&mut BufReader::new(&client_end))
+            .unwrap()
+            .unwrap();
+        assert!(matches!(reply, Reply::Error { message, .. } if message.contains("version")));
+    }
+}
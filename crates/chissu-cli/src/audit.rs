@@ -0,0 +1,140 @@
+use std::path::PathBuf;
+
+use chissu_face_core::audit::{AuditFilter, AuditLog, AuditOutcome, AuditRecord};
+
+use crate::cli::{AuditOutcomeArg, AuditShowArgs};
+use crate::config::{self, AuditSettings};
+use crate::errors::{AppError, AppResult};
+
+#[derive(Debug, Clone)]
+pub struct AuditShowSummary {
+    pub log_path: PathBuf,
+    /// Matching records, oldest first.
+    pub records: Vec<AuditRecord>,
+    /// Lines that could not be parsed as audit records.
+    pub skipped_lines: usize,
+}
+
+pub fn run_audit_show(args: &AuditShowArgs) -> AppResult<AuditShowSummary> {
+    let settings = config::load_audit_settings()?;
+    run_audit_show_with(args, &settings)
+}
+
+pub fn run_audit_show_with(
+    args: &AuditShowArgs,
+    settings: &AuditSettings,
+) -> AppResult<AuditShowSummary> {
+    let path = args
+        .log
+        .clone()
+        .or_else(|| settings.path.clone())
+        .ok_or(AppError::AuditLogDisabled)?;
+    // Size is irrelevant for reading; only the rotated file count matters.
+    let log = AuditLog::new(&path, u64::MAX, settings.keep);
+    let filter = AuditFilter {
+        user: args.user.clone(),
+        service: args.service.clone(),
+        outcome: args.outcome.map(outcome_from_arg),
+    };
+    let (mut records, skipped_lines) = log.read(&filter)?;
+    if let Some(limit) = args.limit {
+        records.drain(..records.len().saturating_sub(limit));
+    }
+
+    Ok(AuditShowSummary {
+        log_path: path,
+        records,
+        skipped_lines,
+    })
+}
+
+fn outcome_from_arg(arg: AuditOutcomeArg) -> AuditOutcome {
+    match arg {
+        AuditOutcomeArg::Success => AuditOutcome::Success,
+        AuditOutcomeArg::Failure => AuditOutcome::Failure,
+        AuditOutcomeArg::Ignored => AuditOutcome::Ignored,
+        AuditOutcomeArg::Error => AuditOutcome::Error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chissu_face_core::audit::audit_timestamp;
+    use tempfile::tempdir;
+
+    fn record(user: &str, outcome: AuditOutcome) -> AuditRecord {
+        AuditRecord {
+            timestamp: audit_timestamp(),
+            service: "sudo".into(),
+            user: user.into(),
+            tty: None,
+            outcome,
+            observed: false,
+            failure_reason: None,
+            detail: None,
+            frames_captured: 2,
+            best_similarity: Some(0.5),
+            similarity_threshold: 0.9,
+            device: "/dev/video0".into(),
+            duration_ms: 800,
+        }
+    }
+
+    fn args(log: Option<PathBuf>) -> AuditShowArgs {
+        AuditShowArgs {
+            user: None,
+            service: None,
+            outcome: None,
+            limit: None,
+            log,
+        }
+    }
+
+    #[test]
+    fn filters_and_limits_to_newest_records() {
+        let dir = tempdir().unwrap();
+        let settings = AuditSettings {
+            path: Some(dir.path().join("audit.jsonl")),
+            keep: 2,
+        };
+        let log = AuditLog::new(settings.path.clone().unwrap(), 1 << 20, 2);
+        for (user, outcome) in [
+            ("alice", AuditOutcome::Failure),
+            ("bob", AuditOutcome::Failure),
+            ("alice", AuditOutcome::Success),
+            ("alice", AuditOutcome::Failure),
+        ] {
+            log.append(&record(user, outcome)).unwrap();
+        }
+
+        let mut show = args(None);
+        show.user = Some("alice".into());
+        show.outcome = Some(AuditOutcomeArg::Failure);
+        let summary = run_audit_show_with(&show, &settings).unwrap();
+        assert_eq!(summary.records.len(), 2);
+
+        show.outcome = None;
+        show.limit = Some(1);
+        let summary = run_audit_show_with(&show, &settings).unwrap();
+        assert_eq!(summary.records.len(), 1);
+        assert_eq!(summary.records[0].outcome, AuditOutcome::Failure);
+    }
+
+    #[test]
+    fn requires_configured_or_explicit_log() {
+        let settings = AuditSettings {
+            path: None,
+            keep: 5,
+        };
+        assert!(matches!(
+            run_audit_show_with(&args(None), &settings),
+            Err(AppError::AuditLogDisabled)
+        ));
+
+        let dir = tempdir().unwrap();
+        let summary =
+            run_audit_show_with(&args(Some(dir.path().join("missing.jsonl"))), &settings).unwrap();
+        assert!(summary.records.is_empty());
+    }
+}
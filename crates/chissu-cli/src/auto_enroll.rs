@@ -2,14 +2,19 @@ use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 
+use chissu_face_core::device_pin::{
+    DeviceIdentity, DevicePin, DevicePinStore, DeviceRejection, DeviceTrustPolicy,
+};
 use chrono::Utc;
 use uuid::Uuid;
 
 use crate::capture::{self, CaptureConfig, CaptureOutcome, DeviceLocator};
 use crate::cli::{CaptureArgs, EnrollArgs, FaceEnrollArgs, FaceExtractArgs};
-use crate::config::{self as config_loader, CaptureDefaults, FaceModelDefaults};
+use crate::config::{self as config_loader, CaptureDefaults, DevicePinSettings, FaceModelDefaults};
 use crate::errors::{AppError, AppResult};
-use crate::faces::{self, FaceEnrollmentConfig, FaceEnrollmentOutcome, FaceExtractionConfig};
+use crate::faces::{
+    self, FaceEnrollmentConfig, FaceEnrollmentOutcome, FaceExtractionConfig, FaceSelectionPolicy,
+};
 
 #[derive(Debug)]
 pub struct AutoEnrollOutcome {
@@ -20,6 +25,8 @@ pub struct AutoEnrollOutcome {
     pub embedding_deleted: bool,
     pub faces_detected: usize,
     pub enrollment: FaceEnrollmentOutcome,
+    /// Pin file written because the user had no camera pinned yet.
+    pub device_pin: Option<PathBuf>,
     pub logs: Vec<String>,
 }
 
@@ -32,6 +39,8 @@ struct AutoEnrollContext {
     landmark_model: Option<PathBuf>,
     encoder_model: Option<PathBuf>,
     jitters: u32,
+    face_selection: FaceSelectionPolicy,
+    device_pins: DevicePinSettings,
     temp_base: PathBuf,
 }
 
@@ -40,6 +49,8 @@ pub fn run_auto_enroll(args: &EnrollArgs) -> AppResult<AutoEnrollOutcome> {
     let store_dir = config_loader::resolve_store_dir(args.store_dir.clone())?;
     let capture_defaults = config_loader::load_capture_defaults()?;
     let model_defaults = config_loader::load_face_model_defaults()?;
+    let face_selection = config_loader::load_face_selection()?;
+    let device_pins = config_loader::load_device_pin_settings()?;
     let (landmark_model, encoder_model) = resolve_model_paths(args, &model_defaults);
     let temp_base = std::env::temp_dir().join(format!("chissu-pam-{}", Uuid::new_v4()));
 
@@ -51,6 +62,8 @@ pub fn run_auto_enroll(args: &EnrollArgs) -> AppResult<AutoEnrollOutcome> {
         landmark_model,
         encoder_model,
         jitters: args.jitters,
+        face_selection,
+        device_pins,
         temp_base,
     };
 
@@ -87,7 +100,8 @@ where
         warmup_frames: None,
         output: Some(capture_path.clone()),
     };
-    let capture_config = capture::build_capture_config(&capture_args, &ctx.capture_defaults);
+    let capture_configs = capture::build_capture_configs(&capture_args, &ctx.capture_defaults);
+    let capture_config = &capture_configs[0];
 
     let mut logs = Vec::new();
     logs.push(format!("Resolved target user: {}", ctx.target_user));
@@ -103,9 +117,44 @@ where
         "Resolved warm-up frames: {}",
         capture_config.warmup_frames
     ));
+    for fallback in &capture_configs[1..] {
+        logs.push(format!(
+            "Fallback video device: {} ({}, {} warm-up frames)",
+            display_device(&fallback.device),
+            fallback.pixel_format,
+            fallback.warmup_frames
+        ));
+    }
 
-    let capture_outcome = capture_runner(&capture_config)?;
+    let capture_outcome = capture::run_first_available(&capture_configs, &capture_runner)?;
     logs.extend(capture_outcome.logs.clone());
+    if !capture_outcome.summary.skipped_devices.is_empty() {
+        logs.push(format!(
+            "Captured with video device {}",
+            capture_outcome.summary.device.path
+        ));
+    }
+
+    let pins = DevicePinStore::new(&ctx.device_pins.dir);
+    let existing_pin = pins.load(&ctx.target_user)?;
+    let device = &capture_outcome.summary.device;
+    let trust = DeviceTrustPolicy {
+        pinned: existing_pin.as_ref().map(|pin| pin.identity.clone()),
+        denied_drivers: ctx.device_pins.denied_drivers.clone(),
+    };
+    if let Err(rejection) = trust.check(Some(device)) {
+        let hint = match rejection {
+            DeviceRejection::IdentityMismatch { .. } => format!(
+                "; run `chissu-cli faces pin-device --user {}` if the camera was replaced",
+                ctx.target_user
+            ),
+            _ => String::new(),
+        };
+        return Err(AppError::UntrustedDevice {
+            device: device.path.clone(),
+            message: format!("{rejection}{hint}"),
+        });
+    }
 
     let extract_args = FaceExtractArgs {
         image: capture_path.clone(),
@@ -114,7 +163,10 @@ where
         output: Some(embedding_path.clone()),
         jitters: ctx.jitters,
     };
-    let extraction_config = FaceExtractionConfig::from(&extract_args);
+    let extraction_config = FaceExtractionConfig {
+        selection: ctx.face_selection,
+        ..FaceExtractionConfig::from(&extract_args)
+    };
     let extraction_outcome = extractor(&extraction_config)?;
     logs.extend(extraction_outcome.logs.clone());
 
@@ -130,10 +182,28 @@ where
         embedding: embedding_path.clone(),
         store_dir: ctx.store_dir.clone(),
     };
-    let enrollment_config = FaceEnrollmentConfig::from(&enroll_args);
+    let enrollment_config = FaceEnrollmentConfig {
+        selection: ctx.face_selection,
+        ..FaceEnrollmentConfig::from(&enroll_args)
+    };
     let enrollment_outcome = enroller(&enrollment_config)?;
     logs.extend(enrollment_outcome.logs.clone());
 
+    let device_pin = match existing_pin {
+        Some(_) => None,
+        None => {
+            let path = pins.save(&ctx.target_user, &DevicePin::new(device))?;
+            logs.push(format!(
+                "Pinned camera {} ({}) for user {} in {}",
+                device.path,
+                DeviceIdentity::from(device),
+                ctx.target_user,
+                path.display()
+            ));
+            Some(path)
+        }
+    };
+
     let capture_deleted = cleanup_file(&capture_path, &mut logs, "captured frame");
     let embedding_deleted = cleanup_file(&embedding_path, &mut logs, "embedding payload");
     cleanup_dir(&ctx.temp_base, &mut logs);
@@ -146,6 +216,7 @@ where
         embedding_deleted,
         faces_detected: extraction_outcome.summary.num_faces,
         enrollment: enrollment_outcome,
+        device_pin,
         logs,
     })
 }
@@ -196,6 +267,7 @@ fn display_device(device: &DeviceLocator) -> String {
     match device {
         DeviceLocator::Index(idx) => format!("/dev/video{idx}"),
         DeviceLocator::Path(path) => path.display().to_string(),
+        DeviceLocator::Selector(selector) => selector.to_string(),
     }
 }
 
@@ -265,6 +337,7 @@ mod tests {
                 gain: None,
                 auto_exposure: Some("applied".into()),
                 auto_gain: None,
+                skipped_devices: Vec::new(),
             },
             logs: vec!["Stub capture".into()],
         }
@@ -281,6 +354,7 @@ mod tests {
                     bottom: 10,
                 },
                 embedding: vec![0.0, 1.0],
+                landmarks: Vec::new(),
             });
         }
         FaceExtractionOutcome {
@@ -288,6 +362,8 @@ mod tests {
                 success: true,
                 image_path: image.display().to_string(),
                 output_path: output.display().to_string(),
+                image_width: 1,
+                image_height: 1,
                 num_faces: faces,
                 faces: face_records,
                 landmark_model: "/models/landmark.dat".into(),
@@ -312,6 +388,13 @@ mod tests {
         }
     }
 
+    fn pin_settings(dir: &Path) -> DevicePinSettings {
+        DevicePinSettings {
+            dir: dir.join("devices"),
+            denied_drivers: vec!["v4l2 loopback".into()],
+        }
+    }
+
     #[test]
     fn resolves_user_defaults_and_allows_root_override() {
         assert_eq!(
@@ -335,11 +418,14 @@ mod tests {
                 device: Some("/dev/video5".into()),
                 pixel_format: Some("GREY".into()),
                 warmup_frames: Some(6),
+                video_devices: Vec::new(),
             },
             device_override: None,
             landmark_model: None,
             encoder_model: None,
             jitters: 1,
+            face_selection: FaceSelectionPolicy::default(),
+            device_pins: pin_settings(dir.path()),
             temp_base: dir.path().join("auto"),
         };
 
@@ -381,6 +467,10 @@ mod tests {
         assert!(outcome.capture_deleted);
         assert!(outcome.embedding_deleted);
         assert_eq!(outcome.faces_detected, 1);
+        assert_eq!(
+            outcome.device_pin,
+            Some(dir.path().join("devices/alice.json"))
+        );
         assert!(outcome
             .logs
             .iter()
@@ -398,6 +488,8 @@ mod tests {
             landmark_model: None,
             encoder_model: None,
             jitters: 1,
+            face_selection: FaceSelectionPolicy::default(),
+            device_pins: pin_settings(dir.path()),
             temp_base: dir.path().join("auto"),
         };
 
@@ -425,6 +517,8 @@ mod tests {
             landmark_model: None,
             encoder_model: None,
             jitters: 1,
+            face_selection: FaceSelectionPolicy::default(),
+            device_pins: pin_settings(dir.path()),
             temp_base: dir.path().join("auto"),
         };
 
@@ -447,6 +541,48 @@ mod tests {
         assert!(matches!(err, Err(AppError::EmbeddingValidation { .. })));
     }
 
+    #[test]
+    fn capture_from_other_camera_is_refused_once_pinned() {
+        let dir = tempdir().unwrap();
+        let ctx = AutoEnrollContext {
+            target_user: "alice".into(),
+            store_dir: None,
+            capture_defaults: CaptureDefaults::default(),
+            device_override: None,
+            landmark_model: None,
+            encoder_model: None,
+            jitters: 1,
+            face_selection: FaceSelectionPolicy::default(),
+            device_pins: pin_settings(dir.path()),
+            temp_base: dir.path().join("auto"),
+        };
+        let mut pinned = stub_capture_outcome(Path::new("/dev/video0"))
+            .summary
+            .device;
+        pinned.bus_info = "usb-0000:00:14.0-5".into();
+        DevicePinStore::new(&ctx.device_pins.dir)
+            .save("alice", &DevicePin::new(&pinned))
+            .unwrap();
+
+        let capture_runner = |config: &CaptureConfig| {
+            fs::write(config.output.as_ref().unwrap(), b"frame").unwrap();
+            Ok(stub_capture_outcome(config.output.as_ref().unwrap()))
+        };
+
+        let err = run_auto_enroll_with(
+            ctx,
+            capture_runner,
+            |_cfg| unreachable!(),
+            |_cfg| unreachable!(),
+        );
+        match err {
+            Err(AppError::UntrustedDevice { message, .. }) => {
+                assert!(message.contains("faces pin-device --user alice"))
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
     #[test]
     fn resolve_model_paths_prefers_cli_then_config() {
         let defaults = FaceModelDefaults {
@@ -39,6 +39,31 @@ pub fn build_capture_config(args: &CaptureArgs, defaults: &CaptureDefaults) -> C
     }
 }
 
+/// Configs to try in order: the `--device` flag alone, else one per
+/// `[[video_devices]]` entry, else the single configured device. Explicit
+/// flags apply to every entry.
+pub fn build_capture_configs(args: &CaptureArgs, defaults: &CaptureDefaults) -> Vec<CaptureConfig> {
+    if args.device.is_some() || defaults.video_devices.is_empty() {
+        return vec![build_capture_config(args, defaults)];
+    }
+    defaults
+        .video_devices
+        .iter()
+        .map(|entry| {
+            let entry_defaults = CaptureDefaults {
+                device: Some(entry.device.clone()),
+                pixel_format: entry
+                    .pixel_format
+                    .clone()
+                    .or_else(|| defaults.pixel_format.clone()),
+                warmup_frames: entry.warmup_frames.or(defaults.warmup_frames),
+                video_devices: Vec::new(),
+            };
+            build_capture_config(args, &entry_defaults)
+        })
+        .collect()
+}
+
 pub struct CaptureHandler {
     args: CaptureArgs,
     load_defaults: Box<CaptureDefaultsLoader>,
@@ -75,8 +100,8 @@ impl CommandHandler for CaptureHandler {
     fn execute(&self, mode: OutputMode, _verbose: bool) -> AppResult<ExitCode> {
         let defaults = (self.load_defaults)()?;
         log_capture_defaults(&self.args, &defaults);
-        let config = build_capture_config(&self.args, &defaults);
-        let outcome = (self.run_capture)(&config)?;
+        let configs = build_capture_configs(&self.args, &defaults);
+        let outcome = run_first_available(&configs, |config| (self.run_capture)(config))?;
         (self.render)(&outcome, mode)?;
         Ok(ExitCode::SUCCESS)
     }
@@ -87,7 +112,7 @@ impl CommandHandler for CaptureHandler {
 }
 
 fn log_capture_defaults(args: &CaptureArgs, defaults: &CaptureDefaults) {
-    if args.device.is_none() && defaults.device.is_none() {
+    if args.device.is_none() && defaults.device.is_none() && defaults.video_devices.is_empty() {
         tracing::info!(
             target: "capture.defaults",
             "No --device flag or config video_device found; defaulting to /dev/video0"
@@ -112,6 +137,7 @@ fn log_capture_defaults(args: &CaptureArgs, defaults: &CaptureDefaults) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chissu_config::VideoDeviceEntry;
     use std::path::PathBuf;
 
     fn base_args() -> CaptureArgs {
@@ -134,6 +160,7 @@ mod tests {
             device: device.map(|d| d.to_string()),
             pixel_format: pixel.map(|p| p.to_string()),
             warmup_frames: warmup,
+            video_devices: Vec::new(),
         }
     }
 
@@ -149,7 +176,7 @@ mod tests {
 
         match config.device {
             DeviceLocator::Path(path) => assert_eq!(path, PathBuf::from("/dev/video9")),
-            DeviceLocator::Index(_) => panic!("expected path"),
+            other => panic!("unexpected device: {:?}", other),
         }
         assert_eq!(config.pixel_format, "GREY");
         assert_eq!(config.warmup_frames, 7);
@@ -181,4 +208,49 @@ mod tests {
         assert_eq!(config.pixel_format, DEFAULT_PIXEL_FORMAT);
         assert_eq!(config.warmup_frames, DEFAULT_WARMUP_FRAMES);
     }
+
+    #[test]
+    fn video_devices_expand_in_order_unless_device_flag_is_set() {
+        let mut config = defaults(Some("/dev/video0"), Some("Y8"), Some(6));
+        config.video_devices = vec![
+            VideoDeviceEntry {
+                device: "/dev/video4".into(),
+                pixel_format: Some("GREY".into()),
+                warmup_frames: None,
+            },
+            VideoDeviceEntry {
+                device: "/dev/video0".into(),
+                pixel_format: None,
+                warmup_frames: Some(2),
+            },
+        ];
+
+        let configs = build_capture_configs(&base_args(), &config);
+        let summary: Vec<_> = configs
+            .iter()
+            .map(|c| {
+                (
+                    c.device.path().unwrap(),
+                    c.pixel_format.as_str(),
+                    c.warmup_frames,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (PathBuf::from("/dev/video4"), "GREY", 6),
+                (PathBuf::from("/dev/video0"), "Y8", 2),
+            ]
+        );
+
+        let mut args = base_args();
+        args.device = Some("/dev/video9".into());
+        let configs = build_capture_configs(&args, &config);
+        assert_eq!(configs.len(), 1);
+        assert_eq!(
+            configs[0].device.path().unwrap(),
+            PathBuf::from("/dev/video9")
+        );
+    }
 }
@@ -38,6 +38,59 @@ pub enum Commands {
     Keyring(KeyringCommands),
     /// Run environment diagnostics for PAM and enrollment prerequisites
     Doctor(DoctorArgs),
+    /// Inspect or reset a user's failed face authentication tally
+    Faillock(FaillockArgs),
+    /// Query the PAM authentication audit log
+    #[command(subcommand)]
+    Audit(AuditCommands),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AuditCommands {
+    /// Print audit records, oldest first, across rotated files
+    Show(AuditShowArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct AuditShowArgs {
+    /// Only show records for this user
+    #[arg(long)]
+    pub user: Option<String>,
+
+    /// Only show records for this PAM service
+    #[arg(long)]
+    pub service: Option<String>,
+
+    /// Only show records with this outcome
+    #[arg(long, value_enum)]
+    pub outcome: Option<AuditOutcomeArg>,
+
+    /// Show at most this many of the newest matching records
+    #[arg(long)]
+    pub limit: Option<usize>,
+
+    /// Audit log path (defaults to `audit_log` from the configuration)
+    #[arg(long)]
+    pub log: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AuditOutcomeArg {
+    Success,
+    Failure,
+    Ignored,
+    Error,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct FaillockArgs {
+    /// Target operating system user name
+    #[arg(long)]
+    pub user: String,
+
+    /// Clear the tally, lifting any active lockout
+    #[arg(long)]
+    pub reset: bool,
 }
 
 #[derive(Debug, Clone, Args)]
@@ -57,6 +110,8 @@ pub enum FacesCommands {
     Enroll(FaceEnrollArgs),
     /// Remove embeddings from a per-user feature store
     Remove(FaceRemoveArgs),
+    /// Record the camera a user authenticates with, replacing any previous pin
+    PinDevice(FacePinDeviceArgs),
 }
 
 #[derive(Debug, Subcommand)]
@@ -169,6 +224,17 @@ pub struct FaceCompareArgs {
     /// Embedding JSON paths to compare against the input (repeatable)
     #[arg(long = "compare-target", required = true)]
     pub compare_targets: Vec<PathBuf>,
+
+    /// Similarity metric (defaults to `similarity_metric` from the configuration)
+    #[arg(long, value_enum)]
+    pub metric: Option<SimilarityMetricArg>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SimilarityMetricArg {
+    Cosine,
+    Euclidean,
+    NormalizedEuclidean,
 }
 
 #[derive(Debug, Args)]
@@ -209,6 +275,17 @@ pub struct FaceRemoveArgs {
     pub store_dir: Option<PathBuf>,
 }
 
+#[derive(Debug, Clone, Args)]
+pub struct FacePinDeviceArgs {
+    /// Target operating system user name
+    #[arg(long)]
+    pub user: String,
+
+    /// Video device path (e.g. /dev/video2) or index. Defaults to config or `/dev/video0`.
+    #[arg(long)]
+    pub device: Option<String>,
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum OutputFormat {
     Png,
@@ -0,0 +1,53 @@
+use std::any::Any;
+use std::process::ExitCode;
+
+use crate::audit::{self, AuditShowSummary};
+use crate::cli::{AuditCommands, OutputMode};
+use crate::commands::CommandHandler;
+use crate::errors::AppResult;
+use crate::output::render_audit_show;
+
+type AuditShowFn = dyn Fn(&AuditCommands) -> AppResult<AuditShowSummary> + Send + Sync;
+type AuditRenderFn = dyn Fn(&AuditShowSummary, OutputMode) -> AppResult<()> + Send + Sync;
+
+pub struct AuditHandler {
+    command: AuditCommands,
+    show: Box<AuditShowFn>,
+    render: Box<AuditRenderFn>,
+}
+
+impl AuditHandler {
+    pub fn new(command: AuditCommands) -> Self {
+        Self::with_dependencies(command, default_show, render_audit_show)
+    }
+
+    pub fn with_dependencies(
+        command: AuditCommands,
+        show: impl Fn(&AuditCommands) -> AppResult<AuditShowSummary> + Send + Sync + 'static,
+        render: impl Fn(&AuditShowSummary, OutputMode) -> AppResult<()> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            command,
+            show: Box::new(show),
+            render: Box::new(render),
+        }
+    }
+}
+
+impl CommandHandler for AuditHandler {
+    fn execute(&self, mode: OutputMode, _verbose: bool) -> AppResult<ExitCode> {
+        let summary = (self.show)(&self.command)?;
+        (self.render)(&summary, mode)?;
+        Ok(ExitCode::SUCCESS)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn default_show(command: &AuditCommands) -> AppResult<AuditShowSummary> {
+    match command {
+        AuditCommands::Show(args) => audit::run_audit_show(args),
+    }
+}
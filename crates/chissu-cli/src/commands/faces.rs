@@ -2,19 +2,24 @@ use std::any::Any;
 use std::path::PathBuf;
 use std::process::ExitCode;
 
-use crate::cli::{FaceRemoveArgs, FacesCommands, OutputMode};
+use crate::cli::{FacePinDeviceArgs, FaceRemoveArgs, FacesCommands, OutputMode};
 use crate::commands::CommandHandler;
 use crate::config;
+use crate::device_pin::{self, DevicePinSummary};
 use crate::errors::AppResult;
 use crate::faces::{
     self, FaceComparisonConfig, FaceComparisonOutcome, FaceEnrollmentConfig, FaceEnrollmentOutcome,
     FaceExtractionConfig, FaceExtractionOutcome, FaceRemovalConfig, FaceRemovalOutcome,
+    FaceSelectionPolicy, SimilarityMetric,
 };
 use crate::output::{
-    render_face_compare, render_face_enroll, render_face_remove, render_face_success,
+    render_device_pin, render_face_compare, render_face_enroll, render_face_remove,
+    render_face_success,
 };
 
 type ResolveStoreDirFn = dyn Fn(Option<PathBuf>) -> AppResult<Option<PathBuf>> + Send + Sync;
+type FaceSelectionLoader = dyn Fn() -> AppResult<FaceSelectionPolicy> + Send + Sync;
+type SimilarityMetricLoader = dyn Fn() -> AppResult<SimilarityMetric> + Send + Sync;
 type FaceExtractRunner =
     dyn Fn(&FaceExtractionConfig) -> AppResult<FaceExtractionOutcome> + Send + Sync;
 type FaceCompareRunner =
@@ -28,6 +33,8 @@ type FaceCompareRenderer =
     dyn Fn(&FaceComparisonOutcome, OutputMode) -> AppResult<()> + Send + Sync;
 type FaceEnrollRenderer = dyn Fn(&FaceEnrollmentOutcome, OutputMode) -> AppResult<()> + Send + Sync;
 type FaceRemoveRenderer = dyn Fn(&FaceRemovalOutcome, OutputMode) -> AppResult<()> + Send + Sync;
+type DevicePinRunner = dyn Fn(&FacePinDeviceArgs) -> AppResult<DevicePinSummary> + Send + Sync;
+type DevicePinRenderer = dyn Fn(&DevicePinSummary, OutputMode) -> AppResult<()> + Send + Sync;
 
 pub struct FacesHandler {
     command: FacesCommands,
@@ -36,6 +43,8 @@ pub struct FacesHandler {
 
 pub struct FacesHandlerDeps {
     pub resolve_store_dir: Box<ResolveStoreDirFn>,
+    pub load_face_selection: Box<FaceSelectionLoader>,
+    pub load_similarity_metric: Box<SimilarityMetricLoader>,
     pub extract: Box<FaceExtractRunner>,
     pub compare: Box<FaceCompareRunner>,
     pub enroll: Box<FaceEnrollRunner>,
@@ -44,6 +53,8 @@ pub struct FacesHandlerDeps {
     pub render_compare: Box<FaceCompareRenderer>,
     pub render_enroll: Box<FaceEnrollRenderer>,
     pub render_remove: Box<FaceRemoveRenderer>,
+    pub pin_device: Box<DevicePinRunner>,
+    pub render_pin_device: Box<DevicePinRenderer>,
 }
 
 impl FacesHandlerDeps {
@@ -82,6 +93,8 @@ impl FacesHandlerDeps {
     ) -> Self {
         Self {
             resolve_store_dir: Box::new(resolve_store_dir),
+            load_face_selection: Box::new(|| Ok(FaceSelectionPolicy::default())),
+            load_similarity_metric: Box::new(|| Ok(SimilarityMetric::default())),
             extract: Box::new(extract),
             compare: Box::new(compare),
             enroll: Box::new(enroll),
@@ -90,8 +103,42 @@ impl FacesHandlerDeps {
             render_compare: Box::new(render_compare),
             render_enroll: Box::new(render_enroll),
             render_remove: Box::new(render_remove),
+            pin_device: Box::new(device_pin::run_device_pin),
+            render_pin_device: Box::new(render_device_pin),
         }
     }
+
+    /// Replaces the face selection policy source, which defaults to the
+    /// permissive policy.
+    pub fn with_face_selection_loader(
+        mut self,
+        loader: impl Fn() -> AppResult<FaceSelectionPolicy> + Send + Sync + 'static,
+    ) -> Self {
+        self.load_face_selection = Box::new(loader);
+        self
+    }
+
+    /// Replaces the source of the `faces compare` metric used when
+    /// `--metric` is absent, which defaults to cosine.
+    pub fn with_similarity_metric_loader(
+        mut self,
+        loader: impl Fn() -> AppResult<SimilarityMetric> + Send + Sync + 'static,
+    ) -> Self {
+        self.load_similarity_metric = Box::new(loader);
+        self
+    }
+
+    /// Replaces the `faces pin-device` runner and renderer, which default to
+    /// querying the real camera.
+    pub fn with_device_pinner(
+        mut self,
+        run: impl Fn(&FacePinDeviceArgs) -> AppResult<DevicePinSummary> + Send + Sync + 'static,
+        render: impl Fn(&DevicePinSummary, OutputMode) -> AppResult<()> + Send + Sync + 'static,
+    ) -> Self {
+        self.pin_device = Box::new(run);
+        self.render_pin_device = Box::new(render);
+        self
+    }
 }
 
 impl Default for FacesHandlerDeps {
@@ -107,6 +154,8 @@ impl Default for FacesHandlerDeps {
             render_face_enroll,
             render_face_remove,
         )
+        .with_face_selection_loader(config::load_face_selection)
+        .with_similarity_metric_loader(config::load_similarity_metric)
     }
 }
 
@@ -127,12 +176,22 @@ impl CommandHandler for FacesHandler {
     fn execute(&self, mode: OutputMode, _verbose: bool) -> AppResult<ExitCode> {
         match &self.command {
             FacesCommands::Extract(args) => {
-                let config = FaceExtractionConfig::from(args);
+                let config = FaceExtractionConfig {
+                    selection: (self.deps.load_face_selection)()?,
+                    ..FaceExtractionConfig::from(args)
+                };
                 let outcome = (self.deps.extract)(&config)?;
                 (self.deps.render_extract)(&outcome, mode)?;
             }
             FacesCommands::Compare(args) => {
-                let config = FaceComparisonConfig::from(args);
+                let metric = match args.metric {
+                    Some(metric) => metric.into(),
+                    None => (self.deps.load_similarity_metric)()?,
+                };
+                let config = FaceComparisonConfig {
+                    metric,
+                    ..FaceComparisonConfig::from(args)
+                };
                 let outcome = (self.deps.compare)(&config)?;
                 (self.deps.render_compare)(&outcome, mode)?;
             }
@@ -142,6 +201,7 @@ impl CommandHandler for FacesHandler {
                     user: args.user.clone(),
                     embedding: args.embedding.clone(),
                     store_dir,
+                    selection: (self.deps.load_face_selection)()?,
                 };
                 let outcome = (self.deps.enroll)(&config)?;
                 (self.deps.render_enroll)(&outcome, mode)?;
@@ -151,6 +211,10 @@ impl CommandHandler for FacesHandler {
                 let outcome = (self.deps.remove)(&config)?;
                 (self.deps.render_remove)(&outcome, mode)?;
             }
+            FacesCommands::PinDevice(args) => {
+                let summary = (self.deps.pin_device)(args)?;
+                (self.deps.render_pin_device)(&summary, mode)?;
+            }
         }
         Ok(ExitCode::SUCCESS)
     }
@@ -0,0 +1,47 @@
+use std::any::Any;
+use std::process::ExitCode;
+
+use crate::cli::{FaillockArgs, OutputMode};
+use crate::commands::CommandHandler;
+use crate::errors::AppResult;
+use crate::faillock::{self, FaillockSummary};
+use crate::output::render_faillock;
+
+type FaillockRunner = dyn Fn(&FaillockArgs) -> AppResult<FaillockSummary> + Send + Sync;
+type FaillockRenderer = dyn Fn(&FaillockSummary, OutputMode) -> AppResult<()> + Send + Sync;
+
+pub struct FaillockHandler {
+    args: FaillockArgs,
+    run: Box<FaillockRunner>,
+    render: Box<FaillockRenderer>,
+}
+
+impl FaillockHandler {
+    pub fn new(args: FaillockArgs) -> Self {
+        Self::with_dependencies(args, faillock::run_faillock, render_faillock)
+    }
+
+    pub fn with_dependencies(
+        args: FaillockArgs,
+        run: impl Fn(&FaillockArgs) -> AppResult<FaillockSummary> + Send + Sync + 'static,
+        render: impl Fn(&FaillockSummary, OutputMode) -> AppResult<()> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            args,
+            run: Box::new(run),
+            render: Box::new(render),
+        }
+    }
+}
+
+impl CommandHandler for FaillockHandler {
+    fn execute(&self, mode: OutputMode, _verbose: bool) -> AppResult<ExitCode> {
+        let summary = (self.run)(&self.args)?;
+        (self.render)(&summary, mode)?;
+        Ok(ExitCode::SUCCESS)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -10,14 +10,18 @@ pub trait CommandHandler: Send + Sync {
     fn as_any(&self) -> &dyn Any;
 }
 
+mod audit;
 mod doctor;
 mod enroll;
 mod faces;
+mod faillock;
 mod keyring;
 
+pub use audit::AuditHandler;
 pub use doctor::DoctorHandler;
 pub use enroll::EnrollHandler;
 pub use faces::{FacesHandler, FacesHandlerDeps};
+pub use faillock::FaillockHandler;
 pub use keyring::KeyringHandler;
 
 impl From<Commands> for Box<dyn CommandHandler> {
@@ -28,6 +32,8 @@ impl From<Commands> for Box<dyn CommandHandler> {
             Commands::Faces(cmd) => Box::new(FacesHandler::new(cmd)),
             Commands::Keyring(cmd) => Box::new(KeyringHandler::new(cmd)),
             Commands::Doctor(args) => Box::new(DoctorHandler::new(args)),
+            Commands::Faillock(args) => Box::new(FaillockHandler::new(args)),
+            Commands::Audit(cmd) => Box::new(AuditHandler::new(cmd)),
         }
     }
 }
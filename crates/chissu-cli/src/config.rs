@@ -1,14 +1,22 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
-use chissu_config::{self, ConfigError, ConfigFile, PRIMARY_CONFIG_PATH, SECONDARY_CONFIG_PATH};
+use chissu_config::{
+    self, ConfigError, ConfigFile, FaceSelection, ResolvedConfig,
+    SimilarityMetric as ConfigSimilarityMetric, VideoDeviceEntry, PRIMARY_CONFIG_PATH,
+    SECONDARY_CONFIG_PATH,
+};
 
 use crate::errors::{AppError, AppResult};
+use crate::faces::{FaceSelectionMode, FaceSelectionPolicy, SimilarityMetric};
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct CaptureDefaults {
     pub device: Option<String>,
     pub pixel_format: Option<String>,
     pub warmup_frames: Option<u32>,
+    /// `[[video_devices]]` entries, tried in order in place of `device`.
+    pub video_devices: Vec<VideoDeviceEntry>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
@@ -50,11 +58,122 @@ fn load_capture_defaults_with_sources(paths: &[PathBuf]) -> AppResult<CaptureDef
                 device: file.video_device,
                 pixel_format: file.pixel_format,
                 warmup_frames: file.warmup_frames,
+                video_devices: file.video_devices.unwrap_or_default(),
             }
         }),
     )
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaillockSettings {
+    pub dir: PathBuf,
+    pub deny: u32,
+    pub window: Duration,
+}
+
+pub fn load_faillock_settings() -> AppResult<FaillockSettings> {
+    let sources = [
+        PathBuf::from(PRIMARY_CONFIG_PATH),
+        PathBuf::from(SECONDARY_CONFIG_PATH),
+    ];
+    load_faillock_settings_with_sources(&sources)
+}
+
+fn load_faillock_settings_with_sources(paths: &[PathBuf]) -> AppResult<FaillockSettings> {
+    let resolved = ResolvedConfig::from_raw(load_config_from_paths(paths)?.unwrap_or_default());
+    Ok(FaillockSettings {
+        dir: resolved.faillock_dir,
+        deny: resolved.faillock_deny,
+        window: resolved.faillock_window,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditSettings {
+    /// `None` when auditing is disabled.
+    pub path: Option<PathBuf>,
+    pub keep: u32,
+}
+
+pub fn load_audit_settings() -> AppResult<AuditSettings> {
+    let sources = [
+        PathBuf::from(PRIMARY_CONFIG_PATH),
+        PathBuf::from(SECONDARY_CONFIG_PATH),
+    ];
+    load_audit_settings_with_sources(&sources)
+}
+
+fn load_audit_settings_with_sources(paths: &[PathBuf]) -> AppResult<AuditSettings> {
+    let resolved = ResolvedConfig::from_raw(load_config_from_paths(paths)?.unwrap_or_default());
+    Ok(AuditSettings {
+        path: resolved.audit_log,
+        keep: resolved.audit_log_keep,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DevicePinSettings {
+    pub dir: PathBuf,
+    /// Drivers refused as virtual cameras.
+    pub denied_drivers: Vec<String>,
+}
+
+pub fn load_device_pin_settings() -> AppResult<DevicePinSettings> {
+    let sources = [
+        PathBuf::from(PRIMARY_CONFIG_PATH),
+        PathBuf::from(SECONDARY_CONFIG_PATH),
+    ];
+    load_device_pin_settings_with_sources(&sources)
+}
+
+fn load_device_pin_settings_with_sources(paths: &[PathBuf]) -> AppResult<DevicePinSettings> {
+    let resolved = ResolvedConfig::from_raw(load_config_from_paths(paths)?.unwrap_or_default());
+    Ok(DevicePinSettings {
+        dir: resolved.device_pin_dir,
+        denied_drivers: resolved.virtual_camera_drivers,
+    })
+}
+
+/// Face selection policy from the top-level configuration keys; service and
+/// user overrides only apply inside PAM.
+pub fn load_face_selection() -> AppResult<FaceSelectionPolicy> {
+    let sources = [
+        PathBuf::from(PRIMARY_CONFIG_PATH),
+        PathBuf::from(SECONDARY_CONFIG_PATH),
+    ];
+    load_face_selection_with_sources(&sources)
+}
+
+fn load_face_selection_with_sources(paths: &[PathBuf]) -> AppResult<FaceSelectionPolicy> {
+    let resolved = ResolvedConfig::from_raw(load_config_from_paths(paths)?.unwrap_or_default());
+    Ok(FaceSelectionPolicy {
+        mode: match resolved.face_selection {
+            FaceSelection::All => FaceSelectionMode::All,
+            FaceSelection::Largest => FaceSelectionMode::Largest,
+            FaceSelection::RejectMultiple => FaceSelectionMode::RejectMultiple,
+        },
+        min_face_size: resolved.face_min_size,
+        max_center_offset: resolved.face_max_center_offset,
+    })
+}
+
+pub fn load_similarity_metric() -> AppResult<SimilarityMetric> {
+    let sources = [
+        PathBuf::from(PRIMARY_CONFIG_PATH),
+        PathBuf::from(SECONDARY_CONFIG_PATH),
+    ];
+    load_similarity_metric_with_sources(&sources)
+}
+
+fn load_similarity_metric_with_sources(paths: &[PathBuf]) -> AppResult<SimilarityMetric> {
+    let resolved = ResolvedConfig::from_raw(load_config_from_paths(paths)?.unwrap_or_default());
+    Ok(match resolved.similarity_metric {
+        ConfigSimilarityMetric::Cosine => SimilarityMetric::Cosine,
+        ConfigSimilarityMetric::Euclidean => SimilarityMetric::Euclidean,
+        ConfigSimilarityMetric::NormalizedEuclidean => SimilarityMetric::NormalizedEuclidean,
+    })
+}
+
 pub fn load_face_model_defaults() -> AppResult<FaceModelDefaults> {
     let sources = [
         PathBuf::from(PRIMARY_CONFIG_PATH),
@@ -160,7 +279,7 @@ mod tests {
         let config_path = dir.path().join("config.toml");
         fs::write(
             &config_path,
-            "video_device = \"/dev/video5\"\npixel_format = \"GREY\"\nwarmup_frames = 9\n",
+            "video_device = \"/dev/video5\"\npixel_format = \"GREY\"\nwarmup_frames = 9\n\n[[video_devices]]\ndevice = \"/dev/video7\"\n",
         )
         .unwrap();
 
@@ -171,6 +290,11 @@ mod tests {
                 device: Some("/dev/video5".into()),
                 pixel_format: Some("GREY".into()),
                 warmup_frames: Some(9),
+                video_devices: vec![VideoDeviceEntry {
+                    device: "/dev/video7".into(),
+                    pixel_format: None,
+                    warmup_frames: None,
+                }],
             }
         );
     }
@@ -226,4 +350,60 @@ mod tests {
         let defaults = load_face_model_defaults_with_sources(&[]).unwrap();
         assert_eq!(defaults, FaceModelDefaults::default());
     }
+
+    #[test]
+    fn faillock_settings_fall_back_to_defaults() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("config.toml");
+        fs::write(&config_path, "faillock_deny = 4\n").unwrap();
+
+        let settings = load_faillock_settings_with_sources(&[config_path]).unwrap();
+        assert_eq!(settings.deny, 4);
+        assert_eq!(
+            settings.dir,
+            PathBuf::from(chissu_config::DEFAULT_FAILLOCK_DIR)
+        );
+        assert_eq!(
+            settings.window,
+            Duration::from_secs(chissu_config::DEFAULT_FAILLOCK_WINDOW_SECS)
+        );
+    }
+
+    #[test]
+    fn audit_settings_read_log_path_and_keep() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            "audit_log = \"/var/log/chissu-pam/audit.jsonl\"\n",
+        )
+        .unwrap();
+
+        let settings = load_audit_settings_with_sources(&[config_path]).unwrap();
+        assert_eq!(
+            settings.path,
+            Some(PathBuf::from("/var/log/chissu-pam/audit.jsonl"))
+        );
+        assert_eq!(settings.keep, chissu_config::DEFAULT_AUDIT_LOG_KEEP);
+        assert_eq!(load_audit_settings_with_sources(&[]).unwrap().path, None);
+    }
+
+    #[test]
+    fn device_pin_settings_read_dir_and_denylist() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            "device_pin_dir = \"/srv/pins\"\nvirtual_camera_drivers = [\"akvcam\"]\n",
+        )
+        .unwrap();
+
+        let settings = load_device_pin_settings_with_sources(&[config_path]).unwrap();
+        assert_eq!(settings.dir, PathBuf::from("/srv/pins"));
+        assert_eq!(settings.denied_drivers, vec!["akvcam".to_string()]);
+        assert_eq!(
+            load_device_pin_settings_with_sources(&[]).unwrap().dir,
+            PathBuf::from(chissu_config::DEFAULT_DEVICE_PIN_DIR)
+        );
+    }
 }
@@ -0,0 +1,111 @@
+use std::path::PathBuf;
+
+use chissu_face_core::device_pin::{is_denied_driver, DeviceIdentity, DevicePin, DevicePinStore};
+
+use crate::capture::{self, DeviceLocator, DeviceSummary};
+use crate::cli::FacePinDeviceArgs;
+use crate::config::{self, DevicePinSettings};
+use crate::errors::{AppError, AppResult};
+
+#[derive(Debug, Clone)]
+pub struct DevicePinSummary {
+    pub user: String,
+    pub pin_path: PathBuf,
+    /// Node the identity was read from.
+    pub device: String,
+    pub identity: DeviceIdentity,
+    /// Identity pinned before this run, if any.
+    pub previous: Option<DeviceIdentity>,
+}
+
+pub fn run_device_pin(args: &FacePinDeviceArgs) -> AppResult<DevicePinSummary> {
+    let settings = config::load_device_pin_settings()?;
+    let defaults = config::load_capture_defaults()?;
+    let device = args.device.clone().or(defaults.device);
+    run_device_pin_with(
+        args,
+        &settings,
+        &DeviceLocator::from_option(device),
+        capture::query_device,
+    )
+}
+
+pub fn run_device_pin_with<Q>(
+    args: &FacePinDeviceArgs,
+    settings: &DevicePinSettings,
+    locator: &DeviceLocator,
+    query: Q,
+) -> AppResult<DevicePinSummary>
+where
+    Q: Fn(&DeviceLocator) -> AppResult<DeviceSummary>,
+{
+    let store = DevicePinStore::new(&settings.dir);
+    let previous = store.load(&args.user)?.map(|pin| pin.identity);
+    let summary = query(locator)?;
+    if is_denied_driver(&summary.driver, &settings.denied_drivers) {
+        return Err(AppError::UntrustedDevice {
+            device: summary.path.clone(),
+            message: format!("driver '{}' is a known virtual camera", summary.driver),
+        });
+    }
+
+    let pin = DevicePin::new(&summary);
+    let pin_path = store.save(&args.user, &pin)?;
+    Ok(DevicePinSummary {
+        user: args.user.clone(),
+        pin_path,
+        device: pin.device,
+        identity: pin.identity,
+        previous,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn summary(driver: &str, bus_info: &str) -> DeviceSummary {
+        DeviceSummary {
+            driver: driver.into(),
+            card: "Integrated IR Camera".into(),
+            bus_info: bus_info.into(),
+            path: "/dev/video2".into(),
+        }
+    }
+
+    #[test]
+    fn pin_replaces_previous_identity_and_refuses_virtual_drivers() {
+        let dir = tempdir().unwrap();
+        let settings = DevicePinSettings {
+            dir: dir.path().to_path_buf(),
+            denied_drivers: vec!["v4l2 loopback".into()],
+        };
+        let args = FacePinDeviceArgs {
+            user: "alice".into(),
+            device: None,
+        };
+        let locator = DeviceLocator::Index(2);
+
+        let first = run_device_pin_with(&args, &settings, &locator, |_| {
+            Ok(summary("uvcvideo", "usb-0000:00:14.0-5"))
+        })
+        .unwrap();
+        assert_eq!(first.previous, None);
+        assert_eq!(first.pin_path, dir.path().join("alice.json"));
+
+        let second = run_device_pin_with(&args, &settings, &locator, |_| {
+            Ok(summary("uvcvideo", "usb-0000:00:14.0-6"))
+        })
+        .unwrap();
+        assert_eq!(second.previous, Some(first.identity));
+        assert_eq!(second.identity.bus_info, "usb-0000:00:14.0-6");
+
+        let err = run_device_pin_with(&args, &settings, &locator, |_| {
+            Ok(summary("v4l2 loopback", "platform:v4l2loopback-000"))
+        });
+        assert!(matches!(err, Err(AppError::UntrustedDevice { .. })));
+        let stored = DevicePinStore::new(dir.path()).load("alice").unwrap();
+        assert_eq!(stored.unwrap().identity, second.identity);
+    }
+}
@@ -5,10 +5,11 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use chissu_config::{
-    self, ConfigError, ResolvedConfig, ResolvedConfigWithSource, PRIMARY_CONFIG_PATH,
+    self, ConfigError, ResolvedConfig, ResolvedConfigWithSource, VideoDevice, PRIMARY_CONFIG_PATH,
     SECONDARY_CONFIG_PATH,
 };
-use chissu_face_core::capture::DeviceLocator;
+use chissu_face_core::capture::ir::{probe_ir_stream, IrStreamReport, IR_PROBE_FRAMES};
+use chissu_face_core::capture::{CaptureConfig, DeviceLocator};
 use chissu_face_core::secret_service::{
     default_service_name, ensure_secret_service_available, KeyringSecretServiceProbe,
     SecretServiceProbe,
@@ -19,6 +20,7 @@ use crate::errors::AppResult;
 
 const CHECK_CONFIG: &str = "config";
 const CHECK_VIDEO_DEVICE: &str = "video_device";
+const CHECK_IR_STREAM: &str = "ir_stream";
 const CHECK_EMBEDDING_DIR: &str = "embedding_store_dir";
 const CHECK_LANDMARK_MODEL: &str = "landmark_model";
 const CHECK_ENCODER_MODEL: &str = "encoder_model";
@@ -102,6 +104,9 @@ impl Default for DoctorPaths {
 
 pub trait DeviceOpener {
     fn open(&self, locator: &DeviceLocator) -> AppResult<()>;
+
+    /// Streams a few frames and reports the pixel format and IR lighting.
+    fn probe_ir(&self, config: &CaptureConfig) -> AppResult<IrStreamReport>;
 }
 
 #[derive(Clone, Copy)]
@@ -112,6 +117,10 @@ impl DeviceOpener for RealDeviceOpener {
         let _ = locator.open()?;
         Ok(())
     }
+
+    fn probe_ir(&self, config: &CaptureConfig) -> AppResult<IrStreamReport> {
+        probe_ir_stream(config, IR_PROBE_FRAMES)
+    }
 }
 
 pub struct DoctorContext<P, D, I = RealPolkitInspector> {
@@ -248,7 +257,7 @@ impl PolkitInspector for RealPolkitInspector {
 const DEFAULT_PROFILE: &[DoctorProfile] = &[DoctorProfile::Default];
 const POLKIT_PROFILE: &[DoctorProfile] = &[DoctorProfile::Polkit];
 
-fn check_registry<P, D, I>() -> [DoctorCheckSpec<P, D, I>; 11]
+fn check_registry<P, D, I>() -> [DoctorCheckSpec<P, D, I>; 12]
 where
     P: SecretServiceProbe,
     D: DeviceOpener,
@@ -265,6 +274,11 @@ where
             profiles: DEFAULT_PROFILE,
             run: run_video_device_check,
         },
+        DoctorCheckSpec {
+            name: CHECK_IR_STREAM,
+            profiles: DEFAULT_PROFILE,
+            run: run_ir_stream_check,
+        },
         DoctorCheckSpec {
             name: CHECK_EMBEDDING_DIR,
             profiles: DEFAULT_PROFILE,
@@ -326,6 +340,21 @@ where
     state.push(check_video_device(state.resolved(), services.device_opener));
 }
 
+fn run_ir_stream_check<P, D, I>(state: &mut DoctorState, services: &DoctorServices<'_, P, D, I>)
+where
+    D: DeviceOpener,
+{
+    let device_ok = state
+        .checks
+        .iter()
+        .any(|check| check.name == CHECK_VIDEO_DEVICE && check.status == CheckStatus::Pass);
+    state.push(check_ir_stream(
+        state.resolved(),
+        services.device_opener,
+        device_ok,
+    ));
+}
+
 fn run_embedding_dir_check<P, D, I>(
     state: &mut DoctorState,
     _services: &DoctorServices<'_, P, D, I>,
@@ -566,25 +595,117 @@ fn check_config(
     }
 }
 
+/// Passes when any configured camera opens, trying them in order like the
+/// PAM module does.
 fn check_video_device<D: DeviceOpener>(cfg: &ResolvedConfigWithSource, opener: &D) -> DoctorCheck {
-    let locator = DeviceLocator::from_option(Some(cfg.resolved.video_device.clone()));
+    let mut failures = Vec::new();
+    let mut display = String::new();
+    for device in &cfg.resolved.video_devices {
+        let locator = DeviceLocator::from_option(Some(device.device.clone()));
+        display = display_device(&locator);
+        match opener.open(&locator) {
+            Ok(_) => {
+                let message = if failures.is_empty() {
+                    format!("Opened video device {display}")
+                } else {
+                    format!(
+                        "Opened fallback video device {display}; skipped {}",
+                        failures.join("; ")
+                    )
+                };
+                return DoctorCheck {
+                    name: CHECK_VIDEO_DEVICE.into(),
+                    status: CheckStatus::Pass,
+                    message,
+                    path: None,
+                    device: Some(display),
+                };
+            }
+            Err(err) => failures.push(err.human_message()),
+        }
+    }
+    DoctorCheck {
+        name: CHECK_VIDEO_DEVICE.into(),
+        status: CheckStatus::Fail,
+        message: failures.join("; "),
+        path: None,
+        device: Some(display),
+    }
+}
+
+/// Runs the same pixel format and IR lighting check as `require_ir`. A
+/// failing stream is only a warning while `require_ir` is off.
+fn check_ir_stream<D: DeviceOpener>(
+    cfg: &ResolvedConfigWithSource,
+    opener: &D,
+    device_ok: bool,
+) -> DoctorCheck {
+    let resolved = &cfg.resolved;
+    let locator_of = |device: &VideoDevice| DeviceLocator::from_option(Some(device.device.clone()));
+    // Probe the camera authentication would use.
+    let device = resolved
+        .video_devices
+        .iter()
+        .find(|device| device_ok && opener.open(&locator_of(device)).is_ok())
+        .unwrap_or(&resolved.video_devices[0]);
+    let locator = locator_of(device);
     let display = display_device(&locator);
+    let failing = if resolved.require_ir {
+        CheckStatus::Fail
+    } else {
+        CheckStatus::Warn
+    };
+    let check = |status, message| DoctorCheck {
+        name: CHECK_IR_STREAM.into(),
+        status,
+        message,
+        path: None,
+        device: Some(display.clone()),
+    };
+    if !device_ok {
+        return check(
+            CheckStatus::Warn,
+            "Skipped because the video device could not be opened".into(),
+        );
+    }
 
-    match opener.open(&locator) {
-        Ok(_) => DoctorCheck {
-            name: CHECK_VIDEO_DEVICE.into(),
-            status: CheckStatus::Pass,
-            message: format!("Opened video device {display}"),
-            path: None,
-            device: Some(display),
-        },
-        Err(err) => DoctorCheck {
-            name: CHECK_VIDEO_DEVICE.into(),
-            status: CheckStatus::Fail,
-            message: err.human_message(),
-            path: None,
-            device: Some(display),
-        },
+    let config = CaptureConfig {
+        device: locator.clone(),
+        pixel_format: device.pixel_format.clone(),
+        width: None,
+        height: None,
+        exposure: None,
+        gain: None,
+        auto_exposure: false,
+        auto_gain: false,
+        warmup_frames: device.warmup_frames,
+        output: None,
+    };
+    let report = match opener.probe_ir(&config) {
+        Ok(report) => report,
+        Err(err) => return check(failing, err.human_message()),
+    };
+    match report.check() {
+        Ok(()) => check(
+            CheckStatus::Pass,
+            format!(
+                "{} stream shows IR illumination{}",
+                report.pixel_format,
+                if resolved.require_ir {
+                    ""
+                } else {
+                    "; require_ir can be enabled"
+                }
+            ),
+        ),
+        Err(rejection) => check(
+            failing,
+            if resolved.require_ir {
+                format!("{rejection}; authentication fails while require_ir = true")
+            } else {
+                format!("{rejection}; enabling require_ir would refuse this camera")
+            },
+        ),
     }
 }
 
@@ -1142,6 +1263,10 @@ fn display_device(locator: &DeviceLocator) -> String {
     match locator {
         DeviceLocator::Index(i) => format!("/dev/video{i}"),
         DeviceLocator::Path(p) => p.display().to_string(),
+        DeviceLocator::Selector(s) => match s.resolve() {
+            Ok(p) => format!("{s} ({})", p.display()),
+            Err(_) => s.to_string(),
+        },
     }
 }
 
@@ -1173,6 +1298,7 @@ fn is_traversable_dir(path: &Path) -> bool {
 mod tests {
     use super::*;
     use crate::errors::AppError;
+    use chissu_face_core::capture::ir::IrFrameStats;
     use chissu_face_core::secret_service::SecretServiceError;
     use std::fs::File;
     use std::io;
@@ -1181,6 +1307,7 @@ mod tests {
     #[derive(Clone)]
     struct StubDeviceOpener {
         ok: bool,
+        pixel_format: &'static str,
     }
 
     impl DeviceOpener for StubDeviceOpener {
@@ -1194,6 +1321,18 @@ mod tests {
                 })
             }
         }
+
+        fn probe_ir(&self, config: &CaptureConfig) -> AppResult<IrStreamReport> {
+            self.open(&config.device)?;
+            Ok(IrStreamReport {
+                pixel_format: self.pixel_format.into(),
+                samples: vec![IrFrameStats {
+                    mean_intensity: 80.0,
+                    dark_fraction: 0.3,
+                    contrast: 40.0,
+                }],
+            })
+        }
     }
 
     #[derive(Clone)]
@@ -1240,6 +1379,7 @@ mod tests {
             frame_interval_millis: None,
             require_secret_service: None,
             secret_service_session: None,
+            ..Default::default()
         };
         ResolvedConfig::from_raw(raw)
     }
@@ -1266,7 +1406,10 @@ mod tests {
         let ctx = DoctorContext {
             paths,
             secret_service_probe: probe,
-            device_opener: StubDeviceOpener { ok: device_ok },
+            device_opener: StubDeviceOpener {
+                ok: device_ok,
+                pixel_format: "GREY",
+            },
             polkit_inspector: StubPolkitInspector {
                 result: Err("not used".into()),
             },
@@ -1286,7 +1429,10 @@ mod tests {
         let ctx = DoctorContext {
             paths,
             secret_service_probe: probe,
-            device_opener: StubDeviceOpener { ok: device_ok },
+            device_opener: StubDeviceOpener {
+                ok: device_ok,
+                pixel_format: "GREY",
+            },
             polkit_inspector: StubPolkitInspector {
                 result: polkit_result,
             },
@@ -1327,6 +1473,7 @@ mod tests {
             vec![
                 CHECK_CONFIG,
                 CHECK_VIDEO_DEVICE,
+                CHECK_IR_STREAM,
                 CHECK_EMBEDDING_DIR,
                 CHECK_LANDMARK_MODEL,
                 CHECK_ENCODER_MODEL,
@@ -1346,6 +1493,7 @@ mod tests {
             vec![
                 CHECK_CONFIG,
                 CHECK_VIDEO_DEVICE,
+                CHECK_IR_STREAM,
                 CHECK_EMBEDDING_DIR,
                 CHECK_LANDMARK_MODEL,
                 CHECK_ENCODER_MODEL,
@@ -1389,6 +1537,7 @@ mod tests {
             vec![
                 CHECK_CONFIG,
                 CHECK_VIDEO_DEVICE,
+                CHECK_IR_STREAM,
                 CHECK_EMBEDDING_DIR,
                 CHECK_LANDMARK_MODEL,
                 CHECK_ENCODER_MODEL,
@@ -1475,6 +1624,35 @@ mod tests {
         assert!(!outcome.ok);
     }
 
+    #[test]
+    fn ir_stream_check_warns_on_colour_stream_unless_required() {
+        let tmp = tempdir().unwrap();
+        let mut cfg = ResolvedConfigWithSource {
+            resolved: resolved_for(tmp.path()),
+            source: None,
+        };
+        let colour = StubDeviceOpener {
+            ok: true,
+            pixel_format: "YUYV",
+        };
+
+        let check = check_ir_stream(&cfg, &colour, true);
+        assert_eq!(check.status, CheckStatus::Warn);
+        assert!(check.message.contains("YUYV"));
+
+        cfg.resolved.require_ir = true;
+        assert_eq!(
+            check_ir_stream(&cfg, &colour, true).status,
+            CheckStatus::Fail
+        );
+        let ir = StubDeviceOpener {
+            ok: true,
+            pixel_format: "Y16",
+        };
+        assert_eq!(check_ir_stream(&cfg, &ir, true).status, CheckStatus::Pass);
+        assert_eq!(check_ir_stream(&cfg, &ir, false).status, CheckStatus::Warn);
+    }
+
     #[test]
     fn doctor_reports_missing_pam_module_and_stack() {
         let tmp = tempdir().unwrap();
@@ -1798,6 +1976,7 @@ mod tests {
             vec![
                 CHECK_CONFIG,
                 CHECK_VIDEO_DEVICE,
+                CHECK_IR_STREAM,
                 CHECK_EMBEDDING_DIR,
                 CHECK_LANDMARK_MODEL,
                 CHECK_ENCODER_MODEL,
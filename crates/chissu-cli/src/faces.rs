@@ -1,6 +1,8 @@
 pub use chissu_face_core::faces::*;
 
-use crate::cli::{FaceCompareArgs, FaceEnrollArgs, FaceExtractArgs, FaceRemoveArgs};
+use crate::cli::{
+    FaceCompareArgs, FaceEnrollArgs, FaceExtractArgs, FaceRemoveArgs, SimilarityMetricArg,
+};
 
 impl From<&FaceExtractArgs> for FaceExtractionConfig {
     fn from(args: &FaceExtractArgs) -> Self {
@@ -10,6 +12,7 @@ impl From<&FaceExtractArgs> for FaceExtractionConfig {
             encoder_model: args.encoder_model.clone(),
             output: args.output.clone(),
             jitters: args.jitters,
+            selection: FaceSelectionPolicy::default(),
         }
     }
 }
@@ -19,6 +22,17 @@ impl From<&FaceCompareArgs> for FaceComparisonConfig {
         Self {
             input: args.input.clone(),
             compare_targets: args.compare_targets.clone(),
+            metric: args.metric.map(SimilarityMetric::from).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<SimilarityMetricArg> for SimilarityMetric {
+    fn from(arg: SimilarityMetricArg) -> Self {
+        match arg {
+            SimilarityMetricArg::Cosine => SimilarityMetric::Cosine,
+            SimilarityMetricArg::Euclidean => SimilarityMetric::Euclidean,
+            SimilarityMetricArg::NormalizedEuclidean => SimilarityMetric::NormalizedEuclidean,
         }
     }
 }
@@ -29,6 +43,7 @@ impl From<&FaceEnrollArgs> for FaceEnrollmentConfig {
             user: args.user.clone(),
             embedding: args.embedding.clone(),
             store_dir: args.store_dir.clone(),
+            selection: FaceSelectionPolicy::default(),
         }
     }
 }
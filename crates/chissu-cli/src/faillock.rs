@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use chissu_face_core::faillock::FaillockStore;
+
+use crate::cli::FaillockArgs;
+use crate::config::{self, FaillockSettings};
+use crate::errors::AppResult;
+
+#[derive(Debug, Clone)]
+pub struct FaillockSummary {
+    pub user: String,
+    pub tally_path: PathBuf,
+    /// Unix timestamps of the recorded consecutive failures, oldest first.
+    pub failures: Vec<u64>,
+    /// Failures inside the configured window.
+    pub recent: usize,
+    pub deny: u32,
+    pub window_secs: u64,
+    /// Seconds until the lockout lifts; `None` when the user is not locked.
+    pub remaining_lock_secs: Option<u64>,
+    /// Whether `--reset` cleared an existing tally.
+    pub reset: bool,
+}
+
+pub fn run_faillock(args: &FaillockArgs) -> AppResult<FaillockSummary> {
+    let settings = config::load_faillock_settings()?;
+    run_faillock_with(args, &settings, SystemTime::now())
+}
+
+pub fn run_faillock_with(
+    args: &FaillockArgs,
+    settings: &FaillockSettings,
+    now: SystemTime,
+) -> AppResult<FaillockSummary> {
+    let store = FaillockStore::new(&settings.dir);
+    let tally_path = store.tally_path(&args.user)?;
+    let (tally, reset) = if args.reset {
+        (Default::default(), store.reset(&args.user)?)
+    } else {
+        (store.load(&args.user)?, false)
+    };
+
+    Ok(FaillockSummary {
+        user: args.user.clone(),
+        tally_path,
+        recent: tally.recent(settings.window, now),
+        remaining_lock_secs: tally.remaining_lock(settings.deny, settings.window, now),
+        failures: tally.failures,
+        deny: settings.deny,
+        window_secs: settings.window.as_secs(),
+        reset,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+    use tempfile::tempdir;
+
+    fn settings(dir: PathBuf) -> FaillockSettings {
+        FaillockSettings {
+            dir,
+            deny: 2,
+            window: Duration::from_secs(300),
+        }
+    }
+
+    #[test]
+    fn inspect_reports_lockout_and_reset_clears_it() {
+        let dir = tempdir().unwrap();
+        let settings = settings(dir.path().to_path_buf());
+        let store = FaillockStore::new(dir.path());
+        let now = UNIX_EPOCH + Duration::from_secs(10_000);
+        for offset in [100, 50] {
+            store
+                .record_failure("alice", settings.window, now - Duration::from_secs(offset))
+                .unwrap();
+        }
+
+        let inspect = FaillockArgs {
+            user: "alice".into(),
+            reset: false,
+        };
+        let summary = run_faillock_with(&inspect, &settings, now).unwrap();
+        assert_eq!(summary.recent, 2);
+        assert_eq!(summary.remaining_lock_secs, Some(200));
+        assert!(!summary.reset);
+
+        let reset = FaillockArgs {
+            user: "alice".into(),
+            reset: true,
+        };
+        let summary = run_faillock_with(&reset, &settings, now).unwrap();
+        assert!(summary.reset);
+        assert!(summary.failures.is_empty());
+        assert_eq!(summary.remaining_lock_secs, None);
+        assert!(!store.tally_path("alice").unwrap().exists());
+    }
+}
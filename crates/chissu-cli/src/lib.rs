@@ -1,10 +1,13 @@
+pub mod audit;
 pub mod auto_enroll;
 pub mod capture;
 pub mod cli;
 pub mod commands;
 pub mod config;
+pub mod device_pin;
 pub mod doctor;
 pub mod errors;
 pub mod faces;
+pub mod faillock;
 pub mod keyring;
 pub mod output;
@@ -3,14 +3,17 @@ use std::io::{self, Write};
 
 use serde_json::{json, Value};
 
+use crate::audit::AuditShowSummary;
 use crate::auto_enroll::AutoEnrollOutcome;
 use crate::capture::CaptureOutcome;
 use crate::cli::OutputMode;
+use crate::device_pin::DevicePinSummary;
 use crate::doctor::{CheckStatus, DoctorOutcome};
 use crate::errors::{AppError, AppResult};
 use crate::faces::{
     FaceComparisonOutcome, FaceEnrollmentOutcome, FaceExtractionOutcome, FaceRemovalOutcome,
 };
+use crate::faillock::FaillockSummary;
 use crate::keyring::KeyringCheckSummary;
 
 pub fn render_success(outcome: &CaptureOutcome, mode: OutputMode) -> AppResult<()> {
@@ -120,6 +123,9 @@ pub fn render_auto_enroll(
                 "Encrypted store: {}",
                 outcome.enrollment.store_path.display()
             );
+            if let Some(pin) = &outcome.device_pin {
+                println!("Camera pinned: {}", pin.display());
+            }
             if verbose {
                 if outcome.capture_deleted {
                     tracing::info!(
@@ -164,6 +170,7 @@ fn auto_enroll_json_payload(outcome: &AutoEnrollOutcome) -> Value {
         "captured_image_deleted": outcome.capture_deleted,
         "embedding_file_deleted": outcome.embedding_deleted,
         "faces_detected": outcome.faces_detected,
+        "device_pin": outcome.device_pin.as_ref().map(|path| path.display().to_string()),
     })
 }
 
@@ -196,6 +203,7 @@ mod tests {
             embedding_deleted: true,
             faces_detected: 1,
             enrollment,
+            device_pin: None,
             logs: vec![],
         };
 
@@ -209,6 +217,7 @@ mod tests {
         assert_eq!(payload["captured_image_deleted"], true);
         assert_eq!(payload["embedding_file_deleted"], true);
         assert_eq!(payload["faces_detected"], 1);
+        assert!(payload["device_pin"].is_null());
     }
 }
 
@@ -312,6 +321,140 @@ pub fn render_keyring_check(summary: &KeyringCheckSummary, mode: OutputMode) ->
     Ok(())
 }
 
+pub fn render_audit_show(summary: &AuditShowSummary, mode: OutputMode) -> AppResult<()> {
+    match mode {
+        OutputMode::Human => {
+            if summary.records.is_empty() {
+                println!(
+                    "No matching audit records in {}",
+                    summary.log_path.display()
+                );
+            }
+            for record in &summary.records {
+                let similarity = record
+                    .best_similarity
+                    .map_or_else(|| "-".to_string(), |value| format!("{value:.4}"));
+                let outcome = serde_json::to_value(record.outcome)?;
+                let marker = if record.observed { " [observe]" } else { "" };
+                let mut line = format!(
+                    "{} {} user={} tty={} {}{} frames={} similarity={}/{} device={} {}ms",
+                    record.timestamp,
+                    record.service,
+                    record.user,
+                    record.tty.as_deref().unwrap_or("-"),
+                    outcome.as_str().unwrap_or_default(),
+                    marker,
+                    record.frames_captured,
+                    similarity,
+                    record.similarity_threshold,
+                    record.device,
+                    record.duration_ms
+                );
+                if let Some(reason) = record.failure_reason.as_ref().or(record.detail.as_ref()) {
+                    line.push_str(&format!(" ({reason})"));
+                }
+                println!("{line}");
+            }
+            if summary.skipped_lines > 0 {
+                eprintln!(
+                    "Skipped {} unreadable line(s) in the audit log",
+                    summary.skipped_lines
+                );
+            }
+        }
+        OutputMode::Json => {
+            let stdout = io::stdout();
+            let mut handle = stdout.lock();
+            let payload = serde_json::to_string(&json!({
+                "log_path": summary.log_path.display().to_string(),
+                "records": summary.records,
+                "skipped_lines": summary.skipped_lines,
+            }))?;
+            handle.write_all(payload.as_bytes())?;
+            handle.write_all(b"\n")?;
+        }
+    }
+    Ok(())
+}
+
+pub fn render_faillock(summary: &FaillockSummary, mode: OutputMode) -> AppResult<()> {
+    match mode {
+        OutputMode::Human => {
+            if summary.reset {
+                println!("Cleared faillock tally for user '{}'", summary.user);
+                return Ok(());
+            }
+            println!(
+                "User '{}': {} recent failure(s) in the last {}s ({} recorded)",
+                summary.user,
+                summary.recent,
+                summary.window_secs,
+                summary.failures.len()
+            );
+            if summary.deny == 0 {
+                println!("Lockout disabled (faillock_deny = 0)");
+            } else if let Some(secs) = summary.remaining_lock_secs {
+                println!(
+                    "Locked out: {} failure limit reached; unlocks in {secs}s",
+                    summary.deny
+                );
+            } else {
+                println!("Not locked (limit {})", summary.deny);
+            }
+            println!("Tally file: {}", summary.tally_path.display());
+        }
+        OutputMode::Json => {
+            let stdout = io::stdout();
+            let mut handle = stdout.lock();
+            let payload = serde_json::to_string(&json!({
+                "user": summary.user,
+                "tally_path": summary.tally_path.display().to_string(),
+                "failures": summary.failures,
+                "recent": summary.recent,
+                "deny": summary.deny,
+                "window_secs": summary.window_secs,
+                "locked": summary.remaining_lock_secs.is_some(),
+                "remaining_lock_secs": summary.remaining_lock_secs,
+                "reset": summary.reset,
+            }))?;
+            handle.write_all(payload.as_bytes())?;
+            handle.write_all(b"\n")?;
+        }
+    }
+    Ok(())
+}
+
+pub fn render_device_pin(summary: &DevicePinSummary, mode: OutputMode) -> AppResult<()> {
+    match mode {
+        OutputMode::Human => {
+            println!(
+                "Pinned camera {} for user '{}': {}",
+                summary.device, summary.user, summary.identity
+            );
+            if let Some(previous) = summary.previous.as_ref() {
+                if *previous != summary.identity {
+                    println!("Replaced previous pin: {previous}");
+                }
+            }
+            println!("Pin file: {}", summary.pin_path.display());
+        }
+        OutputMode::Json => {
+            let stdout = io::stdout();
+            let mut handle = stdout.lock();
+            let payload = serde_json::to_string(&json!({
+                "user": summary.user,
+                "pin_path": summary.pin_path.display().to_string(),
+                "device": summary.device,
+                "identity": summary.identity,
+                "previous": summary.previous,
+            }))?;
+            handle.write_all(payload.as_bytes())?;
+            handle.write_all(b"\n")?;
+        }
+    }
+    Ok(())
+}
+
 pub fn render_doctor(outcome: &DoctorOutcome, mode: OutputMode) -> AppResult<()> {
     match mode {
         OutputMode::Human => {
@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+
+use chissu_cli::audit::AuditShowSummary;
+use chissu_cli::cli::{AuditCommands, AuditOutcomeArg, AuditShowArgs, OutputMode};
+use chissu_cli::commands::{AuditHandler, CommandHandler};
+use chissu_cli::errors::AppError;
+
+fn show_command() -> AuditCommands {
+    AuditCommands::Show(AuditShowArgs {
+        user: Some("alice".into()),
+        service: Some("sudo".into()),
+        outcome: Some(AuditOutcomeArg::Failure),
+        limit: Some(10),
+        log: None,
+    })
+}
+
+#[test]
+fn audit_handler_passes_filters_and_renders_summary() {
+    let rendered = Arc::new(Mutex::new(Vec::new()));
+    let handler = AuditHandler::with_dependencies(
+        show_command(),
+        |command| {
+            let AuditCommands::Show(args) = command;
+            assert_eq!(args.user.as_deref(), Some("alice"));
+            assert_eq!(args.outcome, Some(AuditOutcomeArg::Failure));
+            Ok(AuditShowSummary {
+                log_path: PathBuf::from("/var/log/chissu-pam/audit.jsonl"),
+                records: Vec::new(),
+                skipped_lines: 1,
+            })
+        },
+        {
+            let rendered = Arc::clone(&rendered);
+            move |summary, _mode| {
+                rendered.lock().unwrap().push(summary.skipped_lines);
+                Ok(())
+            }
+        },
+    );
+
+    let code = handler.execute(OutputMode::Json, false).unwrap();
+    assert_eq!(code, ExitCode::SUCCESS);
+    assert_eq!(*rendered.lock().unwrap(), vec![1]);
+}
+
+#[test]
+fn audit_handler_propagates_errors() {
+    let handler = AuditHandler::with_dependencies(
+        show_command(),
+        |_command| Err(AppError::AuditLogDisabled),
+        |_summary, _mode| Ok(()),
+    );
+
+    let err = handler.execute(OutputMode::Human, false).unwrap_err();
+    assert!(matches!(err, AppError::AuditLogDisabled));
+}
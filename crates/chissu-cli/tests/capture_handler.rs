@@ -29,6 +29,7 @@ fn sample_defaults() -> CaptureDefaults {
         device: Some("/dev/video0".into()),
         pixel_format: Some("Y16".into()),
         warmup_frames: Some(4),
+        video_devices: Vec::new(),
     }
 }
 
@@ -52,6 +53,7 @@ fn sample_outcome() -> CaptureOutcome {
             gain: None,
             auto_exposure: None,
             auto_gain: None,
+            skipped_devices: Vec::new(),
         },
         logs: vec![],
     }
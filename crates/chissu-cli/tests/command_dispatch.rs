@@ -2,11 +2,12 @@ use std::path::PathBuf;
 
 use chissu_cli::capture::CaptureHandler;
 use chissu_cli::cli::{
-    CaptureArgs, Commands, DoctorArgs, EnrollArgs, FaceExtractArgs, FacesCommands,
-    KeyringCheckArgs, KeyringCommands,
+    AuditCommands, AuditShowArgs, CaptureArgs, Commands, DoctorArgs, EnrollArgs, FaceExtractArgs,
+    FacesCommands, FaillockArgs, KeyringCheckArgs, KeyringCommands,
 };
 use chissu_cli::commands::{
-    CommandHandler, DoctorHandler, EnrollHandler, FacesHandler, KeyringHandler,
+    AuditHandler, CommandHandler, DoctorHandler, EnrollHandler, FacesHandler, FaillockHandler,
+    KeyringHandler,
 };
 
 fn sample_capture_args() -> CaptureArgs {
@@ -81,3 +82,22 @@ fn keyring_command_dispatches_keyring_handler() {
 fn doctor_command_dispatches_doctor_handler() {
     assert_dispatch::<DoctorHandler>(Commands::Doctor(DoctorArgs { polkit: false }));
 }
+
+#[test]
+fn faillock_command_dispatches_faillock_handler() {
+    assert_dispatch::<FaillockHandler>(Commands::Faillock(FaillockArgs {
+        user: "alice".into(),
+        reset: false,
+    }));
+}
+
+#[test]
+fn audit_command_dispatches_audit_handler() {
+    assert_dispatch::<AuditHandler>(Commands::Audit(AuditCommands::Show(AuditShowArgs {
+        user: None,
+        service: None,
+        outcome: None,
+        limit: None,
+        log: None,
+    })));
+}
@@ -38,6 +38,7 @@ fn sample_outcome() -> AutoEnrollOutcome {
             }],
             logs: vec!["enrolled".into()],
         },
+        device_pin: None,
         logs: vec!["ok".into()],
     }
 }
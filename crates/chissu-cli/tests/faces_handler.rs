@@ -2,7 +2,9 @@ use std::path::PathBuf;
 use std::process::ExitCode;
 use std::sync::{Arc, Mutex};
 
-use chissu_cli::cli::{FaceEnrollArgs, FaceExtractArgs, FacesCommands, OutputMode};
+use chissu_cli::cli::{
+    FaceEnrollArgs, FaceExtractArgs, FacePinDeviceArgs, FacesCommands, OutputMode,
+};
 use chissu_cli::commands::{CommandHandler, FacesHandler, FacesHandlerDeps};
 use chissu_cli::errors::AppError;
 use chissu_cli::faces::{FaceExtractionOutcome, FaceExtractionSummary};
@@ -18,6 +20,8 @@ fn faces_handler_extract_renders_success() {
                     success: true,
                     image_path: "img".into(),
                     output_path: "out".into(),
+                    image_width: 1,
+                    image_height: 1,
                     num_faces: 1,
                     faces: vec![],
                     landmark_model: "landmark".into(),
@@ -87,3 +91,39 @@ fn faces_handler_enroll_surfaces_errors() {
         other => panic!("unexpected error: {other}"),
     }
 }
+
+#[test]
+fn faces_handler_pin_device_surfaces_untrusted_driver() {
+    let deps = FacesHandlerDeps::new(
+        |dir| Ok(dir),
+        |_config| panic!("extract should not run"),
+        |_config| panic!("compare should not run"),
+        |_config| panic!("enroll should not run"),
+        |_config| panic!("remove should not run"),
+        |_outcome, _mode| Ok(()),
+        |_outcome, _mode| Ok(()),
+        |_outcome, _mode| Ok(()),
+        |_outcome, _mode| Ok(()),
+    )
+    .with_device_pinner(
+        |args| {
+            assert_eq!(args.device.as_deref(), Some("/dev/video9"));
+            Err(AppError::UntrustedDevice {
+                device: "/dev/video9".into(),
+                message: "driver 'v4l2 loopback' is a known virtual camera".into(),
+            })
+        },
+        |_summary, _mode| panic!("render should not run"),
+    );
+
+    let handler = FacesHandler::with_dependencies(
+        FacesCommands::PinDevice(FacePinDeviceArgs {
+            user: "alice".into(),
+            device: Some("/dev/video9".into()),
+        }),
+        deps,
+    );
+
+    let err = handler.execute(OutputMode::Human, false).unwrap_err();
+    assert!(matches!(err, AppError::UntrustedDevice { .. }));
+}
@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+
+use chissu_cli::cli::{FaillockArgs, OutputMode};
+use chissu_cli::commands::{CommandHandler, FaillockHandler};
+use chissu_cli::errors::AppError;
+use chissu_cli::faillock::FaillockSummary;
+
+fn sample_args(reset: bool) -> FaillockArgs {
+    FaillockArgs {
+        user: "alice".into(),
+        reset,
+    }
+}
+
+#[test]
+fn faillock_handler_passes_args_and_renders_summary() {
+    let rendered = Arc::new(Mutex::new(Vec::new()));
+    let handler = FaillockHandler::with_dependencies(
+        sample_args(true),
+        |args| {
+            assert!(args.reset);
+            Ok(FaillockSummary {
+                user: args.user.clone(),
+                tally_path: PathBuf::from("/var/lib/chissu-pam/faillock/alice.json"),
+                failures: Vec::new(),
+                recent: 0,
+                deny: 3,
+                window_secs: 900,
+                remaining_lock_secs: None,
+                reset: true,
+            })
+        },
+        {
+            let rendered = Arc::clone(&rendered);
+            move |summary, _mode| {
+                rendered
+                    .lock()
+                    .unwrap()
+                    .push((summary.user.clone(), summary.reset));
+                Ok(())
+            }
+        },
+    );
+
+    let code = handler.execute(OutputMode::Human, false).unwrap();
+    assert_eq!(code, ExitCode::SUCCESS);
+    assert_eq!(*rendered.lock().unwrap(), vec![("alice".to_string(), true)]);
+}
+
+#[test]
+fn faillock_handler_propagates_errors() {
+    let handler = FaillockHandler::with_dependencies(
+        sample_args(false),
+        |args| {
+            Err(AppError::InvalidUser {
+                user: args.user.clone(),
+                message: "nope".into(),
+            })
+        },
+        |_summary, _mode| Ok(()),
+    );
+
+    let err = handler.execute(OutputMode::Json, false).unwrap_err();
+    assert!(matches!(err, AppError::InvalidUser { .. }));
+}
@@ -1,5 +1,7 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::io;
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
@@ -9,6 +11,8 @@ use thiserror::Error;
 pub const PRIMARY_CONFIG_PATH: &str = "/etc/chissu-pam/config.toml";
 pub const SECONDARY_CONFIG_PATH: &str = "/usr/local/etc/chissu-pam/config.toml";
 pub const DEFAULT_SIMILARITY_THRESHOLD: f64 = 0.9;
+pub const DEFAULT_EUCLIDEAN_THRESHOLD: f64 = 0.6;
+pub const DEFAULT_NORMALIZED_EUCLIDEAN_THRESHOLD: f64 = 0.45;
 pub const DEFAULT_TIMEOUT_SECS: u64 = 5;
 pub const DEFAULT_INTERVAL_MILLIS: u64 = 500;
 pub const DEFAULT_VIDEO_DEVICE: &str = "/dev/video0";
@@ -16,6 +20,184 @@ pub const DEFAULT_STORE_DIR: &str = "/var/lib/chissu-pam/embeddings";
 pub const DEFAULT_PIXEL_FORMAT: &str = "Y16";
 pub const DEFAULT_WARMUP_FRAMES: u32 = 4;
 pub const DEFAULT_JITTERS: u32 = 1;
+pub const DEFAULT_AUTHD_SOCKET: &str = "/run/chissu-pam/authd.sock";
+pub const DEFAULT_AUTHD_CAMERA_IDLE_SECS: u64 = 10;
+pub const DEFAULT_FAILLOCK_DIR: &str = "/var/lib/chissu-pam/faillock";
+pub const DEFAULT_FAILLOCK_WINDOW_SECS: u64 = 900;
+pub const DEFAULT_AUDIT_LOG_MAX_BYTES: u64 = 10 * 1024 * 1024;
+pub const DEFAULT_AUDIT_LOG_KEEP: u32 = 5;
+pub const DEFAULT_FUSION_WINDOW: u32 = 5;
+pub const DEFAULT_FUSION_REQUIRED: u32 = 3;
+pub const DEFAULT_ADAPTIVE_UPDATE_MARGIN: f64 = 0.05;
+pub const DEFAULT_ADAPTIVE_UPDATE_MAX_ENTRIES: u32 = 5;
+pub const DEFAULT_DEVICE_PIN_DIR: &str = "/var/lib/chissu-pam/devices";
+pub const DEFAULT_CONFIRM_TIMEOUT_SECS: u64 = 10;
+pub const DEFAULT_PROGRESS_INTERVAL_MILLIS: u64 = 2000;
+/// Drivers of common virtual cameras (v4l2loopback, akvcam, the vivid test
+/// driver), as reported by `VIDIOC_QUERYCAP`.
+pub const DEFAULT_VIRTUAL_CAMERA_DRIVERS: &[&str] =
+    &["v4l2 loopback", "v4l2loopback", "akvcam", "vivid"];
+
+/// How embeddings are compared; see `chissu_face_core::faces::metric`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SimilarityMetric {
+    /// Cosine similarity; `similarity_threshold` is a minimum.
+    #[default]
+    Cosine,
+    /// Euclidean distance; `similarity_threshold` is a maximum.
+    Euclidean,
+    /// Euclidean distance of unit-length embeddings; `similarity_threshold` is a maximum.
+    NormalizedEuclidean,
+}
+
+impl SimilarityMetric {
+    /// Threshold used when `similarity_threshold` is not set.
+    pub fn default_threshold(self) -> f64 {
+        match self {
+            SimilarityMetric::Cosine => DEFAULT_SIMILARITY_THRESHOLD,
+            SimilarityMetric::Euclidean => DEFAULT_EUCLIDEAN_THRESHOLD,
+            SimilarityMetric::NormalizedEuclidean => DEFAULT_NORMALIZED_EUCLIDEAN_THRESHOLD,
+        }
+    }
+
+    /// Values a threshold can meaningfully take: a cosine similarity or a
+    /// non-negative distance, at most 2.0 between unit-length embeddings.
+    pub fn threshold_range(self) -> RangeInclusive<f64> {
+        match self {
+            SimilarityMetric::Cosine => -1.0..=1.0,
+            SimilarityMetric::Euclidean => 0.0..=f64::MAX,
+            SimilarityMetric::NormalizedEuclidean => 0.0..=2.0,
+        }
+    }
+
+    /// The stricter of two thresholds: the higher similarity for cosine, the
+    /// shorter distance for the Euclidean metrics.
+    pub fn stricter_threshold(self, a: f64, b: f64) -> f64 {
+        match self {
+            SimilarityMetric::Cosine => a.max(b),
+            SimilarityMetric::Euclidean | SimilarityMetric::NormalizedEuclidean => a.min(b),
+        }
+    }
+}
+
+/// Eye activity required after a face matches; see `chissu_face_core::faces::liveness`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LivenessMode {
+    #[default]
+    Off,
+    Blink,
+    EyeMotion,
+}
+
+/// Which PAM contexts may open the camera. Anything stricter than the
+/// policy allows returns `PAM_IGNORE` before capture. Variants are ordered
+/// from most to least strict.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionPolicy {
+    /// Skip remote requests and logind sessions without a seat.
+    #[default]
+    Local,
+    /// Skip remote requests but allow seatless sessions (containers, VMs).
+    NoRemote,
+    /// Never skip.
+    Any,
+}
+
+/// Whether a face decision is returned to PAM or only logged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthMode {
+    /// Return the face decision to the PAM stack.
+    #[default]
+    Enforce,
+    /// Capture and match as usual, log the decision, and return `PAM_IGNORE`.
+    Observe,
+}
+
+/// Which detected faces may be matched or enrolled; see
+/// `chissu_face_core::faces::selection`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaceSelection {
+    #[default]
+    All,
+    Largest,
+    RejectMultiple,
+}
+
+/// How per-frame scores combine into a match; see `chissu_face_core::faces::fusion`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fusion {
+    #[default]
+    Single,
+    KOfN,
+    Average,
+}
+
+/// What PAM returns when the camera is absent, busy, or not accessible.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceErrorAction {
+    /// `PAM_IGNORE` right away, so the stack moves on to the password prompt.
+    #[default]
+    Ignore,
+    /// `PAM_SYSTEM_ERR`, like any other capture failure.
+    Error,
+}
+
+/// What PAM returns while a user is locked out of face authentication.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FaillockAction {
+    /// `PAM_IGNORE`, so the stack moves on to the password prompt.
+    #[default]
+    Ignore,
+    /// `PAM_AUTH_ERR`.
+    Deny,
+}
+
+/// What PAM returns when SIGINT or SIGTERM interrupts a capture. Either way
+/// the signal is passed on to the caller's own handler afterwards.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InterruptAction {
+    /// `PAM_ABORT`.
+    #[default]
+    Abort,
+    /// `PAM_IGNORE`, so a caller that survives the signal moves on to the
+    /// password prompt.
+    Ignore,
+}
+
+/// When the capture device must match the identity pinned at enrolment.
+/// Variants are ordered from least to most strict.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DevicePinMode {
+    /// Skip the comparison; the virtual-driver denylist still applies.
+    Off,
+    /// Compare when a pin exists for the user.
+    #[default]
+    IfPinned,
+    /// Fail users without a pin.
+    Required,
+}
+
+/// How eagerly IR frames that look like screen replays or prints are rejected.
+/// Variants are ordered from least to most strict.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AntiSpoofSensitivity {
+    #[default]
+    Off,
+    Low,
+    Medium,
+    High,
+}
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -27,44 +209,529 @@ pub enum SecretServiceSessionMode {
     Wayland,
 }
 
+/// One `[[video_devices]]` entry. Unset keys fall back to the top-level
+/// `pixel_format` and `warmup_frames`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct VideoDeviceEntry {
+    pub device: String,
+    pub pixel_format: Option<String>,
+    pub warmup_frames: Option<u32>,
+}
+
+/// A camera to try, with its capture settings resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VideoDevice {
+    pub device: String,
+    pub pixel_format: String,
+    pub warmup_frames: u32,
+}
+
+/// `[messages]` templates for PAM conversation text. Unset keys keep the
+/// built-in wording and an empty string suppresses the message; see
+/// `pam-chissu` for the placeholders each message supports.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct MessageTemplates {
+    pub success: Option<String>,
+    pub no_face_hint: Option<String>,
+    pub liveness_hint: Option<String>,
+    pub embeddings_missing: Option<String>,
+    pub no_face_detected: Option<String>,
+    pub threshold_not_reached: Option<String>,
+    pub liveness_not_confirmed: Option<String>,
+    pub spoof_suspected: Option<String>,
+    pub locked_out: Option<String>,
+    pub secret_service_unavailable: Option<String>,
+    pub device_unavailable: Option<String>,
+    pub untrusted_device: Option<String>,
+    pub not_infrared: Option<String>,
+    pub frozen_stream: Option<String>,
+    pub confirm_prompt: Option<String>,
+    pub confirmation_timeout: Option<String>,
+    pub password_prompt: Option<String>,
+    pub countdown: Option<String>,
+    pub face_found: Option<String>,
+    pub move_closer: Option<String>,
+    pub too_dark: Option<String>,
+}
+
+impl MessageTemplates {
+    /// Replaces each template that `other` sets, keeping the rest.
+    fn merge(&mut self, other: &MessageTemplates) {
+        let fields = [
+            (&mut self.success, &other.success),
+            (&mut self.no_face_hint, &other.no_face_hint),
+            (&mut self.liveness_hint, &other.liveness_hint),
+            (&mut self.embeddings_missing, &other.embeddings_missing),
+            (&mut self.no_face_detected, &other.no_face_detected),
+            (
+                &mut self.threshold_not_reached,
+                &other.threshold_not_reached,
+            ),
+            (
+                &mut self.liveness_not_confirmed,
+                &other.liveness_not_confirmed,
+            ),
+            (&mut self.spoof_suspected, &other.spoof_suspected),
+            (&mut self.locked_out, &other.locked_out),
+            (
+                &mut self.secret_service_unavailable,
+                &other.secret_service_unavailable,
+            ),
+            (&mut self.device_unavailable, &other.device_unavailable),
+            (&mut self.untrusted_device, &other.untrusted_device),
+            (&mut self.not_infrared, &other.not_infrared),
+            (&mut self.frozen_stream, &other.frozen_stream),
+            (&mut self.confirm_prompt, &other.confirm_prompt),
+            (&mut self.confirmation_timeout, &other.confirmation_timeout),
+            (&mut self.password_prompt, &other.password_prompt),
+            (&mut self.countdown, &other.countdown),
+            (&mut self.face_found, &other.face_found),
+            (&mut self.move_closer, &other.move_closer),
+            (&mut self.too_dark, &other.too_dark),
+        ];
+        for (target, value) in fields {
+            if value.is_some() {
+                target.clone_from(value);
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct ConfigFile {
     pub similarity_threshold: Option<f64>,
+    pub similarity_metric: Option<SimilarityMetric>,
     pub capture_timeout_secs: Option<u64>,
     pub frame_interval_millis: Option<u64>,
     pub embedding_store_dir: Option<PathBuf>,
     pub video_device: Option<String>,
     pub pixel_format: Option<String>,
     pub warmup_frames: Option<u32>,
+    pub video_devices: Option<Vec<VideoDeviceEntry>>,
     pub jitters: Option<u32>,
     pub landmark_model: Option<PathBuf>,
     pub encoder_model: Option<PathBuf>,
     pub require_secret_service: Option<bool>,
     pub secret_service_session: Option<SecretServiceSessionMode>,
+    pub authd_socket: Option<PathBuf>,
+    pub authd_camera_idle_secs: Option<u64>,
+    pub liveness: Option<LivenessMode>,
+    pub anti_spoof: Option<AntiSpoofSensitivity>,
+    pub face_selection: Option<FaceSelection>,
+    pub face_min_size: Option<u32>,
+    pub face_max_center_offset: Option<f64>,
+    pub fusion: Option<Fusion>,
+    pub fusion_window: Option<u32>,
+    pub fusion_required: Option<u32>,
+    pub fusion_max_face_shift: Option<f64>,
+    pub adaptive_update: Option<bool>,
+    pub adaptive_update_margin: Option<f64>,
+    pub adaptive_update_max_entries: Option<u32>,
+    pub session_policy: Option<SessionPolicy>,
+    pub device_absent_action: Option<DeviceErrorAction>,
+    pub device_busy_action: Option<DeviceErrorAction>,
+    pub device_denied_action: Option<DeviceErrorAction>,
+    pub device_pin: Option<DevicePinMode>,
+    pub device_pin_dir: Option<PathBuf>,
+    pub require_ir: Option<bool>,
+    pub stream_integrity: Option<bool>,
+    pub virtual_camera_drivers: Option<Vec<String>>,
+    pub concurrent_prompt: Option<bool>,
+    pub confirm_match: Option<bool>,
+    pub confirm_timeout_secs: Option<u64>,
+    pub interrupt_action: Option<InterruptAction>,
+    pub progress_messages: Option<bool>,
+    pub progress_interval_millis: Option<u64>,
+    pub faillock_deny: Option<u32>,
+    pub faillock_window_secs: Option<u64>,
+    pub faillock_action: Option<FaillockAction>,
+    pub faillock_dir: Option<PathBuf>,
+    pub audit_log: Option<PathBuf>,
+    pub audit_log_max_bytes: Option<u64>,
+    pub audit_log_keep: Option<u32>,
+    pub enabled: Option<bool>,
+    pub mode: Option<AuthMode>,
+    #[serde(default)]
+    pub messages: MessageTemplates,
+    #[serde(default)]
+    pub service: BTreeMap<String, ConfigOverrides>,
+    #[serde(default)]
+    pub user: BTreeMap<String, ConfigOverrides>,
+}
+
+/// Settings that `[service.<name>]` and `[user.<name>]` tables may override.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct ConfigOverrides {
+    pub enabled: Option<bool>,
+    pub mode: Option<AuthMode>,
+    pub similarity_threshold: Option<f64>,
+    pub similarity_metric: Option<SimilarityMetric>,
+    pub capture_timeout_secs: Option<u64>,
+    pub frame_interval_millis: Option<u64>,
+    pub video_device: Option<String>,
+    pub pixel_format: Option<String>,
+    pub warmup_frames: Option<u32>,
+    pub video_devices: Option<Vec<VideoDeviceEntry>>,
+    pub jitters: Option<u32>,
+    pub liveness: Option<LivenessMode>,
+    pub anti_spoof: Option<AntiSpoofSensitivity>,
+    pub face_selection: Option<FaceSelection>,
+    pub face_min_size: Option<u32>,
+    pub face_max_center_offset: Option<f64>,
+    pub fusion: Option<Fusion>,
+    pub fusion_window: Option<u32>,
+    pub fusion_required: Option<u32>,
+    pub fusion_max_face_shift: Option<f64>,
+    pub adaptive_update: Option<bool>,
+    pub adaptive_update_margin: Option<f64>,
+    pub adaptive_update_max_entries: Option<u32>,
+    pub session_policy: Option<SessionPolicy>,
+    pub device_absent_action: Option<DeviceErrorAction>,
+    pub device_busy_action: Option<DeviceErrorAction>,
+    pub device_denied_action: Option<DeviceErrorAction>,
+    pub device_pin: Option<DevicePinMode>,
+    pub require_ir: Option<bool>,
+    pub stream_integrity: Option<bool>,
+    pub concurrent_prompt: Option<bool>,
+    pub confirm_match: Option<bool>,
+    pub confirm_timeout_secs: Option<u64>,
+    pub interrupt_action: Option<InterruptAction>,
+    pub progress_messages: Option<bool>,
+    pub progress_interval_millis: Option<u64>,
+    pub require_secret_service: Option<bool>,
+    #[serde(default)]
+    pub messages: MessageTemplates,
+}
+
+impl ConfigOverrides {
+    fn apply_to(&self, file: &mut ConfigFile) {
+        if self.enabled.is_some() {
+            file.enabled = self.enabled;
+        }
+        if self.mode.is_some() {
+            file.mode = self.mode;
+        }
+        if self.similarity_threshold.is_some() {
+            file.similarity_threshold = self.similarity_threshold;
+        }
+        if self.similarity_metric.is_some() {
+            file.similarity_metric = self.similarity_metric;
+        }
+        if self.capture_timeout_secs.is_some() {
+            file.capture_timeout_secs = self.capture_timeout_secs;
+        }
+        if self.frame_interval_millis.is_some() {
+            file.frame_interval_millis = self.frame_interval_millis;
+        }
+        if self.video_device.is_some() {
+            file.video_device = self.video_device.clone();
+            // A scoped single device wins over an inherited fallback list.
+            file.video_devices = None;
+        }
+        if self.video_devices.is_some() {
+            file.video_devices = self.video_devices.clone();
+        }
+        if self.pixel_format.is_some() {
+            file.pixel_format = self.pixel_format.clone();
+        }
+        if self.warmup_frames.is_some() {
+            file.warmup_frames = self.warmup_frames;
+        }
+        if self.jitters.is_some() {
+            file.jitters = self.jitters;
+        }
+        if self.liveness.is_some() {
+            file.liveness = self.liveness;
+        }
+        if self.anti_spoof.is_some() {
+            file.anti_spoof = self.anti_spoof;
+        }
+        if self.face_selection.is_some() {
+            file.face_selection = self.face_selection;
+        }
+        if self.face_min_size.is_some() {
+            file.face_min_size = self.face_min_size;
+        }
+        if self.face_max_center_offset.is_some() {
+            file.face_max_center_offset = self.face_max_center_offset;
+        }
+        if self.fusion.is_some() {
+            file.fusion = self.fusion;
+        }
+        if self.fusion_window.is_some() {
+            file.fusion_window = self.fusion_window;
+        }
+        if self.fusion_required.is_some() {
+            file.fusion_required = self.fusion_required;
+        }
+        if self.fusion_max_face_shift.is_some() {
+            file.fusion_max_face_shift = self.fusion_max_face_shift;
+        }
+        if self.adaptive_update.is_some() {
+            file.adaptive_update = self.adaptive_update;
+        }
+        if self.adaptive_update_margin.is_some() {
+            file.adaptive_update_margin = self.adaptive_update_margin;
+        }
+        if self.adaptive_update_max_entries.is_some() {
+            file.adaptive_update_max_entries = self.adaptive_update_max_entries;
+        }
+        if self.session_policy.is_some() {
+            file.session_policy = self.session_policy;
+        }
+        if self.device_absent_action.is_some() {
+            file.device_absent_action = self.device_absent_action;
+        }
+        if self.device_busy_action.is_some() {
+            file.device_busy_action = self.device_busy_action;
+        }
+        if self.device_denied_action.is_some() {
+            file.device_denied_action = self.device_denied_action;
+        }
+        if self.device_pin.is_some() {
+            file.device_pin = self.device_pin;
+        }
+        if self.require_ir.is_some() {
+            file.require_ir = self.require_ir;
+        }
+        if self.stream_integrity.is_some() {
+            file.stream_integrity = self.stream_integrity;
+        }
+        if self.concurrent_prompt.is_some() {
+            file.concurrent_prompt = self.concurrent_prompt;
+        }
+        if self.confirm_match.is_some() {
+            file.confirm_match = self.confirm_match;
+        }
+        if self.confirm_timeout_secs.is_some() {
+            file.confirm_timeout_secs = self.confirm_timeout_secs;
+        }
+        if self.interrupt_action.is_some() {
+            file.interrupt_action = self.interrupt_action;
+        }
+        if self.progress_messages.is_some() {
+            file.progress_messages = self.progress_messages;
+        }
+        if self.progress_interval_millis.is_some() {
+            file.progress_interval_millis = self.progress_interval_millis;
+        }
+        if self.require_secret_service.is_some() {
+            file.require_secret_service = self.require_secret_service;
+        }
+        file.messages.merge(&self.messages);
+    }
+
+    /// Puts back what a service scope decided wherever a later user scope
+    /// loosened it. `service_scoped` is the configuration as it stood after
+    /// the service table and before the user table.
+    ///
+    /// `enabled = false` is final. Ordered settings may only get stricter:
+    /// `mode`, `similarity_threshold`, `liveness`, `anti_spoof`,
+    /// `session_policy`, `device_pin`, `require_ir`, `stream_integrity`,
+    /// `confirm_match` and `require_secret_service`. Settings without such an
+    /// order keep the service's value: the fusion keys, `face_selection` and
+    /// the camera list.
+    fn restrict(&self, service_scoped: &ConfigFile, file: &mut ConfigFile) {
+        if self.enabled == Some(false) {
+            file.enabled = Some(false);
+        }
+        if self.mode == Some(AuthMode::Enforce) {
+            file.mode = self.mode;
+        }
+        if let Some(threshold) = self.similarity_threshold {
+            let metric = service_scoped.similarity_metric.unwrap_or_default();
+            let threshold = match file.similarity_threshold {
+                // A threshold for another metric cannot be compared.
+                Some(user) if file.similarity_metric.unwrap_or_default() == metric => {
+                    metric.stricter_threshold(threshold, user)
+                }
+                _ => threshold,
+            };
+            file.similarity_metric = Some(metric);
+            file.similarity_threshold = Some(threshold);
+        }
+        if self.liveness.is_some_and(|mode| mode != LivenessMode::Off)
+            && file.liveness == Some(LivenessMode::Off)
+        {
+            file.liveness = self.liveness;
+        }
+        if let Some(sensitivity) = self.anti_spoof {
+            file.anti_spoof = file.anti_spoof.max(Some(sensitivity));
+        }
+        if let Some(policy) = self.session_policy {
+            file.session_policy = Some(file.session_policy.map_or(policy, |user| user.min(policy)));
+        }
+        if let Some(pin) = self.device_pin {
+            file.device_pin = file.device_pin.max(Some(pin));
+        }
+        if self.require_ir == Some(true) {
+            file.require_ir = Some(true);
+        }
+        if self.stream_integrity == Some(true) {
+            file.stream_integrity = Some(true);
+        }
+        if self.confirm_match == Some(true) {
+            file.confirm_match = Some(true);
+        }
+        if self.require_secret_service == Some(true) {
+            file.require_secret_service = Some(true);
+        }
+        if self.fusion.is_some() {
+            file.fusion = self.fusion;
+        }
+        if self.fusion_window.is_some() {
+            file.fusion_window = self.fusion_window;
+        }
+        if self.fusion_required.is_some() {
+            file.fusion_required = self.fusion_required;
+        }
+        if self.fusion_max_face_shift.is_some() {
+            file.fusion_max_face_shift = self.fusion_max_face_shift;
+        }
+        if self.face_selection.is_some() {
+            file.face_selection = self.face_selection;
+        }
+        if self.video_device.is_some() || self.video_devices.is_some() {
+            file.video_device = service_scoped.video_device.clone();
+            file.video_devices = service_scoped.video_devices.clone();
+        }
+    }
+}
+
+impl ConfigFile {
+    /// Flattens the matching `[service.<name>]` and then `[user.<name>]` tables
+    /// over the top-level keys, so the most specific scope wins. The
+    /// exception is a service's security settings, which a user table can
+    /// tighten but not loosen; see [`ConfigOverrides::restrict`].
+    pub fn scoped(&self, service: Option<&str>, user: Option<&str>) -> ConfigFile {
+        let mut merged = self.clone();
+        let service = service.and_then(|name| self.service.get(name));
+        if let Some(overrides) = service {
+            overrides.apply_to(&mut merged);
+        }
+        if let Some(overrides) = user.and_then(|name| self.user.get(name)) {
+            let service_scoped = merged.clone();
+            overrides.apply_to(&mut merged);
+            if let Some(service) = service {
+                service.restrict(&service_scoped, &mut merged);
+            }
+        }
+        merged
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ResolvedConfig {
+    /// Minimum similarity for cosine, maximum distance for the Euclidean metrics.
     pub similarity_threshold: f64,
+    pub similarity_metric: SimilarityMetric,
     pub capture_timeout: Duration,
     pub frame_interval: Duration,
     pub embedding_store_dir: PathBuf,
+    /// First entry of `video_devices`.
     pub video_device: String,
     pub pixel_format: String,
     pub warmup_frames: u32,
+    /// Cameras to try in order; `video_device` alone unless
+    /// `[[video_devices]]` is set. Never empty.
+    pub video_devices: Vec<VideoDevice>,
     pub jitters: u32,
     pub landmark_model: Option<PathBuf>,
     pub encoder_model: Option<PathBuf>,
     pub require_secret_service: bool,
     pub secret_service_session: SecretServiceSessionMode,
+    pub authd_socket: PathBuf,
+    pub authd_camera_idle: Duration,
+    pub liveness: LivenessMode,
+    pub anti_spoof: AntiSpoofSensitivity,
+    pub face_selection: FaceSelection,
+    /// Minimum face size in pixels; zero disables the check.
+    pub face_min_size: u32,
+    /// Allowed face distance from the frame centre (0.0–1.0); `None` disables it.
+    pub face_max_center_offset: Option<f64>,
+    pub fusion: Fusion,
+    /// Frames in the fusion window (n).
+    pub fusion_window: u32,
+    /// Matching frames needed in `k_of_n` mode (k).
+    pub fusion_required: u32,
+    /// Allowed face movement between scored frames; `None` disables the check.
+    pub fusion_max_face_shift: Option<f64>,
+    /// Add live embeddings from confident matches to the user's store.
+    pub adaptive_update: bool,
+    /// How far past `similarity_threshold` a match must be to be learned from.
+    pub adaptive_update_margin: f64,
+    /// Auto-added entries kept per user; the oldest are replaced first.
+    pub adaptive_update_max_entries: u32,
+    pub session_policy: SessionPolicy,
+    pub device_absent_action: DeviceErrorAction,
+    pub device_busy_action: DeviceErrorAction,
+    pub device_denied_action: DeviceErrorAction,
+    pub device_pin: DevicePinMode,
+    /// Per-user camera identities recorded at enrolment.
+    pub device_pin_dir: PathBuf,
+    /// Drivers refused as virtual cameras; empty allows every driver.
+    pub virtual_camera_drivers: Vec<String>,
+    /// Refuse colour streams and streams without IR lighting.
+    pub require_ir: bool,
+    /// Refuse streams that repeat frames or whose buffer metadata stalls.
+    pub stream_integrity: bool,
+    /// Open a password prompt while the camera runs instead of after it.
+    pub concurrent_prompt: bool,
+    /// Ask the user to press Enter after a face match before succeeding.
+    pub confirm_match: bool,
+    /// How long the confirmation prompt may take to be answered.
+    pub confirm_timeout: Duration,
+    pub interrupt_action: InterruptAction,
+    /// Send countdown and positioning hints while the camera runs.
+    pub progress_messages: bool,
+    /// Least time between two progress messages.
+    pub progress_interval: Duration,
+    /// Consecutive failures that trigger a lockout; zero disables it.
+    pub faillock_deny: u32,
+    pub faillock_window: Duration,
+    pub faillock_action: FaillockAction,
+    pub faillock_dir: PathBuf,
+    /// JSONL audit log path; `None` disables auditing.
+    pub audit_log: Option<PathBuf>,
+    pub audit_log_max_bytes: u64,
+    /// Rotated files kept next to the active log.
+    pub audit_log_keep: u32,
+    pub messages: MessageTemplates,
+    pub enabled: bool,
+    pub mode: AuthMode,
 }
 
 impl ResolvedConfig {
     pub fn from_raw(raw: ConfigFile) -> Self {
+        let similarity_metric = raw.similarity_metric.unwrap_or_default();
+        let pixel_format = raw
+            .pixel_format
+            .unwrap_or_else(|| DEFAULT_PIXEL_FORMAT.to_string());
+        let warmup_frames = raw.warmup_frames.unwrap_or(DEFAULT_WARMUP_FRAMES);
+        let mut video_devices: Vec<VideoDevice> = raw
+            .video_devices
+            .unwrap_or_default()
+            .into_iter()
+            .map(|entry| VideoDevice {
+                device: entry.device,
+                pixel_format: entry.pixel_format.unwrap_or_else(|| pixel_format.clone()),
+                warmup_frames: entry.warmup_frames.unwrap_or(warmup_frames),
+            })
+            .collect();
+        if video_devices.is_empty() {
+            video_devices.push(VideoDevice {
+                device: raw
+                    .video_device
+                    .unwrap_or_else(|| DEFAULT_VIDEO_DEVICE.to_string()),
+                pixel_format: pixel_format.clone(),
+                warmup_frames,
+            });
+        }
         Self {
             similarity_threshold: raw
                 .similarity_threshold
-                .unwrap_or(DEFAULT_SIMILARITY_THRESHOLD),
+                .unwrap_or_else(|| similarity_metric.default_threshold()),
+            similarity_metric,
             capture_timeout: Duration::from_secs(
                 raw.capture_timeout_secs
                     .unwrap_or(DEFAULT_TIMEOUT_SECS)
@@ -76,20 +743,96 @@ impl ResolvedConfig {
             embedding_store_dir: raw
                 .embedding_store_dir
                 .unwrap_or_else(|| PathBuf::from(DEFAULT_STORE_DIR)),
-            video_device: raw
-                .video_device
-                .unwrap_or_else(|| DEFAULT_VIDEO_DEVICE.to_string()),
-            pixel_format: raw
-                .pixel_format
-                .unwrap_or_else(|| DEFAULT_PIXEL_FORMAT.to_string()),
-            warmup_frames: raw.warmup_frames.unwrap_or(DEFAULT_WARMUP_FRAMES),
+            video_device: video_devices[0].device.clone(),
+            pixel_format,
+            warmup_frames,
+            video_devices,
             jitters: raw.jitters.unwrap_or(DEFAULT_JITTERS),
             landmark_model: raw.landmark_model,
             encoder_model: raw.encoder_model,
             require_secret_service: raw.require_secret_service.unwrap_or(true),
             secret_service_session: raw.secret_service_session.unwrap_or_default(),
+            authd_socket: raw
+                .authd_socket
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_AUTHD_SOCKET)),
+            authd_camera_idle: Duration::from_secs(
+                raw.authd_camera_idle_secs
+                    .unwrap_or(DEFAULT_AUTHD_CAMERA_IDLE_SECS),
+            ),
+            liveness: raw.liveness.unwrap_or_default(),
+            anti_spoof: raw.anti_spoof.unwrap_or_default(),
+            face_selection: raw.face_selection.unwrap_or_default(),
+            face_min_size: raw.face_min_size.unwrap_or(0),
+            face_max_center_offset: raw.face_max_center_offset,
+            fusion: raw.fusion.unwrap_or_default(),
+            fusion_window: raw.fusion_window.unwrap_or(DEFAULT_FUSION_WINDOW).max(1),
+            fusion_required: raw
+                .fusion_required
+                .unwrap_or(DEFAULT_FUSION_REQUIRED)
+                .max(1),
+            fusion_max_face_shift: raw.fusion_max_face_shift,
+            adaptive_update: raw.adaptive_update.unwrap_or(false),
+            adaptive_update_margin: raw
+                .adaptive_update_margin
+                .unwrap_or(DEFAULT_ADAPTIVE_UPDATE_MARGIN)
+                .max(0.0),
+            adaptive_update_max_entries: raw
+                .adaptive_update_max_entries
+                .unwrap_or(DEFAULT_ADAPTIVE_UPDATE_MAX_ENTRIES)
+                .max(1),
+            session_policy: raw.session_policy.unwrap_or_default(),
+            device_absent_action: raw.device_absent_action.unwrap_or_default(),
+            device_busy_action: raw.device_busy_action.unwrap_or_default(),
+            device_denied_action: raw.device_denied_action.unwrap_or_default(),
+            device_pin: raw.device_pin.unwrap_or_default(),
+            device_pin_dir: raw
+                .device_pin_dir
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_DEVICE_PIN_DIR)),
+            virtual_camera_drivers: raw.virtual_camera_drivers.unwrap_or_else(|| {
+                DEFAULT_VIRTUAL_CAMERA_DRIVERS
+                    .iter()
+                    .map(|driver| driver.to_string())
+                    .collect()
+            }),
+            require_ir: raw.require_ir.unwrap_or(false),
+            stream_integrity: raw.stream_integrity.unwrap_or(false),
+            concurrent_prompt: raw.concurrent_prompt.unwrap_or(false),
+            confirm_match: raw.confirm_match.unwrap_or(false),
+            confirm_timeout: Duration::from_secs(
+                raw.confirm_timeout_secs
+                    .unwrap_or(DEFAULT_CONFIRM_TIMEOUT_SECS)
+                    .max(1),
+            ),
+            interrupt_action: raw.interrupt_action.unwrap_or_default(),
+            progress_messages: raw.progress_messages.unwrap_or(true),
+            progress_interval: Duration::from_millis(
+                raw.progress_interval_millis
+                    .unwrap_or(DEFAULT_PROGRESS_INTERVAL_MILLIS),
+            ),
+            faillock_deny: raw.faillock_deny.unwrap_or(0),
+            faillock_window: Duration::from_secs(
+                raw.faillock_window_secs
+                    .unwrap_or(DEFAULT_FAILLOCK_WINDOW_SECS),
+            ),
+            faillock_action: raw.faillock_action.unwrap_or_default(),
+            faillock_dir: raw
+                .faillock_dir
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_FAILLOCK_DIR)),
+            audit_log: raw.audit_log,
+            audit_log_max_bytes: raw
+                .audit_log_max_bytes
+                .unwrap_or(DEFAULT_AUDIT_LOG_MAX_BYTES)
+                .max(1),
+            audit_log_keep: raw.audit_log_keep.unwrap_or(DEFAULT_AUDIT_LOG_KEEP),
+            messages: raw.messages,
+            enabled: raw.enabled.unwrap_or(true),
+            mode: raw.mode.unwrap_or_default(),
         }
     }
+
+    pub fn for_scope(raw: ConfigFile, service: Option<&str>, user: Option<&str>) -> Self {
+        Self::from_raw(raw.scoped(service, user))
+    }
 }
 
 impl Default for ResolvedConfig {
@@ -156,6 +899,17 @@ pub fn load_resolved_config() -> Result<ResolvedConfigWithSource, ConfigError> {
     load_resolved_from_paths(&sources)
 }
 
+pub fn load_resolved_config_for(
+    service: &str,
+    user: &str,
+) -> Result<ResolvedConfigWithSource, ConfigError> {
+    let sources = [
+        PathBuf::from(PRIMARY_CONFIG_PATH),
+        PathBuf::from(SECONDARY_CONFIG_PATH),
+    ];
+    load_resolved_from_paths_for(&sources, Some(service), Some(user))
+}
+
 pub fn load_from_paths(paths: &[PathBuf]) -> Result<Option<LoadedConfig>, ConfigError> {
     for path in paths {
         match fs::read_to_string(path) {
@@ -182,12 +936,20 @@ pub fn load_from_paths(paths: &[PathBuf]) -> Result<Option<LoadedConfig>, Config
 
 pub fn load_resolved_from_paths(
     paths: &[PathBuf],
+) -> Result<ResolvedConfigWithSource, ConfigError> {
+    load_resolved_from_paths_for(paths, None, None)
+}
+
+pub fn load_resolved_from_paths_for(
+    paths: &[PathBuf],
+    service: Option<&str>,
+    user: Option<&str>,
 ) -> Result<ResolvedConfigWithSource, ConfigError> {
     match load_from_paths(paths)? {
         Some(entry) => {
             let path = entry.source.clone();
             Ok(ResolvedConfigWithSource {
-                resolved: ResolvedConfig::from_raw(entry.contents),
+                resolved: ResolvedConfig::for_scope(entry.contents, service, user),
                 source: Some(path),
             })
         }
@@ -278,6 +1040,33 @@ mod tests {
             resolved.resolved.secret_service_session,
             SecretServiceSessionMode::Auto
         );
+        assert_eq!(
+            resolved.resolved.authd_socket,
+            PathBuf::from(DEFAULT_AUTHD_SOCKET)
+        );
+        assert_eq!(
+            resolved.resolved.authd_camera_idle,
+            Duration::from_secs(DEFAULT_AUTHD_CAMERA_IDLE_SECS)
+        );
+        assert_eq!(resolved.resolved.liveness, LivenessMode::Off);
+        assert_eq!(resolved.resolved.anti_spoof, AntiSpoofSensitivity::Off);
+        assert_eq!(resolved.resolved.session_policy, SessionPolicy::Local);
+        assert_eq!(resolved.resolved.faillock_deny, 0);
+        assert_eq!(
+            resolved.resolved.faillock_window,
+            Duration::from_secs(DEFAULT_FAILLOCK_WINDOW_SECS)
+        );
+        assert_eq!(resolved.resolved.faillock_action, FaillockAction::Ignore);
+        assert_eq!(
+            resolved.resolved.faillock_dir,
+            PathBuf::from(DEFAULT_FAILLOCK_DIR)
+        );
+        assert_eq!(resolved.resolved.audit_log, None);
+        assert_eq!(
+            resolved.resolved.audit_log_max_bytes,
+            DEFAULT_AUDIT_LOG_MAX_BYTES
+        );
+        assert_eq!(resolved.resolved.audit_log_keep, DEFAULT_AUDIT_LOG_KEEP);
     }
 
     #[test]
@@ -319,4 +1108,734 @@ mod tests {
             other => panic!("unexpected error: {:?}", other),
         }
     }
+
+    #[test]
+    fn service_and_user_tables_override_top_level_keys() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("scoped.toml");
+        fs::write(
+            &path,
+            r#"
+similarity_threshold = 0.9
+video_device = "/dev/video2"
+
+[service.sudo]
+similarity_threshold = 0.95
+capture_timeout_secs = 3
+
+[service.sshd]
+enabled = false
+
+[user.bob]
+similarity_threshold = 0.85
+"#,
+        )
+        .unwrap();
+        let paths = [path];
+
+        let sudo = load_resolved_from_paths_for(&paths, Some("sudo"), Some("alice"))
+            .unwrap()
+            .resolved;
+        assert_eq!(sudo.similarity_threshold, 0.95);
+        assert_eq!(sudo.capture_timeout, Duration::from_secs(3));
+        assert_eq!(sudo.video_device, "/dev/video2");
+        assert!(sudo.enabled);
+
+        let bob_sudo = load_resolved_from_paths_for(&paths, Some("sudo"), Some("bob"))
+            .unwrap()
+            .resolved;
+        assert_eq!(bob_sudo.similarity_threshold, 0.95);
+        assert_eq!(bob_sudo.capture_timeout, Duration::from_secs(3));
+
+        let bob_login = load_resolved_from_paths_for(&paths, Some("login"), Some("bob"))
+            .unwrap()
+            .resolved;
+        assert_eq!(bob_login.similarity_threshold, 0.85);
+
+        let sshd = load_resolved_from_paths_for(&paths, Some("sshd"), Some("alice"))
+            .unwrap()
+            .resolved;
+        assert!(!sshd.enabled);
+        assert_eq!(sshd.similarity_threshold, 0.9);
+
+        let unscoped = load_resolved_from_paths(&paths).unwrap().resolved;
+        assert_eq!(unscoped.similarity_threshold, 0.9);
+        assert!(unscoped.enabled);
+    }
+
+    #[test]
+    fn user_table_can_reenable_globally_disabled_auth() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+enabled = false
+
+[user.alice]
+enabled = true
+"#,
+        )
+        .unwrap();
+
+        assert!(!ResolvedConfig::for_scope(raw.clone(), Some("login"), Some("bob")).enabled);
+        assert!(ResolvedConfig::for_scope(raw, Some("login"), Some("alice")).enabled);
+    }
+
+    #[test]
+    fn user_tables_cannot_loosen_service_security_settings() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+[service.sshd]
+enabled = false
+
+[service.sudo]
+similarity_threshold = 0.95
+liveness = "blink"
+anti_spoof = "medium"
+require_ir = true
+
+[service.gdm-password]
+similarity_metric = "euclidean"
+similarity_threshold = 0.5
+
+[user.alice]
+enabled = true
+similarity_threshold = 0.8
+liveness = "off"
+anti_spoof = "low"
+require_ir = false
+
+[user.bob]
+similarity_threshold = 0.97
+anti_spoof = "high"
+"#,
+        )
+        .unwrap();
+
+        let alice_sshd = ResolvedConfig::for_scope(raw.clone(), Some("sshd"), Some("alice"));
+        assert!(!alice_sshd.enabled);
+
+        let alice_sudo = ResolvedConfig::for_scope(raw.clone(), Some("sudo"), Some("alice"));
+        assert!(alice_sudo.enabled);
+        assert_eq!(alice_sudo.similarity_threshold, 0.95);
+        assert_eq!(alice_sudo.liveness, LivenessMode::Blink);
+        assert_eq!(alice_sudo.anti_spoof, AntiSpoofSensitivity::Medium);
+        assert!(alice_sudo.require_ir);
+
+        let bob_sudo = ResolvedConfig::for_scope(raw.clone(), Some("sudo"), Some("bob"));
+        assert_eq!(bob_sudo.similarity_threshold, 0.97);
+        assert_eq!(bob_sudo.anti_spoof, AntiSpoofSensitivity::High);
+
+        // A cosine threshold cannot replace a Euclidean one.
+        let alice_gdm = ResolvedConfig::for_scope(raw.clone(), Some("gdm-password"), Some("alice"));
+        assert_eq!(alice_gdm.similarity_metric, SimilarityMetric::Euclidean);
+        assert_eq!(alice_gdm.similarity_threshold, 0.5);
+
+        let alice_login = ResolvedConfig::for_scope(raw, Some("login"), Some("alice"));
+        assert_eq!(alice_login.similarity_threshold, 0.8);
+        assert_eq!(alice_login.liveness, LivenessMode::Off);
+    }
+
+    /// Resolves `[service.sudo]` and `[user.alice]` tables written as given.
+    fn sudo_for_alice(service: &str, user: &str) -> ResolvedConfig {
+        let raw: ConfigFile = toml::from_str(&format!(
+            "[service.sudo]\n{service}\n[user.alice]\n{user}\n"
+        ))
+        .unwrap();
+        ResolvedConfig::for_scope(raw, Some("sudo"), Some("alice"))
+    }
+
+    #[test]
+    fn user_tables_cannot_switch_a_service_to_observe_mode() {
+        let config = sudo_for_alice(r#"mode = "enforce""#, r#"mode = "observe""#);
+        assert_eq!(config.mode, AuthMode::Enforce);
+        let config = sudo_for_alice(r#"mode = "observe""#, r#"mode = "enforce""#);
+        assert_eq!(config.mode, AuthMode::Enforce);
+    }
+
+    #[test]
+    fn user_tables_cannot_widen_the_session_policy() {
+        let config = sudo_for_alice(
+            r#"session_policy = "no_remote""#,
+            r#"session_policy = "any""#,
+        );
+        assert_eq!(config.session_policy, SessionPolicy::NoRemote);
+        let config = sudo_for_alice(
+            r#"session_policy = "no_remote""#,
+            r#"session_policy = "local""#,
+        );
+        assert_eq!(config.session_policy, SessionPolicy::Local);
+    }
+
+    #[test]
+    fn user_tables_cannot_turn_off_device_pinning() {
+        let config = sudo_for_alice(r#"device_pin = "required""#, r#"device_pin = "off""#);
+        assert_eq!(config.device_pin, DevicePinMode::Required);
+        let config = sudo_for_alice(r#"device_pin = "off""#, r#"device_pin = "if_pinned""#);
+        assert_eq!(config.device_pin, DevicePinMode::IfPinned);
+    }
+
+    #[test]
+    fn user_tables_cannot_turn_off_stream_integrity() {
+        let config = sudo_for_alice("stream_integrity = true", "stream_integrity = false");
+        assert!(config.stream_integrity);
+    }
+
+    #[test]
+    fn user_tables_cannot_turn_off_confirm_match() {
+        let config = sudo_for_alice("confirm_match = true", "confirm_match = false");
+        assert!(config.confirm_match);
+    }
+
+    #[test]
+    fn user_tables_cannot_turn_off_require_secret_service() {
+        let config = sudo_for_alice(
+            "require_secret_service = true",
+            "require_secret_service = false",
+        );
+        assert!(config.require_secret_service);
+    }
+
+    #[test]
+    fn user_tables_cannot_change_service_fusion() {
+        let config = sudo_for_alice(
+            "fusion = \"k_of_n\"\nfusion_window = 5\nfusion_required = 4\nfusion_max_face_shift = 0.1",
+            "fusion = \"single\"\nfusion_window = 2\nfusion_required = 1\nfusion_max_face_shift = 0.9",
+        );
+        assert_eq!(config.fusion, Fusion::KOfN);
+        assert_eq!(config.fusion_window, 5);
+        assert_eq!(config.fusion_required, 4);
+        assert_eq!(config.fusion_max_face_shift, Some(0.1));
+    }
+
+    #[test]
+    fn user_tables_cannot_change_service_face_selection() {
+        let config = sudo_for_alice(
+            r#"face_selection = "reject_multiple""#,
+            r#"face_selection = "all""#,
+        );
+        assert_eq!(config.face_selection, FaceSelection::RejectMultiple);
+    }
+
+    #[test]
+    fn user_tables_cannot_change_service_cameras() {
+        let config = sudo_for_alice(
+            r#"video_device = "/dev/video2""#,
+            r#"video_device = "/dev/video9""#,
+        );
+        assert_eq!(config.video_device, "/dev/video2");
+        assert_eq!(config.video_devices.len(), 1);
+
+        let config = sudo_for_alice(
+            "[[service.sudo.video_devices]]\ndevice = \"/dev/video2\"",
+            r#"video_device = "/dev/video9""#,
+        );
+        let devices: Vec<_> = config
+            .video_devices
+            .iter()
+            .map(|d| d.device.as_str())
+            .collect();
+        assert_eq!(devices, ["/dev/video2"]);
+    }
+
+    #[test]
+    fn liveness_mode_parses_and_overrides_per_service() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+liveness = "eye_motion"
+
+[service.sudo]
+liveness = "blink"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            ResolvedConfig::for_scope(raw.clone(), Some("login"), None).liveness,
+            LivenessMode::EyeMotion
+        );
+        assert_eq!(
+            ResolvedConfig::for_scope(raw, Some("sudo"), None).liveness,
+            LivenessMode::Blink
+        );
+        assert!(toml::from_str::<ConfigFile>("liveness = \"wink\"").is_err());
+    }
+
+    #[test]
+    fn face_selection_keys_parse_and_override_per_service() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+face_min_size = 80
+
+[service.sudo]
+face_selection = "reject_multiple"
+face_max_center_offset = 0.5
+"#,
+        )
+        .unwrap();
+
+        let login = ResolvedConfig::for_scope(raw.clone(), Some("login"), None);
+        assert_eq!(login.face_selection, FaceSelection::All);
+        assert_eq!(login.face_min_size, 80);
+        assert_eq!(login.face_max_center_offset, None);
+
+        let sudo = ResolvedConfig::for_scope(raw, Some("sudo"), None);
+        assert_eq!(sudo.face_selection, FaceSelection::RejectMultiple);
+        assert_eq!(sudo.face_min_size, 80);
+        assert_eq!(sudo.face_max_center_offset, Some(0.5));
+    }
+
+    #[test]
+    fn fusion_keys_parse_and_override_per_service() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+fusion_window = 4
+
+[service.sudo]
+fusion = "k_of_n"
+fusion_required = 2
+fusion_max_face_shift = 0.5
+"#,
+        )
+        .unwrap();
+
+        let login = ResolvedConfig::for_scope(raw.clone(), Some("login"), None);
+        assert_eq!(login.fusion, Fusion::Single);
+        assert_eq!(login.fusion_window, 4);
+        assert_eq!(login.fusion_required, DEFAULT_FUSION_REQUIRED);
+
+        let sudo = ResolvedConfig::for_scope(raw, Some("sudo"), None);
+        assert_eq!(sudo.fusion, Fusion::KOfN);
+        assert_eq!(sudo.fusion_window, 4);
+        assert_eq!(sudo.fusion_required, 2);
+        assert_eq!(sudo.fusion_max_face_shift, Some(0.5));
+        assert!(toml::from_str::<ConfigFile>("fusion = \"median\"").is_err());
+    }
+
+    #[test]
+    fn device_pin_defaults_and_denylist_override() {
+        let defaults = ResolvedConfig::default();
+        assert_eq!(defaults.device_pin, DevicePinMode::IfPinned);
+        assert_eq!(
+            defaults.device_pin_dir,
+            PathBuf::from(DEFAULT_DEVICE_PIN_DIR)
+        );
+        assert!(defaults
+            .virtual_camera_drivers
+            .iter()
+            .any(|driver| driver == "v4l2 loopback"));
+
+        let raw: ConfigFile = toml::from_str(
+            r#"
+virtual_camera_drivers = []
+
+[service.sudo]
+device_pin = "required"
+"#,
+        )
+        .unwrap();
+        let sudo = ResolvedConfig::for_scope(raw, Some("sudo"), None);
+        assert_eq!(sudo.device_pin, DevicePinMode::Required);
+        assert!(sudo.virtual_camera_drivers.is_empty());
+    }
+
+    #[test]
+    fn adaptive_update_is_opt_in_and_overridable_per_user() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+adaptive_update_margin = 0.08
+
+[user.alice]
+adaptive_update = true
+adaptive_update_max_entries = 0
+"#,
+        )
+        .unwrap();
+
+        let bob = ResolvedConfig::for_scope(raw.clone(), Some("login"), Some("bob"));
+        assert!(!bob.adaptive_update);
+        assert_eq!(bob.adaptive_update_margin, 0.08);
+        assert_eq!(
+            bob.adaptive_update_max_entries,
+            DEFAULT_ADAPTIVE_UPDATE_MAX_ENTRIES
+        );
+
+        let alice = ResolvedConfig::for_scope(raw, Some("login"), Some("alice"));
+        assert!(alice.adaptive_update);
+        assert_eq!(alice.adaptive_update_max_entries, 1);
+    }
+
+    #[test]
+    fn similarity_metric_picks_its_default_threshold() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+similarity_metric = "euclidean"
+
+[service.sudo]
+similarity_threshold = 0.5
+
+[user.alice]
+similarity_metric = "cosine"
+"#,
+        )
+        .unwrap();
+
+        let login = ResolvedConfig::for_scope(raw.clone(), Some("login"), None);
+        assert_eq!(login.similarity_metric, SimilarityMetric::Euclidean);
+        assert_eq!(login.similarity_threshold, DEFAULT_EUCLIDEAN_THRESHOLD);
+
+        let sudo = ResolvedConfig::for_scope(raw.clone(), Some("sudo"), None);
+        assert_eq!(sudo.similarity_threshold, 0.5);
+
+        let alice = ResolvedConfig::for_scope(raw, Some("login"), Some("alice"));
+        assert_eq!(alice.similarity_metric, SimilarityMetric::Cosine);
+        assert_eq!(alice.similarity_threshold, DEFAULT_SIMILARITY_THRESHOLD);
+    }
+
+    #[test]
+    fn anti_spoof_sensitivity_parses_and_overrides_per_user() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+anti_spoof = "medium"
+
+[user.alice]
+anti_spoof = "high"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            ResolvedConfig::for_scope(raw.clone(), None, Some("bob")).anti_spoof,
+            AntiSpoofSensitivity::Medium
+        );
+        assert_eq!(
+            ResolvedConfig::for_scope(raw, None, Some("alice")).anti_spoof,
+            AntiSpoofSensitivity::High
+        );
+    }
+
+    #[test]
+    fn session_policy_can_be_relaxed_per_service() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+session_policy = "no_remote"
+
+[service.machinectl]
+session_policy = "any"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            ResolvedConfig::for_scope(raw.clone(), Some("sshd"), None).session_policy,
+            SessionPolicy::NoRemote
+        );
+        assert_eq!(
+            ResolvedConfig::for_scope(raw, Some("machinectl"), None).session_policy,
+            SessionPolicy::Any
+        );
+    }
+
+    #[test]
+    fn device_error_actions_default_to_ignore_and_override_per_service() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+device_busy_action = "error"
+
+[service.login]
+device_absent_action = "error"
+device_busy_action = "ignore"
+"#,
+        )
+        .unwrap();
+        let global = ResolvedConfig::for_scope(raw.clone(), Some("sudo"), None);
+        assert_eq!(global.device_absent_action, DeviceErrorAction::Ignore);
+        assert_eq!(global.device_busy_action, DeviceErrorAction::Error);
+        assert_eq!(global.device_denied_action, DeviceErrorAction::Ignore);
+
+        let login = ResolvedConfig::for_scope(raw, Some("login"), None);
+        assert_eq!(login.device_absent_action, DeviceErrorAction::Error);
+        assert_eq!(login.device_busy_action, DeviceErrorAction::Ignore);
+    }
+
+    #[test]
+    fn require_ir_is_opt_in_and_overridable_per_user() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+require_ir = true
+
+[user.kiosk]
+require_ir = false
+
+[messages]
+not_infrared = "IR camera required"
+"#,
+        )
+        .unwrap();
+        assert!(!ResolvedConfig::default().require_ir);
+        let alice = ResolvedConfig::for_scope(raw.clone(), Some("sudo"), Some("alice"));
+        assert!(alice.require_ir);
+        assert_eq!(
+            alice.messages.not_infrared.as_deref(),
+            Some("IR camera required")
+        );
+        assert!(!ResolvedConfig::for_scope(raw, Some("sudo"), Some("kiosk")).require_ir);
+    }
+
+    #[test]
+    fn observe_mode_is_selected_per_service() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+[service.sudo]
+mode = "observe"
+"#,
+        )
+        .unwrap();
+        assert_eq!(ResolvedConfig::default().mode, AuthMode::Enforce);
+        assert_eq!(
+            ResolvedConfig::for_scope(raw.clone(), Some("sudo"), Some("alice")).mode,
+            AuthMode::Observe
+        );
+        assert_eq!(
+            ResolvedConfig::for_scope(raw, Some("login"), Some("alice")).mode,
+            AuthMode::Enforce
+        );
+    }
+
+    #[test]
+    fn interrupt_action_defaults_to_abort_and_is_overridable() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+[service.sudo]
+interrupt_action = "ignore"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            ResolvedConfig::default().interrupt_action,
+            InterruptAction::Abort
+        );
+        assert_eq!(
+            ResolvedConfig::for_scope(raw, Some("sudo"), None).interrupt_action,
+            InterruptAction::Ignore
+        );
+    }
+
+    #[test]
+    fn stream_integrity_is_off_by_default_and_overridable() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+[service.sudo]
+stream_integrity = true
+
+[messages]
+frozen_stream = "Camera stream frozen"
+"#,
+        )
+        .unwrap();
+        assert!(!ResolvedConfig::default().stream_integrity);
+        let sudo = ResolvedConfig::for_scope(raw.clone(), Some("sudo"), Some("alice"));
+        assert!(sudo.stream_integrity);
+        assert_eq!(
+            sudo.messages.frozen_stream.as_deref(),
+            Some("Camera stream frozen")
+        );
+        assert!(!ResolvedConfig::for_scope(raw, Some("login"), None).stream_integrity);
+    }
+
+    #[test]
+    fn concurrent_prompt_is_opt_in_per_service() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+[service.sudo]
+concurrent_prompt = true
+
+[service.sudo.messages]
+password_prompt = "Password or face: "
+"#,
+        )
+        .unwrap();
+        let global = ResolvedConfig::for_scope(raw.clone(), Some("login"), None);
+        assert!(!global.concurrent_prompt);
+        assert_eq!(global.messages.password_prompt, None);
+
+        let sudo = ResolvedConfig::for_scope(raw, Some("sudo"), None);
+        assert!(sudo.concurrent_prompt);
+        assert_eq!(
+            sudo.messages.password_prompt.as_deref(),
+            Some("Password or face: ")
+        );
+    }
+
+    #[test]
+    fn confirm_match_is_opt_in_per_service() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+[service.sudo]
+confirm_match = true
+confirm_timeout_secs = 4
+
+[messages]
+confirm_prompt = "Press Enter, {user}"
+"#,
+        )
+        .unwrap();
+        let login = ResolvedConfig::for_scope(raw.clone(), Some("login"), None);
+        assert!(!login.confirm_match);
+        assert_eq!(
+            login.confirm_timeout,
+            Duration::from_secs(DEFAULT_CONFIRM_TIMEOUT_SECS)
+        );
+
+        let sudo = ResolvedConfig::for_scope(raw, Some("sudo"), None);
+        assert!(sudo.confirm_match);
+        assert_eq!(sudo.confirm_timeout, Duration::from_secs(4));
+        assert_eq!(
+            sudo.messages.confirm_prompt.as_deref(),
+            Some("Press Enter, {user}")
+        );
+    }
+
+    #[test]
+    fn progress_messages_are_on_by_default_and_suppressible_per_service() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+progress_interval_millis = 3000
+
+[service.gdm-password]
+progress_messages = false
+
+[messages]
+countdown = "{remaining}s left"
+"#,
+        )
+        .unwrap();
+        let defaults = ResolvedConfig::default();
+        assert!(defaults.progress_messages);
+        assert_eq!(
+            defaults.progress_interval,
+            Duration::from_millis(DEFAULT_PROGRESS_INTERVAL_MILLIS)
+        );
+
+        let sudo = ResolvedConfig::for_scope(raw.clone(), Some("sudo"), None);
+        assert!(sudo.progress_messages);
+        assert_eq!(sudo.progress_interval, Duration::from_millis(3000));
+        assert_eq!(
+            sudo.messages.countdown.as_deref(),
+            Some("{remaining}s left")
+        );
+
+        let gdm = ResolvedConfig::for_scope(raw, Some("gdm-password"), None);
+        assert!(!gdm.progress_messages);
+    }
+
+    #[test]
+    fn video_devices_fall_back_in_order_with_inherited_settings() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+video_device = "/dev/video0"
+pixel_format = "GREY"
+
+[[video_devices]]
+device = "/dev/v4l/by-id/usb-Dock_IR_Camera-video-index0"
+warmup_frames = 8
+
+[[video_devices]]
+device = "/dev/video2"
+pixel_format = "Y16"
+
+[service.sshd]
+video_device = "/dev/video9"
+"#,
+        )
+        .unwrap();
+        let login = ResolvedConfig::for_scope(raw.clone(), Some("login"), None);
+        assert_eq!(
+            login.video_devices,
+            vec![
+                VideoDevice {
+                    device: "/dev/v4l/by-id/usb-Dock_IR_Camera-video-index0".into(),
+                    pixel_format: "GREY".into(),
+                    warmup_frames: 8,
+                },
+                VideoDevice {
+                    device: "/dev/video2".into(),
+                    pixel_format: "Y16".into(),
+                    warmup_frames: DEFAULT_WARMUP_FRAMES,
+                },
+            ]
+        );
+        assert_eq!(
+            login.video_device,
+            "/dev/v4l/by-id/usb-Dock_IR_Camera-video-index0"
+        );
+
+        let sshd = ResolvedConfig::for_scope(raw, Some("sshd"), None);
+        assert_eq!(sshd.video_device, "/dev/video9");
+        assert_eq!(sshd.video_devices.len(), 1);
+
+        let single = ResolvedConfig::default();
+        assert_eq!(single.video_devices[0].device, DEFAULT_VIDEO_DEVICE);
+        assert_eq!(single.video_devices[0].pixel_format, DEFAULT_PIXEL_FORMAT);
+    }
+
+    #[test]
+    fn parses_faillock_settings() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+faillock_deny = 3
+faillock_window_secs = 120
+faillock_action = "deny"
+faillock_dir = "/tmp/tally"
+"#,
+        )
+        .unwrap();
+        let resolved = ResolvedConfig::from_raw(raw);
+        assert_eq!(resolved.faillock_deny, 3);
+        assert_eq!(resolved.faillock_window, Duration::from_secs(120));
+        assert_eq!(resolved.faillock_action, FaillockAction::Deny);
+        assert_eq!(resolved.faillock_dir, PathBuf::from("/tmp/tally"));
+    }
+
+    #[test]
+    fn message_templates_merge_per_scope() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+[messages]
+success = "Welcome back, {user}"
+no_face_hint = ""
+
+[service.gdm-password.messages]
+success = ""
+locked_out = "Face unlock paused"
+"#,
+        )
+        .unwrap();
+
+        let global = ResolvedConfig::for_scope(raw.clone(), Some("sudo"), None).messages;
+        assert_eq!(global.success.as_deref(), Some("Welcome back, {user}"));
+        assert_eq!(global.no_face_hint.as_deref(), Some(""));
+        assert_eq!(global.locked_out, None);
+
+        let gdm = ResolvedConfig::for_scope(raw, Some("gdm-password"), None).messages;
+        assert_eq!(gdm.success.as_deref(), Some(""));
+        assert_eq!(gdm.no_face_hint.as_deref(), Some(""));
+        assert_eq!(gdm.locked_out.as_deref(), Some("Face unlock paused"));
+    }
+
+    #[test]
+    fn parses_audit_log_settings() {
+        let raw: ConfigFile = toml::from_str(
+            r#"
+audit_log = "/var/log/chissu-pam/audit.jsonl"
+audit_log_max_bytes = 4096
+audit_log_keep = 2
+"#,
+        )
+        .unwrap();
+        let resolved = ResolvedConfig::from_raw(raw);
+        assert_eq!(
+            resolved.audit_log,
+            Some(PathBuf::from("/var/log/chissu-pam/audit.jsonl"))
+        );
+        assert_eq!(resolved.audit_log_max_bytes, 4096);
+        assert_eq!(resolved.audit_log_keep, 2);
+    }
 }
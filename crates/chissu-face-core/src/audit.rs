@@ -0,0 +1,275 @@
+//! Append-only JSONL audit trail of PAM authentication attempts.
+//!
+//! `pam-chissu` appends one [`AuditRecord`] per `pam_sm_authenticate` call.
+//! When the active file would grow past its size limit it is renamed to
+//! `<path>.1` (shifting older files up to `<path>.<keep>`) before writing.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{AppError, AppResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+    /// The module returned `PAM_IGNORE` (disabled, locked out, keyring unavailable).
+    Ignored,
+    /// The module returned `PAM_SYSTEM_ERR`.
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// RFC 3339 UTC timestamp taken when the record is written.
+    pub timestamp: String,
+    pub service: String,
+    pub user: String,
+    pub tty: Option<String>,
+    pub outcome: AuditOutcome,
+    /// Set under `mode = "observe"`: `outcome` is the decision that would
+    /// have been returned, while the module returned `PAM_IGNORE`.
+    #[serde(default)]
+    pub observed: bool,
+    /// Snake-case failure reason for `failure` outcomes.
+    pub failure_reason: Option<String>,
+    /// Skip reason or error message for `ignored` and `error` outcomes.
+    pub detail: Option<String>,
+    pub frames_captured: usize,
+    /// `None` when no face was scored.
+    pub best_similarity: Option<f64>,
+    pub similarity_threshold: f64,
+    pub device: String,
+    pub duration_ms: u64,
+}
+
+pub fn audit_timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Narrows [`AuditLog::read`] results; `None` fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub user: Option<String>,
+    pub service: Option<String>,
+    pub outcome: Option<AuditOutcome>,
+}
+
+impl AuditFilter {
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        self.user.iter().all(|user| &record.user == user)
+            && self
+                .service
+                .iter()
+                .all(|service| &record.service == service)
+            && self
+                .outcome
+                .iter()
+                .all(|&outcome| record.outcome == outcome)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: u32,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>, max_bytes: u64, keep: u32) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes,
+            keep,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends `record` as one line, rotating first if it would exceed the size limit.
+    pub fn append(&self, record: &AuditRecord) -> AppResult<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+
+        if let Some(parent) = self.path.parent() {
+            fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o750)
+                .create(parent)
+                .map_err(|source| self.error(source))?;
+        }
+        let current = match fs::metadata(&self.path) {
+            Ok(meta) => meta.len(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => 0,
+            Err(source) => return Err(self.error(source)),
+        };
+        if current > 0 && current + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .mode(0o640)
+            .open(&self.path)
+            .map_err(|source| self.error(source))?;
+        file.write_all(&line).map_err(|source| self.error(source))
+    }
+
+    fn rotate(&self) -> AppResult<()> {
+        if self.keep == 0 {
+            return fs::remove_file(&self.path).map_err(|source| self.error(source));
+        }
+        for index in (1..self.keep).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(index + 1))
+                    .map_err(|source| self.error(source))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1)).map_err(|source| self.error(source))
+    }
+
+    pub fn rotated_path(&self, index: u32) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{index}"));
+        PathBuf::from(name)
+    }
+
+    /// Reads records oldest first across rotated files, skipping lines that
+    /// do not parse. Returns the matching records and the number skipped.
+    pub fn read(&self, filter: &AuditFilter) -> AppResult<(Vec<AuditRecord>, usize)> {
+        let mut records = Vec::new();
+        let mut skipped = 0;
+        let files = (1..=self.keep)
+            .rev()
+            .map(|index| self.rotated_path(index))
+            .chain(std::iter::once(self.path.clone()));
+        for path in files {
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                Err(source) => return Err(AppError::AuditLog { path, source }),
+            };
+            for line in BufReader::new(file).lines() {
+                let line = line.map_err(|source| AppError::AuditLog {
+                    path: path.clone(),
+                    source,
+                })?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<AuditRecord>(&line) {
+                    Ok(record) if filter.matches(&record) => records.push(record),
+                    Ok(_) => {}
+                    Err(_) => skipped += 1,
+                }
+            }
+        }
+        Ok((records, skipped))
+    }
+
+    fn error(&self, source: io::Error) -> AppError {
+        AppError::AuditLog {
+            path: self.path.clone(),
+            source,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn record(user: &str, service: &str, outcome: AuditOutcome) -> AuditRecord {
+        AuditRecord {
+            timestamp: audit_timestamp(),
+            service: service.into(),
+            user: user.into(),
+            tty: Some("/dev/pts/0".into()),
+            outcome,
+            observed: false,
+            failure_reason: (outcome == AuditOutcome::Failure)
+                .then(|| "threshold_not_reached".into()),
+            detail: None,
+            frames_captured: 4,
+            best_similarity: Some(0.42),
+            similarity_threshold: 0.9,
+            device: "/dev/video2".into(),
+            duration_ms: 1234,
+        }
+    }
+
+    #[test]
+    fn append_and_filter_records() {
+        let dir = tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("audit/audit.jsonl"), 1 << 20, 3);
+        log.append(&record("alice", "sudo", AuditOutcome::Success))
+            .unwrap();
+        log.append(&record("bob", "sudo", AuditOutcome::Failure))
+            .unwrap();
+        log.append(&record("alice", "login", AuditOutcome::Failure))
+            .unwrap();
+
+        let (all, skipped) = log.read(&AuditFilter::default()).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(skipped, 0);
+
+        let filter = AuditFilter {
+            user: Some("alice".into()),
+            outcome: Some(AuditOutcome::Failure),
+            ..AuditFilter::default()
+        };
+        let (matched, _) = log.read(&filter).unwrap();
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].service, "login");
+
+        let raw = fs::read_to_string(log.path()).unwrap();
+        assert!(raw.lines().all(|line| line.contains("\"outcome\"")));
+    }
+
+    #[test]
+    fn rotation_keeps_bounded_history_in_order() {
+        let dir = tempdir().unwrap();
+        let line_len = serde_json::to_vec(&record("u0", "sudo", AuditOutcome::Success))
+            .unwrap()
+            .len() as u64
+            + 1;
+        // Room for two records per file, two rotated files.
+        let log = AuditLog::new(dir.path().join("audit.jsonl"), line_len * 2, 2);
+        for idx in 0..7 {
+            log.append(&record(&format!("u{idx}"), "sudo", AuditOutcome::Success))
+                .unwrap();
+        }
+
+        assert!(log.rotated_path(2).exists());
+        assert!(!log.rotated_path(3).exists());
+        let (records, _) = log.read(&AuditFilter::default()).unwrap();
+        let users: Vec<_> = records.iter().map(|r| r.user.as_str()).collect();
+        assert_eq!(users, vec!["u2", "u3", "u4", "u5", "u6"]);
+    }
+
+    #[test]
+    fn read_skips_corrupt_lines() {
+        let dir = tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("audit.jsonl"), 1 << 20, 1);
+        log.append(&record("alice", "sudo", AuditOutcome::Ignored))
+            .unwrap();
+        let mut file = OpenOptions::new().append(true).open(log.path()).unwrap();
+        writeln!(file, "{{not json").unwrap();
+
+        let (records, skipped) = log.read(&AuditFilter::default()).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(skipped, 1);
+    }
+}
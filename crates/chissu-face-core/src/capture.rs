@@ -2,6 +2,7 @@ use std::fs;
 use std::io::Write;
 use std::panic::{self, AssertUnwindSafe};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use chrono::Utc;
 use image::codecs::png::PngEncoder;
@@ -12,18 +13,27 @@ use v4l::control::{Control, Description, Type as ControlType, Value};
 use v4l::format::{Format, FourCC};
 use v4l::framesize::FrameSizeEnum;
 
-use crate::errors::{AppError, AppResult};
+use crate::errors::{AppError, AppResult, DeviceFault};
 
 mod device;
+pub mod integrity;
+pub mod ir;
+pub mod selector;
 
 use self::device::{
-    CaptureDevice, CaptureDeviceFactory, CaptureSink, FileCaptureSink, V4lCaptureDevice,
+    CaptureDevice, CaptureDeviceFactory, CaptureSink, CaptureStream, FileCaptureSink,
+    V4lCaptureDevice,
 };
+use self::selector::DeviceSelector;
 
 const CID_EXPOSURE_AUTO: u32 = 0x009a0901;
 const CID_EXPOSURE_ABSOLUTE: u32 = 0x009a0902;
 const CID_GAIN: u32 = 0x00980913;
 
+/// Longest a blocked dequeue waits before deadlines and cancellation are
+/// checked again.
+pub const DEQUEUE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 #[derive(Debug, Clone)]
 pub struct CaptureConfig {
     pub device: DeviceLocator,
@@ -38,10 +48,23 @@ pub struct CaptureConfig {
     pub output: Option<PathBuf>,
 }
 
+impl CaptureConfig {
+    /// Copy of this config with a selector replaced by the node it names
+    /// right now, so summaries and logs report the real device path.
+    pub fn resolved(&self) -> AppResult<CaptureConfig> {
+        Ok(CaptureConfig {
+            device: self.device.resolve()?,
+            ..self.clone()
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum DeviceLocator {
     Index(u32),
     Path(PathBuf),
+    /// Stable udev selector such as `by-id:...` or `ir:first`.
+    Selector(DeviceSelector),
 }
 
 impl DeviceLocator {
@@ -50,6 +73,8 @@ impl DeviceLocator {
             Some(text) => {
                 if let Ok(index) = text.parse::<u32>() {
                     DeviceLocator::Index(index)
+                } else if let Some(selector) = DeviceSelector::parse(&text) {
+                    DeviceLocator::Selector(selector)
                 } else {
                     DeviceLocator::Path(PathBuf::from(text))
                 }
@@ -62,6 +87,25 @@ impl DeviceLocator {
         match self {
             DeviceLocator::Index(i) => format!("/dev/video{i}"),
             DeviceLocator::Path(path) => path.display().to_string(),
+            DeviceLocator::Selector(selector) => selector.to_string(),
+        }
+    }
+
+    /// Looks a selector up through udev; indices and paths are returned as is.
+    pub fn resolve(&self) -> AppResult<DeviceLocator> {
+        match self {
+            DeviceLocator::Selector(selector) => Ok(DeviceLocator::Path(selector.resolve()?)),
+            other => Ok(other.clone()),
+        }
+    }
+
+    /// Device node this locator opens. Selectors fail with a
+    /// [`AppError::DeviceOpen`] when no node matches.
+    pub fn path(&self) -> AppResult<PathBuf> {
+        match self {
+            DeviceLocator::Index(i) => Ok(PathBuf::from(format!("/dev/video{i}"))),
+            DeviceLocator::Path(path) => Ok(path.clone()),
+            DeviceLocator::Selector(selector) => selector.resolve(),
         }
     }
 
@@ -79,6 +123,7 @@ impl DeviceLocator {
                     source: err,
                 })
             }
+            DeviceLocator::Selector(_) => self.resolve()?.open(),
         }
     }
 }
@@ -97,6 +142,25 @@ pub struct CaptureSummary {
     pub auto_exposure: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub auto_gain: Option<String>,
+    /// Earlier entries of a fallback list that were absent or busy.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped_devices: Vec<SkippedDevice>,
+}
+
+/// A device passed over by [`open_first_available`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SkippedDevice {
+    pub device: String,
+    pub fault: DeviceFault,
+}
+
+/// What [`open_first_available`] opened, and which devices it skipped.
+#[derive(Debug)]
+pub struct FirstAvailable<T> {
+    /// Position of the config that opened.
+    pub index: usize,
+    pub opened: T,
+    pub skipped: Vec<SkippedDevice>,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -114,6 +178,15 @@ pub struct NegotiatedFormat {
     pub height: u32,
 }
 
+/// Buffer metadata the driver attached to a dequeued frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FrameMetadata {
+    /// V4L2 frame counter.
+    pub sequence: u32,
+    /// Capture time on the driver's clock; `None` when the driver left it unset.
+    pub timestamp: Option<Duration>,
+}
+
 #[derive(Debug)]
 pub struct CaptureOutcome {
     pub summary: CaptureSummary,
@@ -130,43 +203,170 @@ pub struct InMemoryCapture {
 }
 
 pub fn capture_frame_in_memory(config: &CaptureConfig) -> AppResult<InMemoryCapture> {
-    let mut device = V4lCaptureDevice::open(&config.device)?;
-    capture_with_device(config, &mut device)
+    CaptureSession::open(config)?.into_capture()
 }
 
-fn capture_with_device(
-    config: &CaptureConfig,
-    device: &mut dyn CaptureDevice,
-) -> AppResult<InMemoryCapture> {
-    let mut logs = Vec::new();
-    logs.push(format!("Opened device {}", config.device.display()));
-    debug!("device" = config.device.display(), "opened" = true);
-
-    let mut verifier = CapabilityVerifier::new(config);
-    let device_summary = verifier.verify(device)?;
-    logs.extend(verifier.into_logs());
-
-    let mut negotiator = FormatNegotiator::new(config);
-    let negotiated = negotiator.negotiate(device)?;
-    logs.extend(negotiator.into_logs());
-
-    let mut control_applier = ControlApplier::new(config);
-    let control_report = control_applier.apply(device)?;
-    logs.extend(control_applier.into_logs());
-
-    let mut reader = FrameReader::new(config);
-    let image = reader.read(device, &negotiated.raw_format)?;
-    logs.extend(reader.into_logs());
-
-    Ok(InMemoryCapture {
-        image,
-        device: device_summary,
-        format: negotiated.public_format,
-        control: control_report,
-        logs,
+/// Opens `locator` only long enough to read what the device reports about
+/// itself, without negotiating a format or streaming.
+pub fn query_device(locator: &DeviceLocator) -> AppResult<DeviceSummary> {
+    let locator = &locator.resolve()?;
+    let device = V4lCaptureDevice::open(locator)?;
+    let caps = device.query_caps()?;
+    Ok(DeviceSummary {
+        driver: caps.driver,
+        card: caps.card,
+        bus_info: caps.bus,
+        path: locator.display(),
     })
 }
 
+/// Keeps a negotiated V4L2 stream running so repeated captures skip the
+/// open, format negotiation, control and warm-up steps.
+pub struct CaptureSession {
+    // Declared before `_device` so the stream is torn down first.
+    stream: Box<dyn CaptureStream>,
+    _device: Box<dyn CaptureDevice>,
+    raw_format: Format,
+    device: DeviceSummary,
+    format: NegotiatedFormat,
+    control: CaptureControlReport,
+    last_metadata: Option<FrameMetadata>,
+    logs: Vec<String>,
+}
+
+impl CaptureSession {
+    pub fn open(config: &CaptureConfig) -> AppResult<Self> {
+        let config = &config.resolved()?;
+        let device = V4lCaptureDevice::open(&config.device)?;
+        Self::start(config, Box::new(device))
+    }
+
+    fn start(config: &CaptureConfig, mut device: Box<dyn CaptureDevice>) -> AppResult<Self> {
+        let mut logs = Vec::new();
+        logs.push(format!("Opened device {}", config.device.display()));
+        debug!("device" = config.device.display(), "opened" = true);
+
+        let mut verifier = CapabilityVerifier::new(config);
+        let device_summary = verifier.verify(device.as_mut())?;
+        logs.extend(verifier.into_logs());
+
+        let mut negotiator = FormatNegotiator::new(config);
+        let negotiated = negotiator.negotiate(device.as_mut())?;
+        logs.extend(negotiator.into_logs());
+
+        let mut control_applier = ControlApplier::new(config);
+        let control_report = control_applier.apply(device.as_mut())?;
+        logs.extend(control_applier.into_logs());
+
+        let mut reader = FrameReader::new(config);
+        let stream = reader.start(device.as_mut())?;
+        logs.extend(reader.into_logs());
+
+        Ok(Self {
+            stream,
+            _device: device,
+            raw_format: negotiated.raw_format,
+            device: device_summary,
+            format: negotiated.public_format,
+            control: control_report,
+            last_metadata: None,
+            logs,
+        })
+    }
+
+    /// Dequeues the next frame and converts it to greyscale.
+    pub fn next_frame(&mut self) -> AppResult<GrayImage> {
+        let (data, metadata) = self.stream.next()?;
+        self.last_metadata = Some(metadata);
+        convert_frame_to_image(&data, &self.raw_format)
+    }
+
+    /// Like [`Self::next_frame`], but never blocks past `deadline`, returning
+    /// `None` instead, and fails with [`AppError::Cancelled`] as soon as
+    /// `cancelled` returns `true`, also while waiting for the device.
+    pub fn next_frame_until(
+        &mut self,
+        deadline: Instant,
+        cancelled: &dyn Fn() -> bool,
+    ) -> AppResult<Option<GrayImage>> {
+        let Some((data, metadata)) = self.dequeue_until(deadline, cancelled)? else {
+            return Ok(None);
+        };
+        self.last_metadata = Some(metadata);
+        convert_frame_to_image(&data, &self.raw_format).map(Some)
+    }
+
+    fn dequeue_until(
+        &mut self,
+        deadline: Instant,
+        cancelled: &dyn Fn() -> bool,
+    ) -> AppResult<Option<(Vec<u8>, FrameMetadata)>> {
+        loop {
+            if cancelled() {
+                return Err(AppError::Cancelled);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(None);
+            }
+            if let Some(frame) = self
+                .stream
+                .next_within(remaining.min(DEQUEUE_POLL_INTERVAL))?
+            {
+                return Ok(Some(frame));
+            }
+        }
+    }
+
+    /// Metadata of the frame last returned by [`Self::next_frame`].
+    pub fn last_metadata(&self) -> Option<FrameMetadata> {
+        self.last_metadata
+    }
+
+    /// Drains frames until `until` so the next one is fresh rather than a
+    /// buffer queued while the caller was busy. Returns the number dropped.
+    /// Stops early with [`AppError::Cancelled`] once `cancelled` returns `true`.
+    pub fn discard_until(
+        &mut self,
+        until: Instant,
+        cancelled: &dyn Fn() -> bool,
+    ) -> AppResult<usize> {
+        let mut discarded = 0;
+        while self.dequeue_until(until, cancelled)?.is_some() {
+            discarded += 1;
+        }
+        Ok(discarded)
+    }
+
+    pub fn device(&self) -> &DeviceSummary {
+        &self.device
+    }
+
+    pub fn format(&self) -> &NegotiatedFormat {
+        &self.format
+    }
+
+    pub fn control(&self) -> &CaptureControlReport {
+        &self.control
+    }
+
+    /// Returns log lines accumulated since the previous call.
+    pub fn take_logs(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.logs)
+    }
+
+    fn into_capture(mut self) -> AppResult<InMemoryCapture> {
+        let image = self.next_frame()?;
+        Ok(InMemoryCapture {
+            image,
+            device: self.device,
+            format: self.format,
+            control: self.control,
+            logs: self.logs,
+        })
+    }
+}
+
 pub struct CapturePipelineBuilder {
     device_factory: Box<CaptureDeviceFactory>,
     sink: Box<dyn CaptureSink>,
@@ -199,8 +399,9 @@ impl CapturePipelineBuilder {
     }
 
     pub fn run(&self, config: &CaptureConfig) -> AppResult<CaptureOutcome> {
-        let mut device = (self.device_factory)(&config.device)?;
-        let mut capture = capture_with_device(config, device.as_mut())?;
+        let config = &config.resolved()?;
+        let device = (self.device_factory)(&config.device)?;
+        let mut capture = CaptureSession::start(config, device)?.into_capture()?;
         let requested = config.output.as_deref();
         let output_path = self.sink.store(&capture.image, requested)?;
         capture
@@ -216,6 +417,7 @@ impl CapturePipelineBuilder {
             gain: config.gain,
             auto_exposure: capture.control.auto_exposure,
             auto_gain: capture.control.auto_gain,
+            skipped_devices: Vec::new(),
         };
 
         Ok(CaptureOutcome {
@@ -229,6 +431,70 @@ pub fn run_capture(config: &CaptureConfig) -> AppResult<CaptureOutcome> {
     CapturePipelineBuilder::default().run(config)
 }
 
+/// Calls `open` for each config in order and returns the first success.
+/// A device that is absent or busy is skipped; any other error, or the error
+/// of the last entry, is returned unchanged so callers can still map it to a
+/// device policy.
+pub fn open_first_available<T>(
+    configs: &[CaptureConfig],
+    mut open: impl FnMut(&CaptureConfig) -> AppResult<T>,
+) -> AppResult<FirstAvailable<T>> {
+    let mut skipped = Vec::new();
+    for (index, config) in configs.iter().enumerate() {
+        match open(config) {
+            Ok(opened) => {
+                return Ok(FirstAvailable {
+                    index,
+                    opened,
+                    skipped,
+                })
+            }
+            Err(err) if index + 1 < configs.len() => match err.device_fault() {
+                Some(fault @ (DeviceFault::Absent | DeviceFault::Busy)) => {
+                    debug!(
+                        "device" = config.device.display(),
+                        "skipped" = fault.describe()
+                    );
+                    skipped.push(SkippedDevice {
+                        device: config.device.display(),
+                        fault,
+                    });
+                }
+                _ => return Err(err),
+            },
+            Err(err) => return Err(err),
+        }
+    }
+    Err(AppError::Capability("no video device configured".into()))
+}
+
+/// Runs `capture` on the first available device in `configs`, recording the
+/// skipped ones in the summary and logs.
+pub fn run_first_available(
+    configs: &[CaptureConfig],
+    capture: impl FnMut(&CaptureConfig) -> AppResult<CaptureOutcome>,
+) -> AppResult<CaptureOutcome> {
+    let FirstAvailable {
+        opened: mut outcome,
+        skipped,
+        ..
+    } = open_first_available(configs, capture)?;
+    let mut logs: Vec<String> = skipped
+        .iter()
+        .map(|entry| {
+            format!(
+                "Skipped device {}: {}",
+                entry.device,
+                entry.fault.describe()
+            )
+        })
+        .collect();
+    logs.append(&mut outcome.logs);
+    outcome.logs = logs;
+    outcome.summary.skipped_devices = skipped;
+    Ok(outcome)
+}
+
 struct CapabilityVerifier<'a> {
     config: &'a CaptureConfig,
     logs: Vec<String>,
@@ -551,7 +817,7 @@ impl<'a> FrameReader<'a> {
         }
     }
 
-    fn read(&mut self, device: &mut dyn CaptureDevice, format: &Format) -> AppResult<GrayImage> {
+    fn start(&mut self, device: &mut dyn CaptureDevice) -> AppResult<Box<dyn CaptureStream>> {
         let mut stream = device.start_stream(4)?;
         if self.config.warmup_frames > 0 {
             self.logs.push(format!(
@@ -569,8 +835,7 @@ impl<'a> FrameReader<'a> {
             }
         }
 
-        let data = stream.next()?;
-        convert_frame_to_image(&data, format)
+        Ok(stream)
     }
 
     fn into_logs(self) -> Vec<String> {
@@ -683,7 +948,28 @@ pub fn convert_frame_to_image(data: &[u8], format: &Format) -> AppResult<GrayIma
             GrayImage::from_vec(format.width, format.height, buffer)
                 .ok_or_else(|| AppError::FrameProcessing("failed to build image buffer".into()))
         }
-        "GREY" | "Y08" => {
+        "Y10" | "Y12" => {
+            if data.len() < expected_pixels * 2 {
+                return Err(AppError::FrameProcessing(format!(
+                    "expected {} bytes for {} frame, got {}",
+                    expected_pixels * 2,
+                    pixel_format,
+                    data.len()
+                )));
+            }
+            // Little-endian samples in the low bits of each 16-bit word.
+            let shift = if pixel_format == "Y10" { 2 } else { 4 };
+            let mut buffer = Vec::with_capacity(expected_pixels);
+            for idx in 0..expected_pixels {
+                let low = data[idx * 2] as u16;
+                let high = data[idx * 2 + 1] as u16;
+                let value = (high << 8) | low;
+                buffer.push((value >> shift).min(255) as u8);
+            }
+            GrayImage::from_vec(format.width, format.height, buffer)
+                .ok_or_else(|| AppError::FrameProcessing("failed to build image buffer".into()))
+        }
+        "GREY" | "Y8" | "Y08" => {
             if data.len() < expected_pixels {
                 return Err(AppError::FrameProcessing(format!(
                     "expected {} bytes for {} frame, got {}",
@@ -885,6 +1171,14 @@ mod tests {
         assert_eq!(image.as_raw(), &data);
     }
 
+    #[test]
+    fn convert_y10_keeps_the_high_eight_bits() {
+        let format = build_format("Y10", 2, 1);
+        let data: Vec<u8> = vec![0xff, 0x03, 0x04, 0x00];
+        let image = convert_frame_to_image(&data, &format).expect("convert y10");
+        assert_eq!(image.as_raw(), &vec![255, 1]);
+    }
+
     #[test]
     fn convert_yuyv_to_png_buffer() {
         let format = build_format("YUYV", 2, 2);
@@ -930,22 +1224,105 @@ mod tests {
         config.pixel_format = "GREY".into();
         config.warmup_frames = 2;
         let mut fake = configure_fake_device(&config);
-        fake.frames.push_back(vec![1, 1, 1, 1]);
-        fake.frames.push_back(vec![2, 2, 2, 2]);
-        fake.frames.push_back(vec![9, 8, 7, 6]);
+        {
+            let mut frames = fake.frames.lock().unwrap();
+            frames.push_back(vec![1, 1, 1, 1]);
+            frames.push_back(vec![2, 2, 2, 2]);
+            frames.push_back(vec![9, 8, 7, 6]);
+        }
 
         let mut reader = FrameReader::new(&config);
-        let image = reader.read(&mut fake, &build_format("GREY", 2, 2)).unwrap();
-        assert_eq!(image.as_raw(), &vec![9, 8, 7, 6]);
-        assert!(fake.frames.is_empty());
+        let mut stream = reader.start(&mut fake).unwrap();
+        let (data, metadata) = stream.next().unwrap();
+        assert_eq!(data, vec![9, 8, 7, 6]);
+        assert_eq!(metadata.sequence, 2);
+        assert!(fake.frames.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn session_warms_up_once_and_serves_consecutive_frames() {
+        let mut config = sample_config();
+        config.pixel_format = "GREY".into();
+        config.warmup_frames = 1;
+        let fake = configure_fake_device(&config);
+        let frames = fake.frames.clone();
+        {
+            let mut queue = frames.lock().unwrap();
+            queue.push_back(vec![0, 0, 0, 0]);
+            queue.push_back(vec![1, 2, 3, 4]);
+            queue.push_back(vec![5, 6, 7, 8]);
+        }
+
+        let mut session = CaptureSession::start(&config, Box::new(fake)).unwrap();
+        assert_eq!(session.format().pixel_format, "GREY");
+        assert_eq!(session.device().driver, "fake");
+        assert!(session
+            .take_logs()
+            .iter()
+            .any(|line| line.contains("warm-up")));
+
+        assert_eq!(session.next_frame().unwrap().as_raw(), &vec![1, 2, 3, 4]);
+        assert_eq!(session.next_frame().unwrap().as_raw(), &vec![5, 6, 7, 8]);
+        assert!(session.take_logs().is_empty());
+        assert!(matches!(
+            session.next_frame(),
+            Err(AppError::FrameProcessing(_))
+        ));
+    }
+
+    #[test]
+    fn session_discard_until_drains_stale_frames() {
+        let mut config = sample_config();
+        config.pixel_format = "GREY".into();
+        let fake = configure_fake_device(&config);
+        let frames = fake.frames.clone();
+        frames.lock().unwrap().push_back(vec![1, 1, 1, 1]);
+
+        let mut session = CaptureSession::start(&config, Box::new(fake)).unwrap();
+        assert_eq!(session.discard_until(Instant::now(), &|| false).unwrap(), 0);
+        assert_eq!(frames.lock().unwrap().len(), 1);
+
+        let err = session
+            .discard_until(Instant::now() + Duration::from_secs(5), &|| false)
+            .unwrap_err();
+        assert!(matches!(err, AppError::FrameProcessing(_)));
+        assert!(frames.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn session_reads_stop_at_deadline_and_on_cancellation() {
+        let mut config = sample_config();
+        config.pixel_format = "GREY".into();
+        let fake = configure_fake_device(&config);
+        let frames = fake.frames.clone();
+        frames.lock().unwrap().push_back(vec![1, 2, 3, 4]);
+
+        let mut session = CaptureSession::start(&config, Box::new(fake)).unwrap();
+        assert!(session
+            .next_frame_until(Instant::now(), &|| false)
+            .unwrap()
+            .is_none());
+        let far = Instant::now() + Duration::from_secs(5);
+        assert!(matches!(
+            session.next_frame_until(far, &|| true),
+            Err(AppError::Cancelled)
+        ));
+        assert!(matches!(
+            session.discard_until(far, &|| true),
+            Err(AppError::Cancelled)
+        ));
+        assert_eq!(frames.lock().unwrap().len(), 1);
+
+        let frame = session.next_frame_until(far, &|| false).unwrap().unwrap();
+        assert_eq!(frame.as_raw(), &vec![1, 2, 3, 4]);
     }
 
     #[test]
     fn builder_uses_injected_device_and_sink() {
         let mut config = sample_config();
         config.output = Some(PathBuf::from("/tmp/fake.png"));
-        let mut fake = configure_fake_device(&config);
-        fake.frames.push_back(vec![0; 8]);
+        let fake = configure_fake_device(&config);
+        fake.frames.lock().unwrap().push_back(vec![0; 8]);
 
         let device_slot = Arc::new(Mutex::new(Some(fake)));
         let factory = {
@@ -973,6 +1350,54 @@ mod tests {
         assert_eq!(sink_state.lock().unwrap().len(), 1);
     }
 
+    #[test]
+    fn fallback_skips_absent_and_busy_devices_only() {
+        let configs: Vec<CaptureConfig> = (0..3)
+            .map(|index| CaptureConfig {
+                device: DeviceLocator::Index(index),
+                ..sample_config()
+            })
+            .collect();
+        let fault = |errno| AppError::Io(std::io::Error::from_raw_os_error(errno));
+
+        let opened = open_first_available(&configs, |config| match config.device {
+            DeviceLocator::Index(0) => Err(fault(libc::ENOENT)),
+            DeviceLocator::Index(1) => Err(fault(libc::EBUSY)),
+            _ => Ok(config.device.display()),
+        })
+        .unwrap();
+        assert_eq!(opened.index, 2);
+        assert_eq!(opened.opened, "/dev/video2");
+        assert_eq!(
+            opened.skipped,
+            vec![
+                SkippedDevice {
+                    device: "/dev/video0".into(),
+                    fault: DeviceFault::Absent,
+                },
+                SkippedDevice {
+                    device: "/dev/video1".into(),
+                    fault: DeviceFault::Busy,
+                },
+            ]
+        );
+
+        let denied = open_first_available(&configs, |config| match config.device {
+            DeviceLocator::Index(0) => Err::<(), _>(fault(libc::EACCES)),
+            _ => Ok(()),
+        });
+        assert_eq!(
+            denied.unwrap_err().device_fault(),
+            Some(DeviceFault::PermissionDenied)
+        );
+
+        let all_absent = open_first_available(&configs, |_| Err::<(), _>(fault(libc::ENODEV)));
+        assert_eq!(
+            all_absent.unwrap_err().device_fault(),
+            Some(DeviceFault::Absent)
+        );
+    }
+
     struct LoggingSink {
         writes: Arc<Mutex<Vec<PathBuf>>>,
     }
@@ -1036,6 +1461,7 @@ mod tests {
             gain: None,
             auto_exposure: None,
             auto_gain: None,
+            skipped_devices: Vec::new(),
         };
 
         let json = serde_json::to_value(&summary).expect("serialize summary");
@@ -1046,6 +1472,7 @@ mod tests {
         assert!(json["gain"].is_null());
         assert!(json["auto_exposure"].is_null());
         assert!(json["auto_gain"].is_null());
+        assert!(json["skipped_devices"].is_null());
     }
 
     #[test]
@@ -1068,10 +1495,15 @@ mod tests {
             gain: None,
             auto_exposure: Some("applied".into()),
             auto_gain: Some("unsupported".into()),
+            skipped_devices: vec![SkippedDevice {
+                device: "/dev/video4".into(),
+                fault: DeviceFault::Absent,
+            }],
         };
 
         let json = serde_json::to_value(&summary).expect("serialize summary");
         assert_eq!(json["auto_exposure"], "applied");
         assert_eq!(json["auto_gain"], "unsupported");
+        assert_eq!(json["skipped_devices"][0]["fault"], "absent");
     }
 }
@@ -1,4 +1,6 @@
+use std::io;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use image::GrayImage;
 use v4l::buffer::Type;
@@ -12,11 +14,13 @@ use v4l::video::Capture;
 
 use crate::errors::{AppError, AppResult};
 
-use super::{ensure_output_path, write_image, DeviceLocator};
+use super::{ensure_output_path, write_image, DeviceLocator, FrameMetadata};
 
 #[cfg(test)]
 use std::collections::VecDeque;
 #[cfg(test)]
+use std::sync::{Arc, Mutex};
+#[cfg(test)]
 use v4l::capability::Flags as CapabilityFlags;
 #[cfg(test)]
 use v4l::framesize::FrameSizeEnum;
@@ -29,11 +33,18 @@ pub trait CaptureDevice {
     fn set_format(&mut self, format: &Format) -> AppResult<Format>;
     fn query_controls(&self) -> AppResult<Vec<Description>>;
     fn set_control(&mut self, control: Control) -> AppResult<()>;
-    fn start_stream<'a>(&'a mut self, buffer_count: u32) -> AppResult<Box<dyn CaptureStream + 'a>>;
+    fn start_stream(&mut self, buffer_count: u32) -> AppResult<Box<dyn CaptureStream>>;
 }
 
 pub trait CaptureStream {
-    fn next(&mut self) -> AppResult<Vec<u8>>;
+    fn next(&mut self) -> AppResult<(Vec<u8>, FrameMetadata)>;
+
+    /// Like `next`, but returns `None` when no frame arrived within `timeout`
+    /// or a signal interrupted the wait, so callers can check deadlines and
+    /// cancellation between polls.
+    fn next_within(&mut self, _timeout: Duration) -> AppResult<Option<(Vec<u8>, FrameMetadata)>> {
+        self.next().map(Some)
+    }
 }
 
 pub type CaptureDeviceFactory =
@@ -84,21 +95,80 @@ impl CaptureDevice for V4lCaptureDevice {
         self.inner.set_control(control).map_err(AppError::from)
     }
 
-    fn start_stream<'a>(&'a mut self, buffer_count: u32) -> AppResult<Box<dyn CaptureStream + 'a>> {
+    fn start_stream(&mut self, buffer_count: u32) -> AppResult<Box<dyn CaptureStream>> {
+        // The mmap stream holds its own handle to the device, so it can outlive this borrow.
         let stream = Stream::with_buffers(&self.inner, Type::VideoCapture, buffer_count)
             .map_err(AppError::from)?;
-        Ok(Box::new(V4lStream { stream }))
+        Ok(Box::new(V4lStream {
+            stream,
+            drained: false,
+        }))
     }
 }
 
-struct V4lStream<'a> {
-    stream: Stream<'a>,
+struct V4lStream {
+    stream: Stream<'static>,
+    /// Set when a dequeue gave up after the buffer we held was queued again,
+    /// so the driver owns every buffer. `Stream::next` re-queues the buffer
+    /// it handed out last, so one has to be taken back before calling it.
+    drained: bool,
+}
+
+impl V4lStream {
+    fn read(&mut self, timeout: Option<Duration>) -> AppResult<Option<(Vec<u8>, FrameMetadata)>> {
+        match timeout {
+            Some(timeout) => self
+                .stream
+                .set_timeout(timeout.max(Duration::from_millis(1))),
+            None => self.stream.clear_timeout(),
+        }
+        if self.drained {
+            let millis = timeout.map_or(-1, |timeout| timeout.as_millis().max(1) as i32);
+            match self.stream.handle().poll(libc::POLLIN, millis) {
+                Ok(0) => return Ok(None),
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => return Ok(None),
+                Err(err) => return Err(err.into()),
+            }
+            // The frame in this buffer is stale by now; `next` re-queues it.
+            V4lCaptureStream::dequeue(&mut self.stream)?;
+            self.drained = false;
+        }
+        let (data, meta) = match V4lCaptureStream::next(&mut self.stream) {
+            Ok(frame) => frame,
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::TimedOut | io::ErrorKind::Interrupted
+                ) =>
+            {
+                self.drained = true;
+                return Ok(None);
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let timestamp = Duration::from_secs(meta.timestamp.sec.max(0) as u64)
+            + Duration::from_micros(meta.timestamp.usec.max(0) as u64);
+        let metadata = FrameMetadata {
+            sequence: meta.sequence,
+            // Drivers that do not timestamp buffers leave the field zeroed.
+            timestamp: (timestamp > Duration::ZERO).then_some(timestamp),
+        };
+        Ok(Some((data.to_vec(), metadata)))
+    }
 }
 
-impl CaptureStream for V4lStream<'_> {
-    fn next(&mut self) -> AppResult<Vec<u8>> {
-        let (data, _) = self.stream.next().map_err(AppError::from)?;
-        Ok(data.to_vec())
+impl CaptureStream for V4lStream {
+    fn next(&mut self) -> AppResult<(Vec<u8>, FrameMetadata)> {
+        loop {
+            if let Some(frame) = self.read(None)? {
+                return Ok(frame);
+            }
+        }
+    }
+
+    fn next_within(&mut self, timeout: Duration) -> AppResult<Option<(Vec<u8>, FrameMetadata)>> {
+        self.read(Some(timeout))
     }
 }
 
@@ -120,7 +190,7 @@ pub struct FakeCaptureDevice {
     pub format: Format,
     pub control_error: Option<String>,
     pub set_control_calls: Vec<Control>,
-    pub frames: VecDeque<Vec<u8>>,
+    pub frames: Arc<Mutex<VecDeque<Vec<u8>>>>,
 }
 
 #[cfg(test)]
@@ -139,7 +209,7 @@ impl FakeCaptureDevice {
             format,
             control_error: None,
             set_control_calls: Vec::new(),
-            frames: VecDeque::new(),
+            frames: Arc::new(Mutex::new(VecDeque::new())),
         }
     }
 }
@@ -180,27 +250,36 @@ impl CaptureDevice for FakeCaptureDevice {
         Ok(())
     }
 
-    fn start_stream<'a>(
-        &'a mut self,
-        _buffer_count: u32,
-    ) -> AppResult<Box<dyn CaptureStream + 'a>> {
+    fn start_stream(&mut self, _buffer_count: u32) -> AppResult<Box<dyn CaptureStream>> {
         Ok(Box::new(FakeStream {
-            frames: &mut self.frames,
+            frames: self.frames.clone(),
+            sequence: 0,
         }))
     }
 }
 
+/// Numbers frames from zero, 33 ms apart.
 #[cfg(test)]
-struct FakeStream<'a> {
-    frames: &'a mut VecDeque<Vec<u8>>,
+struct FakeStream {
+    frames: Arc<Mutex<VecDeque<Vec<u8>>>>,
+    sequence: u32,
 }
 
 #[cfg(test)]
-impl<'a> CaptureStream for FakeStream<'a> {
-    fn next(&mut self) -> AppResult<Vec<u8>> {
-        self.frames
+impl CaptureStream for FakeStream {
+    fn next(&mut self) -> AppResult<(Vec<u8>, FrameMetadata)> {
+        let data = self
+            .frames
+            .lock()
+            .unwrap()
             .pop_front()
-            .ok_or_else(|| AppError::FrameProcessing("no frame".into()))
+            .ok_or_else(|| AppError::FrameProcessing("no frame".into()))?;
+        let metadata = FrameMetadata {
+            sequence: self.sequence,
+            timestamp: Some(Duration::from_millis(33 * (u64::from(self.sequence) + 1))),
+        };
+        self.sequence += 1;
+        Ok((data, metadata))
     }
 }
 
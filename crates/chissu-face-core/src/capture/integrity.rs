@@ -0,0 +1,223 @@
+//! Detects frozen or replayed capture streams.
+//!
+//! A live sensor never delivers two bit-identical frames of a lit scene, and
+//! the driver stamps every buffer with a rising sequence number and capture
+//! time. A stream injected from a still image or a recording tends to break
+//! one of those: frames repeat exactly, timestamps stand still or run
+//! backwards, or the counter skips ahead faster than any camera could
+//! produce frames.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
+
+use image::GrayImage;
+use serde::{Deserialize, Serialize};
+
+use super::FrameMetadata;
+
+/// Consecutive bit-identical frames after which a stream counts as frozen.
+pub const MAX_IDENTICAL_FRAMES: usize = 3;
+
+/// Frame rate no capture device reaches; a sequence jump implying a higher
+/// rate than this was not produced by the sensor.
+const MAX_PLAUSIBLE_FPS: u64 = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StreamAnomaly {
+    /// The same non-uniform frame arrived `count` times in a row.
+    IdenticalFrames { count: usize },
+    /// The buffer timestamp did not advance past the previous frame.
+    TimestampStalled {
+        previous_micros: u64,
+        current_micros: u64,
+    },
+    /// The sequence number repeated or ran backwards.
+    SequenceRegressed { previous: u32, current: u32 },
+    /// The sequence number skipped more frames than the elapsed time allows.
+    SequenceJump { delta: u32, elapsed_micros: u64 },
+}
+
+impl fmt::Display for StreamAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamAnomaly::IdenticalFrames { count } => {
+                write!(f, "{count} bit-identical frames in a row")
+            }
+            StreamAnomaly::TimestampStalled {
+                previous_micros,
+                current_micros,
+            } => write!(
+                f,
+                "buffer timestamp did not advance ({previous_micros}us -> {current_micros}us)"
+            ),
+            StreamAnomaly::SequenceRegressed { previous, current } => {
+                write!(f, "sequence number went from {previous} to {current}")
+            }
+            StreamAnomaly::SequenceJump {
+                delta,
+                elapsed_micros,
+            } => write!(
+                f,
+                "sequence number advanced by {delta} in {elapsed_micros}us"
+            ),
+        }
+    }
+}
+
+/// Tracks consecutive frames of one stream. Frames skipped by the caller,
+/// for instance while waiting out a frame interval, are fine: only jumps
+/// faster than the clock allows are reported.
+#[derive(Debug, Default)]
+pub struct StreamMonitor {
+    last_digest: Option<u64>,
+    identical: usize,
+    last_metadata: Option<FrameMetadata>,
+}
+
+impl StreamMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks the next frame and the buffer metadata it came with, when the
+    /// source reports any.
+    pub fn observe(
+        &mut self,
+        image: &GrayImage,
+        metadata: Option<FrameMetadata>,
+    ) -> Result<(), StreamAnomaly> {
+        self.observe_pixels(image);
+        if self.identical >= MAX_IDENTICAL_FRAMES {
+            return Err(StreamAnomaly::IdenticalFrames {
+                count: self.identical,
+            });
+        }
+        let Some(current) = metadata else {
+            return Ok(());
+        };
+        let previous = self.last_metadata.replace(current);
+        match previous {
+            Some(previous) => check_metadata(&previous, &current),
+            None => Ok(()),
+        }
+    }
+
+    fn observe_pixels(&mut self, image: &GrayImage) {
+        // A covered lens or an unlit scene legitimately repeats one flat
+        // level; only frames with some content are compared.
+        let pixels = image.as_raw();
+        if pixels.windows(2).all(|pair| pair[0] == pair[1]) {
+            self.last_digest = None;
+            self.identical = 0;
+            return;
+        }
+        let mut hasher = DefaultHasher::new();
+        image.dimensions().hash(&mut hasher);
+        pixels.hash(&mut hasher);
+        let digest = hasher.finish();
+        if self.last_digest == Some(digest) {
+            self.identical += 1;
+        } else {
+            self.last_digest = Some(digest);
+            self.identical = 1;
+        }
+    }
+}
+
+fn check_metadata(previous: &FrameMetadata, current: &FrameMetadata) -> Result<(), StreamAnomaly> {
+    if current.sequence <= previous.sequence {
+        return Err(StreamAnomaly::SequenceRegressed {
+            previous: previous.sequence,
+            current: current.sequence,
+        });
+    }
+    let (Some(before), Some(now)) = (previous.timestamp, current.timestamp) else {
+        return Ok(());
+    };
+    if now <= before {
+        return Err(StreamAnomaly::TimestampStalled {
+            previous_micros: micros(before),
+            current_micros: micros(now),
+        });
+    }
+    let delta = current.sequence - previous.sequence;
+    let elapsed = now - before;
+    if delta > 1
+        && u128::from(delta) * 1_000_000 > u128::from(MAX_PLAUSIBLE_FPS) * elapsed.as_micros()
+    {
+        return Err(StreamAnomaly::SequenceJump {
+            delta,
+            elapsed_micros: micros(elapsed),
+        });
+    }
+    Ok(())
+}
+
+fn micros(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    fn frame(seed: u8) -> GrayImage {
+        GrayImage::from_fn(16, 16, |x, y| Luma([seed.wrapping_add((x * y) as u8)]))
+    }
+
+    fn meta(sequence: u32, millis: u64) -> Option<FrameMetadata> {
+        Some(FrameMetadata {
+            sequence,
+            timestamp: Some(Duration::from_millis(millis)),
+        })
+    }
+
+    #[test]
+    fn repeated_frames_are_frozen_unless_flat() {
+        let mut monitor = StreamMonitor::new();
+        assert_eq!(monitor.observe(&frame(1), None), Ok(()));
+        assert_eq!(monitor.observe(&frame(1), None), Ok(()));
+        assert_eq!(
+            monitor.observe(&frame(1), None),
+            Err(StreamAnomaly::IdenticalFrames { count: 3 })
+        );
+
+        let mut dark = StreamMonitor::new();
+        for _ in 0..MAX_IDENTICAL_FRAMES + 2 {
+            assert_eq!(dark.observe(&GrayImage::new(16, 16), None), Ok(()));
+        }
+    }
+
+    #[test]
+    fn metadata_must_advance_at_a_plausible_rate() {
+        let mut monitor = StreamMonitor::new();
+        assert_eq!(monitor.observe(&frame(1), meta(10, 1_000)), Ok(()));
+        // Frames dropped while the caller was busy are fine.
+        assert_eq!(monitor.observe(&frame(2), meta(40, 2_000)), Ok(()));
+        assert!(matches!(
+            monitor.observe(&frame(3), meta(40, 2_033)),
+            Err(StreamAnomaly::SequenceRegressed { .. })
+        ));
+
+        let mut stalled = StreamMonitor::new();
+        stalled.observe(&frame(1), meta(1, 500)).unwrap();
+        assert!(matches!(
+            stalled.observe(&frame(2), meta(2, 500)),
+            Err(StreamAnomaly::TimestampStalled { .. })
+        ));
+
+        let mut jumped = StreamMonitor::new();
+        jumped.observe(&frame(1), meta(1, 500)).unwrap();
+        assert_eq!(
+            jumped.observe(&frame(2), meta(5_001, 533)),
+            Err(StreamAnomaly::SequenceJump {
+                delta: 5_000,
+                elapsed_micros: 33_000
+            })
+        );
+    }
+}
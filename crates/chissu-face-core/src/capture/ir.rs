@@ -0,0 +1,212 @@
+//! Checks that a capture stream really comes from an infrared camera.
+//!
+//! Colour webcams are far easier to fool than IR sensors, because a phone
+//! screen or a printout looks the same to them as a face. The negotiated
+//! pixel format has to be one of the monochrome formats IR modules expose,
+//! and at least one of the first frames has to show the near-field lighting
+//! of an active IR emitter: not black, not one flat grey level.
+
+use std::fmt;
+
+use image::GrayImage;
+use serde::{Deserialize, Serialize};
+
+use super::{CaptureConfig, CaptureSession};
+use crate::errors::AppResult;
+
+/// Monochrome FourCCs accepted as IR streams, as reported by V4L2 without
+/// trailing padding.
+pub const IR_PIXEL_FORMATS: &[&str] = &["GREY", "Y8", "Y08", "Y10", "Y12", "Y16"];
+
+/// Frames sampled before a stream without visible IR lighting is refused.
+/// Many modules fire the emitter on every other frame only.
+pub const IR_PROBE_FRAMES: usize = 4;
+
+/// Mean grey level below which the emitter is taken to be off.
+const MIN_MEAN_INTENSITY: f64 = 10.0;
+/// Pixels at or below this level count towards [`IrFrameStats::dark_fraction`].
+const DARK_LEVEL: u8 = 8;
+/// Frames darker than this everywhere show no lit subject.
+const MAX_DARK_FRACTION: f64 = 0.95;
+/// Standard deviation below which a frame is a single flat level.
+const MIN_CONTRAST: f64 = 4.0;
+
+pub fn is_ir_pixel_format(pixel_format: &str) -> bool {
+    let pixel_format = pixel_format.trim();
+    IR_PIXEL_FORMATS
+        .iter()
+        .any(|format| format.eq_ignore_ascii_case(pixel_format))
+}
+
+/// Whole-frame intensity statistics of one greyscale frame.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct IrFrameStats {
+    /// Mean grey level (0-255).
+    pub mean_intensity: f64,
+    /// Share of pixels at or below the dark level.
+    pub dark_fraction: f64,
+    /// Standard deviation of the grey levels.
+    pub contrast: f64,
+}
+
+impl IrFrameStats {
+    pub fn from_image(image: &GrayImage) -> Self {
+        let pixels = image.as_raw();
+        if pixels.is_empty() {
+            return Self {
+                mean_intensity: 0.0,
+                dark_fraction: 1.0,
+                contrast: 0.0,
+            };
+        }
+        let count = pixels.len() as f64;
+        let mut sum = 0.0;
+        let mut sum_sq = 0.0;
+        let mut dark = 0usize;
+        for &value in pixels {
+            let value_f = f64::from(value);
+            sum += value_f;
+            sum_sq += value_f * value_f;
+            if value <= DARK_LEVEL {
+                dark += 1;
+            }
+        }
+        let mean = sum / count;
+        let variance = (sum_sq / count - mean * mean).max(0.0);
+        Self {
+            mean_intensity: mean,
+            dark_fraction: dark as f64 / count,
+            contrast: variance.sqrt(),
+        }
+    }
+
+    /// Whether the frame shows a scene lit by the IR emitter.
+    pub fn is_illuminated(&self) -> bool {
+        self.mean_intensity >= MIN_MEAN_INTENSITY
+            && self.dark_fraction <= MAX_DARK_FRACTION
+            && self.contrast >= MIN_CONTRAST
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IrRejection {
+    /// The stream uses a colour or compressed format.
+    PixelFormat { pixel_format: String },
+    /// The frame source did not report its pixel format.
+    FormatUnknown,
+    /// None of the sampled frames looked lit by an IR emitter.
+    NotIlluminated { frames: usize },
+}
+
+impl fmt::Display for IrRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IrRejection::PixelFormat { pixel_format } => write!(
+                f,
+                "pixel format {pixel_format} is not a monochrome IR format ({})",
+                IR_PIXEL_FORMATS.join(", ")
+            ),
+            IrRejection::FormatUnknown => write!(f, "pixel format unavailable"),
+            IrRejection::NotIlluminated { frames } => {
+                write!(f, "no IR illumination in {frames} frame(s)")
+            }
+        }
+    }
+}
+
+/// Checks the negotiated pixel format of a stream.
+pub fn check_ir_format(pixel_format: Option<&str>) -> Result<(), IrRejection> {
+    match pixel_format {
+        None => Err(IrRejection::FormatUnknown),
+        Some(format) if is_ir_pixel_format(format) => Ok(()),
+        Some(format) => Err(IrRejection::PixelFormat {
+            pixel_format: format.to_string(),
+        }),
+    }
+}
+
+/// Result of sampling a stream with [`probe_ir_stream`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IrStreamReport {
+    pub pixel_format: String,
+    /// Statistics of every sampled frame; empty when the format was refused.
+    pub samples: Vec<IrFrameStats>,
+}
+
+impl IrStreamReport {
+    pub fn check(&self) -> Result<(), IrRejection> {
+        check_ir_format(Some(&self.pixel_format))?;
+        if self.samples.iter().any(IrFrameStats::is_illuminated) {
+            Ok(())
+        } else {
+            Err(IrRejection::NotIlluminated {
+                frames: self.samples.len(),
+            })
+        }
+    }
+}
+
+/// Opens the device in `config` and samples up to `frames` frames, stopping
+/// at the first one that looks lit. Frames are only read when the negotiated
+/// format passes [`check_ir_format`].
+pub fn probe_ir_stream(config: &CaptureConfig, frames: usize) -> AppResult<IrStreamReport> {
+    let mut session = CaptureSession::open(config)?;
+    let pixel_format = session.format().pixel_format.clone();
+    let mut samples = Vec::new();
+    if is_ir_pixel_format(&pixel_format) {
+        for _ in 0..frames.max(1) {
+            let stats = IrFrameStats::from_image(&session.next_frame()?);
+            samples.push(stats);
+            if stats.is_illuminated() {
+                break;
+            }
+        }
+    }
+    Ok(IrStreamReport {
+        pixel_format,
+        samples,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    fn lit_frame() -> GrayImage {
+        GrayImage::from_fn(32, 32, |x, y| {
+            let dx = x as f64 - 16.0;
+            let dy = y as f64 - 16.0;
+            let falloff = (dx * dx + dy * dy).sqrt() * 10.0;
+            Luma([(200.0 - falloff).max(0.0) as u8])
+        })
+    }
+
+    #[test]
+    fn accepts_monochrome_formats_only() {
+        assert_eq!(check_ir_format(Some("Y16")), Ok(()));
+        assert_eq!(check_ir_format(Some("grey")), Ok(()));
+        assert!(matches!(
+            check_ir_format(Some("YUYV")),
+            Err(IrRejection::PixelFormat { .. })
+        ));
+        assert_eq!(check_ir_format(None), Err(IrRejection::FormatUnknown));
+    }
+
+    #[test]
+    fn lit_frames_pass_and_dark_or_flat_frames_do_not() {
+        assert!(IrFrameStats::from_image(&lit_frame()).is_illuminated());
+        assert!(!IrFrameStats::from_image(&GrayImage::new(32, 32)).is_illuminated());
+        let flat = GrayImage::from_pixel(32, 32, Luma([120]));
+        assert!(!IrFrameStats::from_image(&flat).is_illuminated());
+
+        let report = IrStreamReport {
+            pixel_format: "GREY".into(),
+            samples: vec![IrFrameStats::from_image(&GrayImage::new(8, 8))],
+        };
+        assert_eq!(
+            report.check(),
+            Err(IrRejection::NotIlluminated { frames: 1 })
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! Stable camera selectors resolved through udev.
+//!
+//! `/dev/videoN` numbers follow probe order, so they change across reboots
+//! and dock events. A selector names the camera by something that stays put
+//! and is looked up among the `video4linux` capture nodes each time the
+//! device is opened:
+//!
+//! - `by-id:<id>`: the udev `ID_SERIAL`, as in
+//!   `/dev/v4l/by-id/usb-<id>-video-index0`, or `<vendor>:<product>` USB ids
+//! - `by-path:<path>`: the udev `ID_PATH` of the port, as in `/dev/v4l/by-path`
+//! - `card:<name>`: the V4L2 card name, compared case-insensitively
+//! - `ir:first`: the first node named as an infrared camera or offering a
+//!   monochrome IR pixel format
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::device::{CaptureDevice, V4lCaptureDevice};
+use super::ir::is_ir_pixel_format;
+use super::{fourcc_to_string, DeviceLocator};
+use crate::errors::{AppError, AppResult};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceSelector {
+    ById(String),
+    ByPath(String),
+    Card(String),
+    FirstIr,
+}
+
+impl DeviceSelector {
+    /// Parses the selector forms listed in the module docs; anything else is
+    /// not a selector.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (kind, value) = text.split_once(':')?;
+        let value = value.trim();
+        match kind {
+            "by-id" if !value.is_empty() => Some(DeviceSelector::ById(value.into())),
+            "by-path" if !value.is_empty() => Some(DeviceSelector::ByPath(value.into())),
+            "card" if !value.is_empty() => Some(DeviceSelector::Card(value.into())),
+            "ir" if value == "first" => Some(DeviceSelector::FirstIr),
+            _ => None,
+        }
+    }
+
+    /// Picks the node this selector names. `ir:first` calls `has_ir_format`
+    /// for nodes whose name does not already mark them as infrared.
+    pub fn select<'a>(
+        &self,
+        cameras: &'a [CameraNode],
+        has_ir_format: impl Fn(&Path) -> bool,
+    ) -> Option<&'a CameraNode> {
+        let mut cameras = cameras.iter();
+        match self {
+            DeviceSelector::ById(id) => cameras.find(|camera| camera.matches_id(id)),
+            DeviceSelector::ByPath(path) => {
+                cameras.find(|camera| camera.id_path.as_deref() == Some(path.as_str()))
+            }
+            DeviceSelector::Card(name) => {
+                cameras.find(|camera| camera.name.trim().eq_ignore_ascii_case(name))
+            }
+            DeviceSelector::FirstIr => {
+                cameras.find(|camera| camera.is_named_ir() || has_ir_format(&camera.devnode))
+            }
+        }
+    }
+
+    /// Looks the selector up among the capture nodes udev knows about now.
+    pub fn resolve(&self) -> AppResult<PathBuf> {
+        let cameras = scan_cameras().map_err(|source| AppError::DeviceOpen {
+            device: self.to_string(),
+            source,
+        })?;
+        self.select(&cameras, offers_ir_format)
+            .map(|camera| camera.devnode.clone())
+            .ok_or_else(|| AppError::DeviceOpen {
+                device: self.to_string(),
+                source: io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "no matching video4linux capture node",
+                ),
+            })
+    }
+}
+
+impl fmt::Display for DeviceSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceSelector::ById(id) => write!(f, "by-id:{id}"),
+            DeviceSelector::ByPath(path) => write!(f, "by-path:{path}"),
+            DeviceSelector::Card(name) => write!(f, "card:{name}"),
+            DeviceSelector::FirstIr => write!(f, "ir:first"),
+        }
+    }
+}
+
+/// What udev reports about one capture node.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CameraNode {
+    pub devnode: PathBuf,
+    /// V4L2 card name from the sysfs `name` attribute.
+    pub name: String,
+    pub serial: Option<String>,
+    pub id_path: Option<String>,
+    pub vendor_id: Option<String>,
+    pub model_id: Option<String>,
+}
+
+impl CameraNode {
+    fn matches_id(&self, id: &str) -> bool {
+        if self.serial.as_deref() == Some(id) {
+            return true;
+        }
+        match (&self.vendor_id, &self.model_id) {
+            (Some(vendor), Some(model)) => id.eq_ignore_ascii_case(&format!("{vendor}:{model}")),
+            _ => false,
+        }
+    }
+
+    fn is_named_ir(&self) -> bool {
+        self.name
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .any(|word| word.eq_ignore_ascii_case("ir") || word.eq_ignore_ascii_case("infrared"))
+    }
+}
+
+/// Lists the `video4linux` capture nodes known to udev in node order.
+/// Metadata nodes of UVC cameras are left out.
+pub fn scan_cameras() -> io::Result<Vec<CameraNode>> {
+    let mut enumerator = udev::Enumerator::new()?;
+    enumerator.match_subsystem("video4linux")?;
+    let mut cameras = Vec::new();
+    for device in enumerator.scan_devices()? {
+        let Some(devnode) = device.devnode() else {
+            continue;
+        };
+        let property = |key: &str| {
+            device
+                .property_value(key)
+                .map(|value| value.to_string_lossy().into_owned())
+        };
+        if !property("ID_V4L_CAPABILITIES").is_some_and(|caps| caps.contains(":capture:")) {
+            continue;
+        }
+        let camera = CameraNode {
+            devnode: devnode.to_path_buf(),
+            name: device
+                .attribute_value("name")
+                .map(|value| value.to_string_lossy().trim().to_string())
+                .unwrap_or_default(),
+            serial: property("ID_SERIAL"),
+            id_path: property("ID_PATH"),
+            vendor_id: property("ID_VENDOR_ID"),
+            model_id: property("ID_MODEL_ID"),
+        };
+        cameras.push((device.sysnum().unwrap_or(usize::MAX), camera));
+    }
+    cameras.sort_by_key(|(number, _)| *number);
+    Ok(cameras.into_iter().map(|(_, camera)| camera).collect())
+}
+
+fn offers_ir_format(devnode: &Path) -> bool {
+    let Ok(device) = V4lCaptureDevice::open(&DeviceLocator::Path(devnode.to_path_buf())) else {
+        return false;
+    };
+    device.enum_formats().is_ok_and(|formats| {
+        formats
+            .iter()
+            .any(|format| is_ir_pixel_format(&fourcc_to_string(format.fourcc)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera(devnode: &str, name: &str, serial: &str, id_path: &str) -> CameraNode {
+        CameraNode {
+            devnode: devnode.into(),
+            name: name.into(),
+            serial: Some(serial.into()),
+            id_path: Some(id_path.into()),
+            vendor_id: Some("04f2".into()),
+            model_id: Some(format!("b6{}", &devnode[devnode.len() - 1..])),
+        }
+    }
+
+    #[test]
+    fn parses_known_forms_only() {
+        for text in [
+            "by-id:Chicony_Integrated_IR_Camera",
+            "by-path:pci-0000:00:14.0-usb-0:5:1.2",
+            "card:Integrated IR Camera",
+            "ir:first",
+        ] {
+            let selector = DeviceSelector::parse(text).unwrap();
+            assert_eq!(selector.to_string(), text);
+        }
+        assert_eq!(DeviceSelector::parse("/dev/video2"), None);
+        assert_eq!(DeviceSelector::parse("ir:second"), None);
+        assert_eq!(DeviceSelector::parse("by-id:"), None);
+        assert_eq!(
+            DeviceSelector::parse("/dev/v4l/by-path/pci-0000:00:14.0-video-index0"),
+            None
+        );
+    }
+
+    #[test]
+    fn selects_cameras_by_stable_properties() {
+        let cameras = [
+            camera(
+                "/dev/video0",
+                "Integrated Camera: Integrated C",
+                "Chicony_Integrated_Camera",
+                "pci-0000:00:14.0-usb-0:5:1.0",
+            ),
+            camera(
+                "/dev/video2",
+                "Integrated Camera: Integrated I",
+                "Chicony_Integrated_Camera",
+                "pci-0000:00:14.0-usb-0:5:1.2",
+            ),
+            camera(
+                "/dev/video4",
+                "Dock IR Camera",
+                "Dock_IR_Camera_0001",
+                "pci-0000:3a:00.0-usb-0:2:1.0",
+            ),
+        ];
+        let no_ir_formats = |_: &Path| false;
+        let select = |text: &str, has_ir: &dyn Fn(&Path) -> bool| {
+            DeviceSelector::parse(text)
+                .unwrap()
+                .select(&cameras, has_ir)
+                .map(|camera| camera.devnode.display().to_string())
+        };
+
+        assert_eq!(
+            select("by-id:Dock_IR_Camera_0001", &no_ir_formats).as_deref(),
+            Some("/dev/video4")
+        );
+        assert_eq!(
+            select("by-id:04F2:B62", &no_ir_formats).as_deref(),
+            Some("/dev/video2")
+        );
+        assert_eq!(
+            select("by-path:pci-0000:00:14.0-usb-0:5:1.2", &no_ir_formats).as_deref(),
+            Some("/dev/video2")
+        );
+        assert_eq!(
+            select("card:dock ir camera", &no_ir_formats).as_deref(),
+            Some("/dev/video4")
+        );
+        assert_eq!(
+            select("ir:first", &no_ir_formats).as_deref(),
+            Some("/dev/video4")
+        );
+        let grey_on_video2 = |node: &Path| node == Path::new("/dev/video2");
+        assert_eq!(
+            select("ir:first", &grey_on_video2).as_deref(),
+            Some("/dev/video2")
+        );
+        assert_eq!(select("by-id:Missing", &no_ir_formats), None);
+    }
+}
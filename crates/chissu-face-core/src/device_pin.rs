@@ -0,0 +1,253 @@
+//! Camera identity pinning against injected video devices.
+//!
+//! Anyone who can load `v4l2loopback` can feed prerecorded frames into a
+//! `/dev/videoN` node. Enrolment therefore records the `driver`, `card` and
+//! `bus_info` the capture device reported in `<dir>/<user>.json`, and
+//! authentication refuses a device that reports anything else. Drivers on a
+//! denylist of virtual cameras are refused whether or not a pin exists.
+
+use std::fmt;
+use std::fs::{self, DirBuilder};
+use std::io::{self, Write};
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+
+use crate::capture::DeviceSummary;
+use crate::errors::{AppError, AppResult};
+use crate::faces::validate_user_name;
+
+/// What a V4L2 device says it is; the node path is left out because it can
+/// change between boots.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceIdentity {
+    pub driver: String,
+    pub card: String,
+    pub bus_info: String,
+}
+
+impl From<&DeviceSummary> for DeviceIdentity {
+    fn from(summary: &DeviceSummary) -> Self {
+        Self {
+            driver: summary.driver.clone(),
+            card: summary.card.clone(),
+            bus_info: summary.bus_info.clone(),
+        }
+    }
+}
+
+impl fmt::Display for DeviceIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "driver={} card={} bus={}",
+            self.driver, self.card, self.bus_info
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DevicePin {
+    #[serde(flatten)]
+    pub identity: DeviceIdentity,
+    /// Node the identity was read from, for reference only.
+    pub device: String,
+    pub pinned_at: String,
+}
+
+impl DevicePin {
+    pub fn new(summary: &DeviceSummary) -> Self {
+        Self {
+            identity: DeviceIdentity::from(summary),
+            device: summary.path.clone(),
+            pinned_at: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        }
+    }
+}
+
+/// Checks applied to the capture device before any frame is used.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceTrustPolicy {
+    /// Identity the device must report; `None` skips the comparison.
+    #[serde(default)]
+    pub pinned: Option<DeviceIdentity>,
+    /// Drivers refused outright, compared case-insensitively.
+    #[serde(default)]
+    pub denied_drivers: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceRejection {
+    VirtualDriver {
+        driver: String,
+    },
+    IdentityMismatch {
+        expected: DeviceIdentity,
+        found: DeviceIdentity,
+    },
+    /// The frame source could not report its identity.
+    IdentityUnavailable,
+}
+
+impl fmt::Display for DeviceRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceRejection::VirtualDriver { driver } => {
+                write!(f, "driver '{driver}' is a known virtual camera")
+            }
+            DeviceRejection::IdentityMismatch { expected, found } => {
+                write!(f, "device reports {found}, pinned {expected}")
+            }
+            DeviceRejection::IdentityUnavailable => write!(f, "device identity unavailable"),
+        }
+    }
+}
+
+impl DeviceTrustPolicy {
+    pub fn is_empty(&self) -> bool {
+        self.pinned.is_none() && self.denied_drivers.is_empty()
+    }
+
+    pub fn check(&self, device: Option<&DeviceSummary>) -> Result<(), DeviceRejection> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        let found = device
+            .map(DeviceIdentity::from)
+            .ok_or(DeviceRejection::IdentityUnavailable)?;
+        if is_denied_driver(&found.driver, &self.denied_drivers) {
+            return Err(DeviceRejection::VirtualDriver {
+                driver: found.driver,
+            });
+        }
+        match &self.pinned {
+            Some(expected) if *expected != found => Err(DeviceRejection::IdentityMismatch {
+                expected: expected.clone(),
+                found,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub fn is_denied_driver(driver: &str, denied: &[String]) -> bool {
+    let driver = driver.trim();
+    denied
+        .iter()
+        .any(|entry| entry.trim().eq_ignore_ascii_case(driver))
+}
+
+#[derive(Debug, Clone)]
+pub struct DevicePinStore {
+    dir: PathBuf,
+}
+
+impl DevicePinStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn pin_path(&self, user: &str) -> AppResult<PathBuf> {
+        validate_user_name(user)?;
+        Ok(self.dir.join(format!("{user}.json")))
+    }
+
+    /// Reads the pin for `user`; `None` when nothing was pinned yet.
+    pub fn load(&self, user: &str) -> AppResult<Option<DevicePin>> {
+        let path = self.pin_path(user)?;
+        match fs::read(&path) {
+            Ok(bytes) => {
+                serde_json::from_slice(&bytes)
+                    .map(Some)
+                    .map_err(|err| AppError::DevicePin {
+                        path,
+                        source: io::Error::new(io::ErrorKind::InvalidData, err),
+                    })
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(source) => Err(AppError::DevicePin { path, source }),
+        }
+    }
+
+    pub fn save(&self, user: &str, pin: &DevicePin) -> AppResult<PathBuf> {
+        let path = self.pin_path(user)?;
+        let wrap = |source| AppError::DevicePin {
+            path: path.clone(),
+            source,
+        };
+        DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(&self.dir)
+            .map_err(wrap)?;
+        let mut tmp = NamedTempFile::new_in(&self.dir).map_err(wrap)?;
+        let payload = serde_json::to_vec_pretty(pin)?;
+        tmp.write_all(&payload)
+            .and_then(|_| tmp.as_file().sync_all())
+            .map_err(wrap)?;
+        let file = tmp.persist(&path).map_err(|err| wrap(err.error))?;
+        file.set_permissions(fs::Permissions::from_mode(0o600))
+            .map_err(wrap)?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn summary(driver: &str, bus_info: &str) -> DeviceSummary {
+        DeviceSummary {
+            driver: driver.into(),
+            card: "Integrated IR Camera".into(),
+            bus_info: bus_info.into(),
+            path: "/dev/video2".into(),
+        }
+    }
+
+    #[test]
+    fn policy_rejects_virtual_drivers_and_other_devices() {
+        let pinned = summary("uvcvideo", "usb-0000:00:14.0-5");
+        let policy = DeviceTrustPolicy {
+            pinned: Some(DeviceIdentity::from(&pinned)),
+            denied_drivers: vec!["v4l2 loopback".into(), "akvcam".into()],
+        };
+
+        assert_eq!(policy.check(Some(&pinned)), Ok(()));
+        assert!(matches!(
+            policy.check(Some(&summary("V4L2 Loopback", "platform:v4l2loopback-000"))),
+            Err(DeviceRejection::VirtualDriver { .. })
+        ));
+        assert!(matches!(
+            policy.check(Some(&summary("uvcvideo", "usb-0000:00:14.0-6"))),
+            Err(DeviceRejection::IdentityMismatch { .. })
+        ));
+        assert_eq!(
+            policy.check(None),
+            Err(DeviceRejection::IdentityUnavailable)
+        );
+        assert_eq!(DeviceTrustPolicy::default().check(None), Ok(()));
+    }
+
+    #[test]
+    fn pins_round_trip_with_private_permissions() {
+        let dir = tempdir().unwrap();
+        let store = DevicePinStore::new(dir.path().join("devices"));
+        assert_eq!(store.load("alice").unwrap(), None);
+
+        let pin = DevicePin::new(&summary("uvcvideo", "usb-0000:00:14.0-5"));
+        let path = store.save("alice", &pin).unwrap();
+        assert_eq!(store.load("alice").unwrap(), Some(pin));
+        let mode = fs::metadata(path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(store.pin_path("../etc/passwd").is_err());
+    }
+}
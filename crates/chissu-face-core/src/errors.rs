@@ -3,6 +3,7 @@ use std::path::PathBuf;
 use std::process::ExitCode;
 
 use image::ImageError;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::secret_service::SecretServiceError;
@@ -109,6 +110,36 @@ pub enum AppError {
 
     #[error("Secret Service embedding key for user {user} invalid: {message}")]
     SecretServiceKeyInvalid { user: String, message: String },
+
+    #[error("failed to access faillock tally {path}: {source}")]
+    Faillock {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("failed to access device pin {path}: {source}")]
+    DevicePin {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("video device {device} is not trusted: {message}")]
+    UntrustedDevice { device: String, message: String },
+
+    #[error("verification cancelled")]
+    Cancelled,
+
+    #[error("audit logging is disabled; set audit_log in the configuration or pass --log")]
+    AuditLogDisabled,
+
+    #[error("failed to access audit log {path}: {source}")]
+    AuditLog {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
 }
 
 impl AppError {
@@ -133,6 +164,11 @@ impl AppError {
             AppError::EncryptedStoreRequiresKey { .. } => ExitCode::from(2),
             AppError::Encryption(_) => ExitCode::from(2),
             AppError::SecretServiceKeyInvalid { .. } => ExitCode::from(2),
+            AppError::Faillock { .. } => ExitCode::from(2),
+            AppError::DevicePin { .. } => ExitCode::from(2),
+            AppError::UntrustedDevice { .. } => ExitCode::from(3),
+            AppError::AuditLog { .. } => ExitCode::from(2),
+            AppError::AuditLogDisabled => ExitCode::from(2),
             _ => ExitCode::from(1),
         }
     }
@@ -140,6 +176,52 @@ impl AppError {
     pub fn human_message(&self) -> String {
         self.to_string()
     }
+
+    /// Classifies errors that mean the camera cannot be used right now, as
+    /// opposed to a capture that went wrong once the device was in hand.
+    pub fn device_fault(&self) -> Option<DeviceFault> {
+        match self {
+            AppError::DeviceOpen { source, .. } | AppError::Io(source) => {
+                DeviceFault::from_io(source)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Why a video device is unavailable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceFault {
+    /// The node is missing, e.g. an external camera on a closed, docked laptop.
+    Absent,
+    /// Another process (typically a video call) holds the stream.
+    Busy,
+    PermissionDenied,
+}
+
+impl DeviceFault {
+    pub fn from_io(err: &io::Error) -> Option<Self> {
+        match err.raw_os_error() {
+            Some(libc::ENOENT | libc::ENODEV | libc::ENXIO) => Some(DeviceFault::Absent),
+            Some(libc::EBUSY) => Some(DeviceFault::Busy),
+            Some(libc::EACCES | libc::EPERM) => Some(DeviceFault::PermissionDenied),
+            Some(_) => None,
+            None => match err.kind() {
+                io::ErrorKind::NotFound => Some(DeviceFault::Absent),
+                io::ErrorKind::PermissionDenied => Some(DeviceFault::PermissionDenied),
+                _ => None,
+            },
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            DeviceFault::Absent => "not present",
+            DeviceFault::Busy => "busy",
+            DeviceFault::PermissionDenied => "not accessible (permission denied)",
+        }
+    }
 }
 
 pub type AppResult<T> = Result<T, AppError>;
@@ -169,3 +251,40 @@ impl From<crate::secret_service::EmbeddingKeyLookupError> for AppError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_error(errno: i32) -> AppError {
+        AppError::DeviceOpen {
+            device: "/dev/video2".into(),
+            source: io::Error::from_raw_os_error(errno),
+        }
+    }
+
+    #[test]
+    fn device_faults_are_classified_from_errno() {
+        assert_eq!(
+            open_error(libc::ENOENT).device_fault(),
+            Some(DeviceFault::Absent)
+        );
+        assert_eq!(
+            open_error(libc::ENODEV).device_fault(),
+            Some(DeviceFault::Absent)
+        );
+        assert_eq!(
+            AppError::Io(io::Error::from_raw_os_error(libc::EBUSY)).device_fault(),
+            Some(DeviceFault::Busy)
+        );
+        assert_eq!(
+            open_error(libc::EACCES).device_fault(),
+            Some(DeviceFault::PermissionDenied)
+        );
+        assert_eq!(open_error(libc::EIO).device_fault(), None);
+        assert_eq!(
+            AppError::FrameProcessing("timeout".into()).device_fault(),
+            None
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Adds embeddings from confident live matches to the user's store.
+//!
+//! Faces drift with haircuts, glasses and age while manual enrolments stay
+//! fixed. After a match that clears the threshold by a margin, the fresh
+//! embedding is appended with [`EnrolledEmbedding::auto_added`] set, and only
+//! the newest `max_entries` auto-added entries are kept. Manual enrolments are
+//! never evicted.
+
+use std::path::Path;
+
+use chrono::{SecondsFormat, Utc};
+use uuid::Uuid;
+
+use crate::errors::{AppError, AppResult};
+use crate::faces::extractor::FaceEmbeddingRecord;
+use crate::faces::metric::SimilarityMetric;
+use crate::faces::store::{EnrolledEmbedding, FaceStore};
+
+/// `source` recorded on auto-added entries.
+pub const ADAPTIVE_SOURCE: &str = "adaptive-update";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveUpdatePolicy {
+    /// How far past the threshold, in the metric's direction, a match must be.
+    pub margin: f64,
+    /// Auto-added entries kept per user; older ones are replaced.
+    pub max_entries: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdaptiveUpdateOutcome {
+    pub added_id: String,
+    pub evicted_ids: Vec<String>,
+    pub auto_entries: usize,
+}
+
+impl AdaptiveUpdatePolicy {
+    /// Whether a match with `score` is confident enough to learn from.
+    pub fn qualifies(&self, metric: SimilarityMetric, score: f64, threshold: f64) -> bool {
+        let required = if metric.lower_is_better() {
+            threshold - self.margin
+        } else {
+            threshold + self.margin
+        };
+        score.is_finite() && metric.passes(score, required)
+    }
+}
+
+/// Appends `face` to the store at `path` and drops the oldest auto-added
+/// entries beyond the policy's limit. The store is re-read and written back
+/// with the same `key`, so the encryption state is preserved.
+pub fn apply_adaptive_update<S: FaceStore>(
+    store: &S,
+    path: &Path,
+    key: Option<&[u8]>,
+    face: &FaceEmbeddingRecord,
+    policy: &AdaptiveUpdatePolicy,
+) -> AppResult<AdaptiveUpdateOutcome> {
+    if policy.max_entries == 0 {
+        return Err(AppError::EmbeddingValidation {
+            path: path.to_path_buf(),
+            message: "adaptive update keeps no entries".into(),
+        });
+    }
+    let mut entries = store.load(path, key)?;
+    if entries.is_empty() {
+        return Err(AppError::EmbeddingValidation {
+            path: path.to_path_buf(),
+            message: "adaptive update needs an existing enrolment".into(),
+        });
+    }
+    if let Some(expected) = entries.first().map(|entry| entry.embedding.len()) {
+        if expected != face.embedding.len() {
+            return Err(AppError::EmbeddingValidation {
+                path: path.to_path_buf(),
+                message: format!(
+                    "embedding length mismatch with existing store (expected {expected}, found {})",
+                    face.embedding.len()
+                ),
+            });
+        }
+    }
+
+    let added_id = Uuid::new_v4().to_string();
+    entries.push(EnrolledEmbedding {
+        id: added_id.clone(),
+        embedding: face.embedding.clone(),
+        bounding_box: face.bounding_box.clone(),
+        source: ADAPTIVE_SOURCE.into(),
+        created_at: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        auto_added: true,
+    });
+
+    // Entries are kept in insertion order, so the first auto-added ones are
+    // the oldest.
+    let auto_entries = entries.iter().filter(|entry| entry.auto_added).count();
+    let mut excess = auto_entries.saturating_sub(policy.max_entries);
+    let mut evicted_ids = Vec::new();
+    entries.retain(|entry| {
+        if excess > 0 && entry.auto_added {
+            excess -= 1;
+            evicted_ids.push(entry.id.clone());
+            return false;
+        }
+        true
+    });
+
+    store.save(path, &entries, key)?;
+    Ok(AdaptiveUpdateOutcome {
+        added_id,
+        evicted_ids,
+        auto_entries: auto_entries.min(policy.max_entries),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+
+    use crate::faces::extractor::BoundingBox;
+
+    #[derive(Default)]
+    struct MemoryStore {
+        entries: RefCell<Vec<EnrolledEmbedding>>,
+        saved_with_key: RefCell<Option<Option<Vec<u8>>>>,
+    }
+
+    impl FaceStore for MemoryStore {
+        fn load(&self, _path: &Path, _key: Option<&[u8]>) -> AppResult<Vec<EnrolledEmbedding>> {
+            Ok(self.entries.borrow().clone())
+        }
+
+        fn save(
+            &self,
+            _path: &Path,
+            embeddings: &[EnrolledEmbedding],
+            key: Option<&[u8]>,
+        ) -> AppResult<()> {
+            *self.entries.borrow_mut() = embeddings.to_vec();
+            *self.saved_with_key.borrow_mut() = Some(key.map(<[u8]>::to_vec));
+            Ok(())
+        }
+
+        fn delete(&self, _path: &Path) -> AppResult<()> {
+            self.entries.borrow_mut().clear();
+            Ok(())
+        }
+    }
+
+    fn bbox() -> BoundingBox {
+        BoundingBox {
+            left: 0,
+            top: 0,
+            right: 10,
+            bottom: 10,
+        }
+    }
+
+    fn manual(id: &str) -> EnrolledEmbedding {
+        EnrolledEmbedding {
+            id: id.into(),
+            embedding: vec![1.0, 0.0],
+            bounding_box: bbox(),
+            source: "manual.json".into(),
+            created_at: "2025-01-01T00:00:00Z".into(),
+            auto_added: false,
+        }
+    }
+
+    fn live_face() -> FaceEmbeddingRecord {
+        FaceEmbeddingRecord {
+            bounding_box: bbox(),
+            embedding: vec![0.9, 0.1],
+            landmarks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn margin_follows_metric_direction() {
+        let policy = AdaptiveUpdatePolicy {
+            margin: 0.05,
+            max_entries: 3,
+        };
+        assert!(policy.qualifies(SimilarityMetric::Cosine, 0.96, 0.9));
+        assert!(!policy.qualifies(SimilarityMetric::Cosine, 0.93, 0.9));
+        assert!(policy.qualifies(SimilarityMetric::Euclidean, 0.5, 0.6));
+        assert!(!policy.qualifies(SimilarityMetric::Euclidean, 0.58, 0.6));
+        assert!(!policy.qualifies(SimilarityMetric::Cosine, f64::NEG_INFINITY, 0.9));
+    }
+
+    #[test]
+    fn rolling_window_replaces_oldest_auto_entries_only() {
+        let store = MemoryStore::default();
+        *store.entries.borrow_mut() = vec![manual("m1"), manual("m2")];
+        let path = PathBuf::from("/tmp/alice.json");
+        let policy = AdaptiveUpdatePolicy {
+            margin: 0.05,
+            max_entries: 2,
+        };
+
+        let first = apply_adaptive_update(&store, &path, Some(b"k"), &live_face(), &policy)
+            .unwrap()
+            .added_id;
+        apply_adaptive_update(&store, &path, Some(b"k"), &live_face(), &policy).unwrap();
+        let outcome =
+            apply_adaptive_update(&store, &path, Some(b"k"), &live_face(), &policy).unwrap();
+
+        assert_eq!(outcome.evicted_ids, vec![first]);
+        assert_eq!(outcome.auto_entries, 2);
+        let entries = store.entries.borrow();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].id, "m1");
+        assert_eq!(entries[1].id, "m2");
+        assert!(entries[2..].iter().all(|entry| entry.auto_added));
+        assert_eq!(entries[3].source, ADAPTIVE_SOURCE);
+        assert_eq!(*store.saved_with_key.borrow(), Some(Some(b"k".to_vec())));
+    }
+
+    #[test]
+    fn update_requires_an_existing_enrolment() {
+        let store = MemoryStore::default();
+        let err = apply_adaptive_update(
+            &store,
+            Path::new("/tmp/bob.json"),
+            None,
+            &live_face(),
+            &AdaptiveUpdatePolicy {
+                margin: 0.0,
+                max_entries: 1,
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, AppError::EmbeddingValidation { .. }));
+        assert!(store.saved_with_key.borrow().is_none());
+    }
+}
@@ -0,0 +1,216 @@
+use image::GrayImage;
+use serde::{Deserialize, Serialize};
+
+use crate::faces::extractor::BoundingBox;
+
+/// Pixels at or below this level count towards [`SpoofScores::dark_fraction`].
+const DARK_LEVEL: u8 = 20;
+/// Pixels at or above this level count towards [`SpoofScores::specular_fraction`].
+const SPECULAR_LEVEL: u8 = 250;
+
+/// How eagerly matching frames are rejected as presentation attacks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AntiSpoofSensitivity {
+    #[default]
+    Off,
+    Low,
+    Medium,
+    High,
+}
+
+/// Why a face region was judged not to be a live face under IR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpoofVerdict {
+    /// LCD and OLED panels emit almost no near-IR, so the face renders dark.
+    ScreenReplay,
+    /// Paper prints lack skin micro-texture or reflect IR in broad glossy patches.
+    FlatPrint,
+}
+
+/// Intensity statistics of the face region of one IR frame.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SpoofScores {
+    /// Mean grey level (0-255).
+    pub mean_intensity: f64,
+    /// Share of pixels at or below the dark level.
+    pub dark_fraction: f64,
+    /// Mean absolute 4-neighbour Laplacian, a proxy for local texture variance.
+    pub texture: f64,
+    /// Share of saturated pixels from specular reflections.
+    pub specular_fraction: f64,
+}
+
+struct Thresholds {
+    max_mean_for_screen: f64,
+    min_dark_fraction_for_screen: f64,
+    min_texture: f64,
+    max_specular_fraction: f64,
+}
+
+impl AntiSpoofSensitivity {
+    fn thresholds(self) -> Option<Thresholds> {
+        let (mean, dark, texture, specular) = match self {
+            AntiSpoofSensitivity::Off => return None,
+            AntiSpoofSensitivity::Low => (25.0, 0.8, 1.5, 0.15),
+            AntiSpoofSensitivity::Medium => (40.0, 0.6, 3.0, 0.08),
+            AntiSpoofSensitivity::High => (55.0, 0.45, 4.5, 0.04),
+        };
+        Some(Thresholds {
+            max_mean_for_screen: mean,
+            min_dark_fraction_for_screen: dark,
+            min_texture: texture,
+            max_specular_fraction: specular,
+        })
+    }
+}
+
+impl SpoofScores {
+    /// Classifies the scores, returning `None` when the region looks live or
+    /// the check is disabled.
+    pub fn verdict(&self, sensitivity: AntiSpoofSensitivity) -> Option<SpoofVerdict> {
+        let limits = sensitivity.thresholds()?;
+        if self.mean_intensity <= limits.max_mean_for_screen
+            || self.dark_fraction >= limits.min_dark_fraction_for_screen
+        {
+            return Some(SpoofVerdict::ScreenReplay);
+        }
+        if self.texture < limits.min_texture
+            || self.specular_fraction > limits.max_specular_fraction
+        {
+            return Some(SpoofVerdict::FlatPrint);
+        }
+        None
+    }
+}
+
+/// Scores the part of `image` inside `bbox`, clamped to the frame. Returns
+/// `None` when the clamped region is smaller than 3x3 pixels.
+pub fn analyze_face_region(image: &GrayImage, bbox: &BoundingBox) -> Option<SpoofScores> {
+    let clamp = |value: i64, max: u32| value.clamp(0, i64::from(max)) as u32;
+    let (left, right) = (
+        clamp(bbox.left, image.width()),
+        clamp(bbox.right, image.width()),
+    );
+    let (top, bottom) = (
+        clamp(bbox.top, image.height()),
+        clamp(bbox.bottom, image.height()),
+    );
+    if right.saturating_sub(left) < 3 || bottom.saturating_sub(top) < 3 {
+        return None;
+    }
+
+    let at = |x: u32, y: u32| f64::from(image.get_pixel(x, y)[0]);
+    let mut sum = 0.0;
+    let mut dark = 0usize;
+    let mut specular = 0usize;
+    for y in top..bottom {
+        for x in left..right {
+            let value = image.get_pixel(x, y)[0];
+            sum += f64::from(value);
+            if value <= DARK_LEVEL {
+                dark += 1;
+            }
+            if value >= SPECULAR_LEVEL {
+                specular += 1;
+            }
+        }
+    }
+
+    let mut laplacian = 0.0;
+    for y in top + 1..bottom - 1 {
+        for x in left + 1..right - 1 {
+            let neighbours = at(x - 1, y) + at(x + 1, y) + at(x, y - 1) + at(x, y + 1);
+            laplacian += (4.0 * at(x, y) - neighbours).abs();
+        }
+    }
+
+    let area = f64::from((right - left) * (bottom - top));
+    let interior = f64::from((right - left - 2) * (bottom - top - 2));
+    Some(SpoofScores {
+        mean_intensity: sum / area,
+        dark_fraction: dark as f64 / area,
+        texture: laplacian / interior,
+        specular_fraction: specular as f64 / area,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    fn region() -> BoundingBox {
+        BoundingBox {
+            left: 4,
+            top: 4,
+            right: 28,
+            bottom: 28,
+        }
+    }
+
+    /// Mid-grey skin with per-pixel noise, as a live face looks under IR.
+    fn textured_face() -> GrayImage {
+        GrayImage::from_fn(32, 32, |x, y| Luma([110 + ((x * 7 + y * 13) % 23) as u8]))
+    }
+
+    #[test]
+    fn live_face_passes_every_sensitivity() {
+        let scores = analyze_face_region(&textured_face(), &region()).unwrap();
+        for sensitivity in [
+            AntiSpoofSensitivity::Low,
+            AntiSpoofSensitivity::Medium,
+            AntiSpoofSensitivity::High,
+        ] {
+            assert_eq!(scores.verdict(sensitivity), None, "{sensitivity:?}");
+        }
+    }
+
+    #[test]
+    fn dark_screen_is_flagged_as_replay() {
+        let screen = GrayImage::from_fn(32, 32, |x, y| Luma([((x + y) % 9) as u8]));
+        let scores = analyze_face_region(&screen, &region()).unwrap();
+        assert!(scores.dark_fraction > 0.9);
+        assert_eq!(
+            scores.verdict(AntiSpoofSensitivity::Low),
+            Some(SpoofVerdict::ScreenReplay)
+        );
+        assert_eq!(scores.verdict(AntiSpoofSensitivity::Off), None);
+    }
+
+    #[test]
+    fn flat_or_glossy_print_is_flagged() {
+        let flat = GrayImage::from_pixel(32, 32, Luma([120]));
+        let scores = analyze_face_region(&flat, &region()).unwrap();
+        assert_eq!(scores.texture, 0.0);
+        assert_eq!(
+            scores.verdict(AntiSpoofSensitivity::Medium),
+            Some(SpoofVerdict::FlatPrint)
+        );
+
+        let mut glossy = textured_face();
+        for y in 4..14 {
+            for x in 4..28 {
+                glossy.put_pixel(x, y, Luma([255]));
+            }
+        }
+        let scores = analyze_face_region(&glossy, &region()).unwrap();
+        assert!(scores.specular_fraction > 0.3);
+        assert_eq!(
+            scores.verdict(AntiSpoofSensitivity::Low),
+            Some(SpoofVerdict::FlatPrint)
+        );
+    }
+
+    #[test]
+    fn degenerate_regions_are_skipped() {
+        let bbox = BoundingBox {
+            left: 30,
+            top: -5,
+            right: 40,
+            bottom: 2,
+        };
+        assert!(analyze_face_region(&textured_face(), &bbox).is_none());
+    }
+}
@@ -1,20 +1,23 @@
-use std::cmp::Ordering;
 use std::path::PathBuf;
 
 use serde::Serialize;
 
 use crate::errors::{AppError, AppResult};
 use crate::faces::extractor::{ensure_valid_faces, load_summary, FaceEmbeddingRecord};
+use crate::faces::metric::SimilarityMetric;
 
 #[derive(Debug, Clone)]
 pub struct FaceComparisonConfig {
     pub input: PathBuf,
     pub compare_targets: Vec<PathBuf>,
+    pub metric: SimilarityMetric,
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct FaceComparisonScore {
     pub target_path: String,
+    pub metric: SimilarityMetric,
+    /// Closest score in the metric's direction (a distance for Euclidean).
     pub best_similarity: f64,
     pub input_face_index: usize,
     pub target_face_index: usize,
@@ -44,20 +47,17 @@ pub fn run_face_comparison(config: &FaceComparisonConfig) -> AppResult<FaceCompa
         }
 
         let (best_similarity, input_index, target_index) =
-            compute_best_similarity(&input_summary.faces, &summary.faces);
+            compute_best_similarity(&input_summary.faces, &summary.faces, config.metric);
         scores.push(FaceComparisonScore {
             target_path: target.display().to_string(),
+            metric: config.metric,
             best_similarity,
             input_face_index: input_index,
             target_face_index: target_index,
         });
     }
 
-    scores.sort_by(|a, b| {
-        b.best_similarity
-            .partial_cmp(&a.best_similarity)
-            .unwrap_or(Ordering::Equal)
-    });
+    scores.sort_by(|a, b| config.metric.rank(a.best_similarity, b.best_similarity));
 
     let mut logs = Vec::new();
     logs.push(format!(
@@ -65,11 +65,12 @@ pub fn run_face_comparison(config: &FaceComparisonConfig) -> AppResult<FaceCompa
         input_summary.faces.len(),
         config.input.display()
     ));
-    logs.push("Similarity metric: cosine".to_string());
+    logs.push(format!("Similarity metric: {}", config.metric.name()));
     for score in &scores {
         logs.push(format!(
-            "Target {} => cosine similarity {:.4} (input face #{}, target face #{})",
+            "Target {} => {} {:.4} (input face #{}, target face #{})",
             score.target_path,
+            config.metric.name(),
             score.best_similarity,
             score.input_face_index,
             score.target_face_index
@@ -82,14 +83,15 @@ pub fn run_face_comparison(config: &FaceComparisonConfig) -> AppResult<FaceCompa
 pub fn compute_best_similarity(
     input_faces: &[FaceEmbeddingRecord],
     target_faces: &[FaceEmbeddingRecord],
+    metric: SimilarityMetric,
 ) -> (f64, usize, usize) {
-    let mut best_similarity = f64::NEG_INFINITY;
+    let mut best_similarity = metric.worst();
     let mut best_pair = (0, 0);
 
     for (i, input_face) in input_faces.iter().enumerate() {
         for (j, target_face) in target_faces.iter().enumerate() {
-            let similarity = cosine_similarity(&input_face.embedding, &target_face.embedding);
-            if similarity > best_similarity {
+            let similarity = metric.score(&input_face.embedding, &target_face.embedding);
+            if metric.is_better(similarity, best_similarity) {
                 best_similarity = similarity;
                 best_pair = (i, j);
             }
@@ -139,6 +141,7 @@ mod tests {
         let config = FaceComparisonConfig {
             input: input_path.clone(),
             compare_targets: vec![target_b.clone(), target_a.clone()],
+            metric: SimilarityMetric::Cosine,
         };
 
         let outcome = run_face_comparison(&config).unwrap();
@@ -149,6 +152,38 @@ mod tests {
         assert!(outcome.scores[1].best_similarity.abs() < 1e-6);
     }
 
+    #[test]
+    fn compare_ranks_euclidean_distances_ascending() {
+        let tmp = TempDir::new().unwrap();
+        let input_path = tmp.path().join("input.json");
+        let near = tmp.path().join("near.json");
+        let far = tmp.path().join("far.json");
+        write_summary(
+            &input_path,
+            &summary_with_embeddings("input", vec![vec![1.0, 0.0]]),
+        );
+        write_summary(
+            &near,
+            &summary_with_embeddings("near", vec![vec![0.8, 0.0]]),
+        );
+        write_summary(&far, &summary_with_embeddings("far", vec![vec![0.0, 1.0]]));
+
+        let config = FaceComparisonConfig {
+            input: input_path,
+            compare_targets: vec![far, near],
+            metric: SimilarityMetric::Euclidean,
+        };
+
+        let outcome = run_face_comparison(&config).unwrap();
+        assert!(outcome.scores[0].target_path.ends_with("near.json"));
+        assert!((outcome.scores[0].best_similarity - 0.2).abs() < 1e-9);
+        assert!((outcome.scores[1].best_similarity - 2f64.sqrt()).abs() < 1e-9);
+        assert!(outcome
+            .logs
+            .iter()
+            .any(|line| line.contains("euclidean 0.2000")));
+    }
+
     #[test]
     fn compare_errors_when_target_missing() {
         let tmp = TempDir::new().unwrap();
@@ -161,6 +196,7 @@ mod tests {
         let config = FaceComparisonConfig {
             input: input_path,
             compare_targets: vec![missing_target.clone()],
+            metric: SimilarityMetric::Cosine,
         };
 
         let err = run_face_comparison(&config).unwrap_err();
@@ -175,6 +211,8 @@ mod tests {
             success: true,
             image_path: format!("{source}.png"),
             output_path: format!("{source}.json"),
+            image_width: 0,
+            image_height: 0,
             num_faces: embeddings.len(),
             faces: embeddings
                 .into_iter()
@@ -186,6 +224,7 @@ mod tests {
                         bottom: 1,
                     },
                     embedding,
+                    landmarks: Vec::new(),
                 })
                 .collect(),
             landmark_model: "landmark.dat".into(),
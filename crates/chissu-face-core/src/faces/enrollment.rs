@@ -7,6 +7,7 @@ use uuid::Uuid;
 
 use crate::errors::{AppError, AppResult};
 use crate::faces::extractor::{ensure_valid_faces, load_summary};
+use crate::faces::selection::FaceSelectionPolicy;
 use crate::faces::store::{
     EnrolledEmbedding, EnvStoreDirResolver, FaceStore, FilesystemFaceStore, StoreDirResolver,
 };
@@ -20,6 +21,7 @@ pub struct FaceEnrollmentConfig {
     pub user: String,
     pub embedding: PathBuf,
     pub store_dir: Option<PathBuf>,
+    pub selection: FaceSelectionPolicy,
 }
 
 #[derive(Debug, Clone)]
@@ -108,11 +110,27 @@ where
 
     let summary = load_summary(&config.embedding)
         .map_err(|err| map_to_embedding_validation(&config.embedding, err))?;
-    let embedding_len = ensure_valid_faces(&summary.faces, &config.embedding)
+    let frame_size = (summary.image_width > 0 && summary.image_height > 0)
+        .then_some((summary.image_width, summary.image_height));
+    let selection = config.selection.select(summary.faces, frame_size);
+    for rejection in &selection.rejected {
+        logs.push(format!(
+            "Face selection policy skipped a face: {}",
+            rejection.describe()
+        ));
+    }
+    if selection.faces.is_empty() && !selection.rejected.is_empty() {
+        return Err(AppError::EmbeddingValidation {
+            path: config.embedding.clone(),
+            message: "no face satisfies the face selection policy".into(),
+        });
+    }
+    let faces = selection.faces;
+    let embedding_len = ensure_valid_faces(&faces, &config.embedding)
         .map_err(|err| map_to_embedding_validation(&config.embedding, err))?;
     logs.push(format!(
         "Validated {} embedding(s) with length {}",
-        summary.faces.len(),
+        faces.len(),
         embedding_len
     ));
 
@@ -138,8 +156,8 @@ where
         }
     }
 
-    let mut added = Vec::with_capacity(summary.faces.len());
-    for face in &summary.faces {
+    let mut added = Vec::with_capacity(faces.len());
+    for face in &faces {
         let id = Uuid::new_v4().to_string();
         let created_at = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
         let record = EnrolledEmbedding {
@@ -148,6 +166,7 @@ where
             bounding_box: face.bounding_box.clone(),
             source: config.embedding.display().to_string(),
             created_at: created_at.clone(),
+            auto_added: false,
         };
         existing.push(record);
         added.push(EnrollmentRecord {
@@ -347,6 +366,7 @@ mod tests {
             user: "alice".into(),
             embedding: embedding_path.clone(),
             store_dir: Some(tmp.path().to_path_buf()),
+            selection: FaceSelectionPolicy::default(),
         };
         let store = InMemoryStore::default();
         let keys = StubKeyProvider::new();
@@ -357,6 +377,35 @@ mod tests {
         assert_eq!(keys.saved_keys.borrow().len(), 1);
     }
 
+    #[test]
+    fn enroll_rejects_payload_failing_face_selection() {
+        let tmp = TempDir::new().unwrap();
+        let embedding_path = tmp.path().join("faces.json");
+        let summary =
+            summary_with_embeddings("input", vec![vec![1.0, 0.0, 0.5], vec![0.1, 0.2, 0.3]]);
+        std::fs::write(
+            &embedding_path,
+            serde_json::to_string_pretty(&summary).unwrap(),
+        )
+        .unwrap();
+
+        let config = FaceEnrollmentConfig {
+            user: "alice".into(),
+            embedding: embedding_path,
+            store_dir: Some(tmp.path().to_path_buf()),
+            selection: FaceSelectionPolicy {
+                mode: crate::faces::selection::FaceSelectionMode::RejectMultiple,
+                ..FaceSelectionPolicy::default()
+            },
+        };
+        let store = InMemoryStore::default();
+        let keys = StubKeyProvider::new();
+        let resolver = FixedStoreResolver(tmp.path().to_path_buf());
+        let err = run_face_enrollment_with(&config, &store, &keys, &resolver).unwrap_err();
+        assert!(matches!(err, AppError::EmbeddingValidation { .. }));
+        assert!(store.saved.borrow().is_empty());
+    }
+
     #[test]
     fn removal_requires_existing_embedding_when_not_removing_all() {
         let tmp = TempDir::new().unwrap();
@@ -439,6 +488,8 @@ mod tests {
             success: true,
             image_path: format!("{source}.png"),
             output_path: format!("{source}.json"),
+            image_width: 0,
+            image_height: 0,
             num_faces: embeddings.len(),
             faces: embeddings
                 .into_iter()
@@ -450,6 +501,7 @@ mod tests {
                         bottom: 1,
                     },
                     embedding,
+                    landmarks: Vec::new(),
                 })
                 .collect(),
             landmark_model: "landmark.dat".into(),
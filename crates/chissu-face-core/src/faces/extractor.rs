@@ -13,6 +13,7 @@ use serde::{Deserialize, Serialize};
 use tracing::debug;
 
 use crate::errors::{AppError, AppResult};
+use crate::faces::selection::FaceSelectionPolicy;
 
 const LANDMARK_ENV: &str = "DLIB_LANDMARK_MODEL";
 const ENCODER_ENV: &str = "DLIB_ENCODER_MODEL";
@@ -24,6 +25,7 @@ pub struct FaceExtractionConfig {
     pub encoder_model: Option<PathBuf>,
     pub output: Option<PathBuf>,
     pub jitters: u32,
+    pub selection: FaceSelectionPolicy,
 }
 
 #[derive(Debug, Clone)]
@@ -83,11 +85,20 @@ pub struct BoundingBox {
     pub bottom: i64,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LandmarkPoint {
+    pub x: i64,
+    pub y: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct FaceEmbeddingRecord {
     pub bounding_box: BoundingBox,
     #[serde(rename = "embedding")]
     pub embedding: Vec<f64>,
+    /// dlib 68-point shape for the face; only kept in memory for liveness checks.
+    #[serde(skip)]
+    pub landmarks: Vec<LandmarkPoint>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,6 +106,11 @@ pub struct FaceExtractionSummary {
     pub success: bool,
     pub image_path: String,
     pub output_path: String,
+    /// Source image size; 0 in files written before it was recorded.
+    #[serde(default)]
+    pub image_width: u32,
+    #[serde(default)]
+    pub image_height: u32,
     pub num_faces: usize,
     pub faces: Vec<FaceEmbeddingRecord>,
     pub landmark_model: String,
@@ -143,6 +159,16 @@ pub fn run_face_extraction_with_backend<B: FaceEmbeddingBackend>(
 
     let faces = backend.extract(&rgb, config.jitters)?;
     logs.push(format!("Detected {} face(s)", faces.len()));
+    let selection = config
+        .selection
+        .select(faces, Some((rgb.width(), rgb.height())));
+    for rejection in &selection.rejected {
+        logs.push(format!(
+            "Face selection policy skipped a face: {}",
+            rejection.describe()
+        ));
+    }
+    let faces = selection.faces;
     if let Some(first) = faces.first() {
         logs.push(format!(
             "Embedding vector length: {}",
@@ -159,6 +185,8 @@ pub fn run_face_extraction_with_backend<B: FaceEmbeddingBackend>(
         success: true,
         image_path: image_path.display().to_string(),
         output_path: output_path.display().to_string(),
+        image_width: rgb.width(),
+        image_height: rgb.height(),
         num_faces: faces.len(),
         faces,
         landmark_model: models.landmark.display().to_string(),
@@ -300,7 +328,7 @@ impl FaceEmbeddingBackend for DlibBackend {
             .get_face_encodings(&matrix, &landmarks, num_jitters);
 
         let mut records = Vec::with_capacity(locations.len());
-        for (rect, encoding) in locations.iter().zip(encodings.iter()) {
+        for ((rect, encoding), shape) in locations.iter().zip(encodings.iter()).zip(&landmarks) {
             let embedding = encoding.as_ref().to_vec();
             let bounding_box = BoundingBox {
                 left: rect.left,
@@ -311,6 +339,13 @@ impl FaceEmbeddingBackend for DlibBackend {
             records.push(FaceEmbeddingRecord {
                 bounding_box,
                 embedding,
+                landmarks: shape
+                    .iter()
+                    .map(|point| LandmarkPoint {
+                        x: point.x(),
+                        y: point.y(),
+                    })
+                    .collect(),
             });
         }
 
@@ -353,6 +388,8 @@ mod tests {
             success: true,
             image_path: "input.png".into(),
             output_path: output_path.display().to_string(),
+            image_width: 1,
+            image_height: 1,
             num_faces: 1,
             faces: vec![FaceEmbeddingRecord {
                 bounding_box: BoundingBox {
@@ -362,6 +399,7 @@ mod tests {
                     bottom: 1,
                 },
                 embedding: vec![0.1, 0.2],
+                landmarks: Vec::new(),
             }],
             landmark_model: "landmark.dat".into(),
             encoder_model: "encoder.dat".into(),
@@ -387,6 +425,7 @@ mod tests {
             encoder_model: Some(PathBuf::from("encoder.dat")),
             output: None,
             jitters: 1,
+            selection: FaceSelectionPolicy::default(),
         };
 
         let backend = StubBackend { faces: vec![] };
@@ -411,6 +450,7 @@ mod tests {
                 bottom: 1,
             },
             embedding: vec![0.0, 0.0],
+            landmarks: Vec::new(),
         };
 
         let err = ensure_valid_faces(&[record], path).unwrap_err();
@@ -0,0 +1,236 @@
+//! Combines per-frame similarity scores into one accept decision.
+//!
+//! By default a single frame above the threshold is enough. Stricter
+//! services can ask for k matching frames out of the last n, or for the mean
+//! score over the last n frames, and can require the matched face to stay in
+//! place between frames so the window cannot be filled by different people.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::faces::extractor::BoundingBox;
+use crate::faces::metric::SimilarityMetric;
+
+pub const DEFAULT_FUSION_WINDOW: u32 = 5;
+pub const DEFAULT_FUSION_REQUIRED: u32 = 3;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FusionMode {
+    /// Accept the first frame at or above the threshold.
+    #[default]
+    Single,
+    /// Accept once `required` of the last `window` frames reach the threshold.
+    KOfN,
+    /// Accept once the last `window` frames all scored and their mean reaches
+    /// the threshold.
+    Average,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FusionPolicy {
+    pub mode: FusionMode,
+    /// Frames in the sliding window (n).
+    pub window: u32,
+    /// Matching frames needed inside the window in `KOfN` mode (k).
+    pub required: u32,
+    /// Largest movement of the matched face between scored frames, relative
+    /// to the larger side of its previous bounding box. A bigger jump clears
+    /// the window. `None` disables the check.
+    pub max_face_shift: Option<f64>,
+}
+
+impl Default for FusionPolicy {
+    fn default() -> Self {
+        Self {
+            mode: FusionMode::Single,
+            window: DEFAULT_FUSION_WINDOW,
+            required: DEFAULT_FUSION_REQUIRED,
+            max_face_shift: None,
+        }
+    }
+}
+
+/// Sliding window of per-frame scores for one verification attempt.
+#[derive(Debug, Clone)]
+pub struct FusionTracker {
+    policy: FusionPolicy,
+    metric: SimilarityMetric,
+    threshold: f64,
+    scores: VecDeque<Option<f64>>,
+    last_box: Option<BoundingBox>,
+}
+
+impl FusionTracker {
+    pub fn new(policy: FusionPolicy, threshold: f64) -> Self {
+        Self {
+            policy,
+            metric: SimilarityMetric::Cosine,
+            threshold,
+            scores: VecDeque::new(),
+            last_box: None,
+        }
+    }
+
+    /// Interprets scores and the threshold in `metric`'s direction.
+    pub fn with_metric(mut self, metric: SimilarityMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    /// Compares the matched face with the previous one and clears the window
+    /// when it moved further than `max_face_shift`. Returns the shift when the
+    /// window was cleared.
+    pub fn check_continuity(&mut self, bbox: &BoundingBox) -> Option<f64> {
+        let previous = self.last_box.replace(bbox.clone())?;
+        let max = self.policy.max_face_shift?;
+        let shift = face_shift(&previous, bbox);
+        if shift > max {
+            self.scores.clear();
+            return Some(shift);
+        }
+        None
+    }
+
+    /// Records one frame; `None` marks a frame without a usable face. Returns
+    /// whether the window now satisfies the policy.
+    pub fn observe(&mut self, score: Option<f64>) -> bool {
+        self.scores.push_back(score);
+        while self.scores.len() > self.window_len() {
+            self.scores.pop_front();
+        }
+        self.is_satisfied()
+    }
+
+    pub fn is_satisfied(&self) -> bool {
+        match self.policy.mode {
+            FusionMode::Single => {
+                matches!(self.scores.back(), Some(Some(score)) if self.metric.passes(*score, self.threshold))
+            }
+            FusionMode::KOfN => self.matching_frames() >= self.required(),
+            FusionMode::Average => {
+                self.scores.len() == self.window_len()
+                    && self.scores.iter().all(Option::is_some)
+                    && self
+                        .mean()
+                        .is_some_and(|mean| self.metric.passes(mean, self.threshold))
+            }
+        }
+    }
+
+    /// Frames in the window that reach the threshold.
+    pub fn matching_frames(&self) -> usize {
+        self.scores
+            .iter()
+            .flatten()
+            .filter(|score| self.metric.passes(**score, self.threshold))
+            .count()
+    }
+
+    /// Mean of the scored frames in the window.
+    pub fn mean(&self) -> Option<f64> {
+        let scored: Vec<f64> = self.scores.iter().flatten().copied().collect();
+        (!scored.is_empty()).then(|| scored.iter().sum::<f64>() / scored.len() as f64)
+    }
+
+    pub fn window_len(&self) -> usize {
+        match self.policy.mode {
+            FusionMode::Single => 1,
+            FusionMode::KOfN | FusionMode::Average => self.policy.window.max(1) as usize,
+        }
+    }
+
+    fn required(&self) -> usize {
+        (self.policy.required.max(1) as usize).min(self.window_len())
+    }
+}
+
+/// Distance between the box centres relative to the larger side of `previous`.
+fn face_shift(previous: &BoundingBox, current: &BoundingBox) -> f64 {
+    let size = (previous.right - previous.left)
+        .max(previous.bottom - previous.top)
+        .max(1) as f64;
+    let dx = (current.left + current.right - previous.left - previous.right) as f64 / 2.0;
+    let dy = (current.top + current.bottom - previous.top - previous.bottom) as f64 / 2.0;
+    (dx * dx + dy * dy).sqrt() / size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(mode: FusionMode) -> FusionPolicy {
+        FusionPolicy {
+            mode,
+            window: 5,
+            required: 3,
+            max_face_shift: None,
+        }
+    }
+
+    fn bbox(left: i64) -> BoundingBox {
+        BoundingBox {
+            left,
+            top: 0,
+            right: left + 100,
+            bottom: 100,
+        }
+    }
+
+    #[test]
+    fn single_mode_accepts_first_matching_frame() {
+        let mut tracker = FusionTracker::new(FusionPolicy::default(), 0.9);
+        assert!(!tracker.observe(Some(0.5)));
+        assert!(!tracker.observe(None));
+        assert!(tracker.observe(Some(0.95)));
+        assert!(!tracker.observe(Some(0.8)));
+    }
+
+    #[test]
+    fn k_of_n_counts_matches_in_sliding_window() {
+        let mut tracker = FusionTracker::new(policy(FusionMode::KOfN), 0.9);
+        let accepted: Vec<bool> = [0.95, 0.95, 0.2, 0.2, 0.2, 0.95, 0.95, 0.95]
+            .into_iter()
+            .map(|score| tracker.observe(Some(score)))
+            .collect();
+        // The first two matches slide out of the window before the third
+        // arrives; frames 6 to 8 then satisfy it on their own.
+        assert_eq!(
+            accepted,
+            vec![false, false, false, false, false, false, false, true]
+        );
+        assert_eq!(tracker.matching_frames(), 3);
+    }
+
+    #[test]
+    fn average_mode_needs_a_full_window_of_scored_frames() {
+        let mut tracker = FusionTracker::new(policy(FusionMode::Average), 0.9);
+        for score in [0.95, 0.88, 0.92, 0.91] {
+            assert!(!tracker.observe(Some(score)));
+        }
+        assert!(tracker.observe(Some(0.9)));
+        assert!(!tracker.observe(None));
+    }
+
+    #[test]
+    fn face_jump_clears_the_window() {
+        let mut tracker = FusionTracker::new(
+            FusionPolicy {
+                max_face_shift: Some(0.5),
+                ..policy(FusionMode::KOfN)
+            },
+            0.9,
+        );
+        assert_eq!(tracker.check_continuity(&bbox(0)), None);
+        tracker.observe(Some(0.95));
+        assert_eq!(tracker.check_continuity(&bbox(20)), None);
+        tracker.observe(Some(0.95));
+
+        let shift = tracker.check_continuity(&bbox(300)).unwrap();
+        assert!((shift - 2.8).abs() < 1e-9);
+        assert!(!tracker.observe(Some(0.95)));
+        assert_eq!(tracker.matching_frames(), 1);
+    }
+}
@@ -0,0 +1,163 @@
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::faces::extractor::LandmarkPoint;
+
+/// Indices of the six contour points of each eye in dlib's 68-point shape.
+const LEFT_EYE: Range<usize> = 36..42;
+const RIGHT_EYE: Range<usize> = 42..48;
+
+/// Eye-aspect ratio at or above which both eyes are treated as open.
+pub const EAR_OPEN: f64 = 0.25;
+/// Eye-aspect ratio at or below which both eyes are treated as closed.
+pub const EAR_CLOSED: f64 = 0.2;
+/// Spread of observed eye-aspect ratios that counts as natural eye motion.
+pub const EAR_MOTION_DELTA: f64 = 0.05;
+
+/// How much eye activity a recognised face must show before a match counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LivenessMode {
+    /// Accept the first matching frame.
+    #[default]
+    Off,
+    /// Require an open → closed → open transition of the eyes.
+    Blink,
+    /// Accept a blink or enough variation in eye openness across frames.
+    EyeMotion,
+}
+
+/// Mean eye-aspect ratio of both eyes, or `None` when the shape lacks eye points.
+///
+/// EAR = (|p2 - p6| + |p3 - p5|) / (2 |p1 - p4|), as described by
+/// Soukupová and Čech; it drops towards zero while the eye is closed.
+pub fn eye_aspect_ratio(landmarks: &[LandmarkPoint]) -> Option<f64> {
+    let left = single_eye_ratio(landmarks.get(LEFT_EYE)?)?;
+    let right = single_eye_ratio(landmarks.get(RIGHT_EYE)?)?;
+    Some((left + right) / 2.0)
+}
+
+fn single_eye_ratio(eye: &[LandmarkPoint]) -> Option<f64> {
+    let width = distance(eye[0], eye[3]);
+    if width <= f64::EPSILON {
+        return None;
+    }
+    Some((distance(eye[1], eye[5]) + distance(eye[2], eye[4])) / (2.0 * width))
+}
+
+fn distance(a: LandmarkPoint, b: LandmarkPoint) -> f64 {
+    let dx = (a.x - b.x) as f64;
+    let dy = (a.y - b.y) as f64;
+    (dx * dx + dy * dy).sqrt()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlinkPhase {
+    AwaitOpen,
+    AwaitClose,
+    AwaitReopen,
+    Done,
+}
+
+/// Accumulates eye-aspect ratios from consecutive frames of the matched face.
+#[derive(Debug, Clone)]
+pub struct LivenessTracker {
+    mode: LivenessMode,
+    phase: BlinkPhase,
+    min_ear: f64,
+    max_ear: f64,
+}
+
+impl LivenessTracker {
+    pub fn new(mode: LivenessMode) -> Self {
+        Self {
+            mode,
+            phase: BlinkPhase::AwaitOpen,
+            min_ear: f64::INFINITY,
+            max_ear: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Records one sample and reports whether liveness is now confirmed.
+    pub fn observe(&mut self, ear: f64) -> bool {
+        self.min_ear = self.min_ear.min(ear);
+        self.max_ear = self.max_ear.max(ear);
+        self.phase = match self.phase {
+            BlinkPhase::AwaitOpen if ear >= EAR_OPEN => BlinkPhase::AwaitClose,
+            BlinkPhase::AwaitClose if ear <= EAR_CLOSED => BlinkPhase::AwaitReopen,
+            BlinkPhase::AwaitReopen if ear >= EAR_OPEN => BlinkPhase::Done,
+            phase => phase,
+        };
+        self.is_satisfied()
+    }
+
+    pub fn is_satisfied(&self) -> bool {
+        match self.mode {
+            LivenessMode::Off => true,
+            LivenessMode::Blink => self.phase == BlinkPhase::Done,
+            LivenessMode::EyeMotion => {
+                self.phase == BlinkPhase::Done || self.max_ear - self.min_ear >= EAR_MOTION_DELTA
+            }
+        }
+    }
+}
+
+/// Builds a 68-point shape whose eyes have the given aspect ratio.
+#[cfg(test)]
+pub(crate) fn shape_with_ear(ear: f64) -> Vec<LandmarkPoint> {
+    let mut points = vec![LandmarkPoint { x: 0, y: 0 }; 68];
+    let half_height = (ear * 100.0 / 2.0).round() as i64;
+    for (start, offset) in [(36, 0), (42, 200)] {
+        let eye = [
+            (offset, 0),
+            (offset + 33, -half_height),
+            (offset + 66, -half_height),
+            (offset + 100, 0),
+            (offset + 66, half_height),
+            (offset + 33, half_height),
+        ];
+        for (idx, (x, y)) in eye.into_iter().enumerate() {
+            points[start + idx] = LandmarkPoint { x, y };
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eye_aspect_ratio_tracks_eye_openness() {
+        let open = eye_aspect_ratio(&shape_with_ear(0.3)).unwrap();
+        let closed = eye_aspect_ratio(&shape_with_ear(0.1)).unwrap();
+        assert!((open - 0.3).abs() < 0.01);
+        assert!((closed - 0.1).abs() < 0.01);
+        assert!(eye_aspect_ratio(&shape_with_ear(0.3)[..40]).is_none());
+    }
+
+    #[test]
+    fn blink_mode_requires_open_closed_open_sequence() {
+        let mut tracker = LivenessTracker::new(LivenessMode::Blink);
+        assert!(!tracker.observe(0.3));
+        assert!(!tracker.observe(0.29));
+        assert!(!tracker.observe(0.12));
+        assert!(tracker.observe(0.31));
+
+        let mut static_photo = LivenessTracker::new(LivenessMode::Blink);
+        for _ in 0..10 {
+            assert!(!static_photo.observe(0.3));
+        }
+    }
+
+    #[test]
+    fn eye_motion_mode_accepts_partial_movement() {
+        let mut tracker = LivenessTracker::new(LivenessMode::EyeMotion);
+        assert!(!tracker.observe(0.30));
+        assert!(!tracker.observe(0.31));
+        assert!(tracker.observe(0.24));
+
+        assert!(LivenessTracker::new(LivenessMode::Off).is_satisfied());
+    }
+}
@@ -0,0 +1,143 @@
+//! Scores two embeddings against each other.
+//!
+//! dlib's ResNet encoder was trained for Euclidean distance with a 0.6
+//! cutoff, while chissu-pam historically used cosine similarity. Scores keep
+//! the metric's own direction: cosine grows with likeness, distances shrink,
+//! so thresholds and "best" comparisons go through [`SimilarityMetric`].
+
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::faces::comparer::cosine_similarity;
+
+pub const DEFAULT_COSINE_THRESHOLD: f64 = 0.9;
+pub const DEFAULT_EUCLIDEAN_THRESHOLD: f64 = 0.6;
+/// Roughly the normalized distance at a cosine similarity of 0.9.
+pub const DEFAULT_NORMALIZED_EUCLIDEAN_THRESHOLD: f64 = 0.45;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SimilarityMetric {
+    /// Cosine similarity in -1.0..=1.0; higher is closer.
+    #[default]
+    Cosine,
+    /// Euclidean distance of the raw embeddings; lower is closer.
+    Euclidean,
+    /// Euclidean distance after scaling both embeddings to unit length,
+    /// in 0.0..=2.0; lower is closer.
+    NormalizedEuclidean,
+}
+
+impl SimilarityMetric {
+    pub fn name(self) -> &'static str {
+        match self {
+            SimilarityMetric::Cosine => "cosine",
+            SimilarityMetric::Euclidean => "euclidean",
+            SimilarityMetric::NormalizedEuclidean => "normalized_euclidean",
+        }
+    }
+
+    pub fn default_threshold(self) -> f64 {
+        match self {
+            SimilarityMetric::Cosine => DEFAULT_COSINE_THRESHOLD,
+            SimilarityMetric::Euclidean => DEFAULT_EUCLIDEAN_THRESHOLD,
+            SimilarityMetric::NormalizedEuclidean => DEFAULT_NORMALIZED_EUCLIDEAN_THRESHOLD,
+        }
+    }
+
+    /// Whether a smaller score means a closer match.
+    pub fn lower_is_better(self) -> bool {
+        !matches!(self, SimilarityMetric::Cosine)
+    }
+
+    pub fn score(self, lhs: &[f64], rhs: &[f64]) -> f64 {
+        match self {
+            SimilarityMetric::Cosine => cosine_similarity(lhs, rhs),
+            SimilarityMetric::Euclidean => euclidean_distance(lhs, rhs),
+            SimilarityMetric::NormalizedEuclidean => euclidean_distance(&unit(lhs), &unit(rhs)),
+        }
+    }
+
+    /// Score that every real comparison beats.
+    pub fn worst(self) -> f64 {
+        if self.lower_is_better() {
+            f64::INFINITY
+        } else {
+            f64::NEG_INFINITY
+        }
+    }
+
+    /// Whether `score` is a closer match than `other`.
+    pub fn is_better(self, score: f64, other: f64) -> bool {
+        if self.lower_is_better() {
+            score < other
+        } else {
+            score > other
+        }
+    }
+
+    /// Whether `score` reaches `threshold` in the metric's direction.
+    pub fn passes(self, score: f64, threshold: f64) -> bool {
+        if self.lower_is_better() {
+            score <= threshold
+        } else {
+            score >= threshold
+        }
+    }
+
+    /// Orders scores from closest to furthest match.
+    pub fn rank(self, lhs: f64, rhs: f64) -> Ordering {
+        let ordering = lhs.partial_cmp(&rhs).unwrap_or(Ordering::Equal);
+        if self.lower_is_better() {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    }
+}
+
+pub fn euclidean_distance(lhs: &[f64], rhs: &[f64]) -> f64 {
+    lhs.iter()
+        .zip(rhs.iter())
+        .map(|(l, r)| (l - r) * (l - r))
+        .sum::<f64>()
+        .sqrt()
+}
+
+fn unit(values: &[f64]) -> Vec<f64> {
+    let norm = values.iter().map(|v| v * v).sum::<f64>().sqrt();
+    if norm <= f64::EPSILON {
+        return values.to_vec();
+    }
+    values.iter().map(|v| v / norm).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_score_in_their_own_direction() {
+        let a = [3.0, 0.0];
+        let b = [0.0, 4.0];
+
+        assert!(SimilarityMetric::Cosine.score(&a, &b).abs() < 1e-9);
+        assert!((SimilarityMetric::Euclidean.score(&a, &b) - 5.0).abs() < 1e-9);
+        assert!((SimilarityMetric::NormalizedEuclidean.score(&a, &b) - 2f64.sqrt()).abs() < 1e-9);
+        assert!(SimilarityMetric::NormalizedEuclidean.score(&a, &[6.0, 0.0]) < 1e-9);
+    }
+
+    #[test]
+    fn thresholds_follow_the_metric_direction() {
+        assert!(SimilarityMetric::Cosine.passes(0.95, 0.9));
+        assert!(!SimilarityMetric::Cosine.passes(0.5, 0.9));
+        assert!(SimilarityMetric::Euclidean.passes(0.4, 0.6));
+        assert!(!SimilarityMetric::Euclidean.passes(0.7, 0.6));
+
+        assert!(SimilarityMetric::Euclidean.is_better(0.3, SimilarityMetric::Euclidean.worst()));
+        assert!(SimilarityMetric::Cosine.is_better(-0.5, SimilarityMetric::Cosine.worst()));
+        assert_eq!(SimilarityMetric::Euclidean.rank(0.3, 0.5), Ordering::Less);
+        assert_eq!(SimilarityMetric::Cosine.rank(0.3, 0.5), Ordering::Greater);
+    }
+}
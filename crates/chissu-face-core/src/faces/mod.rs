@@ -1,7 +1,20 @@
+pub mod adaptive;
+pub mod antispoof;
 pub mod comparer;
 pub mod enrollment;
 pub mod extractor;
+pub mod fusion;
+pub mod liveness;
+pub mod metric;
+pub mod selection;
 pub mod store;
+pub mod verifier;
+
+pub use adaptive::{
+    apply_adaptive_update, AdaptiveUpdateOutcome, AdaptiveUpdatePolicy, ADAPTIVE_SOURCE,
+};
+
+pub use antispoof::{analyze_face_region, AntiSpoofSensitivity, SpoofScores, SpoofVerdict};
 
 pub use comparer::{
     cosine_similarity, run_face_comparison, FaceComparisonConfig, FaceComparisonOutcome,
@@ -19,10 +32,25 @@ pub use extractor::{
     ensure_valid_faces, run_face_extraction, run_face_extraction_with_backend, BoundingBox,
     DlibBackend, EnvModelPathResolver, FaceEmbeddingBackend, FaceEmbeddingRecord,
     FaceExtractionConfig, FaceExtractionOutcome, FaceExtractionSummary, FaceModelPaths,
-    ModelPathResolver,
+    LandmarkPoint, ModelPathResolver,
+};
+
+pub use fusion::{FusionMode, FusionPolicy, FusionTracker};
+
+pub use liveness::{eye_aspect_ratio, LivenessMode, LivenessTracker};
+
+pub use metric::{euclidean_distance, SimilarityMetric};
+
+pub use selection::{
+    face_size, FaceRejection, FaceSelection, FaceSelectionMode, FaceSelectionPolicy,
 };
 
 pub use store::{
     load_enrolled_embeddings, user_store_path, EnrolledEmbedding, EnvStoreDirResolver, FaceStore,
     FilesystemFaceStore, StoreDirResolver,
 };
+
+pub use verifier::{
+    best_similarity_against, gray_to_rgb, verify_live, FrameSource, VerifyEvent, VerifyFailure,
+    VerifyObserver, VerifyOutcome, VerifySettings,
+};
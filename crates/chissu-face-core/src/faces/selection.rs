@@ -0,0 +1,256 @@
+//! Decides which detected faces may be matched or enrolled.
+//!
+//! dlib returns every face in the frame, so without a policy a colleague in
+//! the background can be enrolled or matched. [`FaceSelectionPolicy`] first
+//! drops faces that are too small or too far from the frame centre, then
+//! applies its [`FaceSelectionMode`] to what is left.
+
+use serde::{Deserialize, Serialize};
+
+use crate::faces::extractor::{BoundingBox, FaceEmbeddingRecord};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaceSelectionMode {
+    /// Keep every face that passes the size and centring checks.
+    #[default]
+    All,
+    /// Keep only the face with the largest bounding box.
+    Largest,
+    /// Reject the frame when more than one face remains.
+    RejectMultiple,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct FaceSelectionPolicy {
+    pub mode: FaceSelectionMode,
+    /// Minimum length of the shorter bounding-box side in pixels; 0 disables.
+    #[serde(default)]
+    pub min_face_size: u32,
+    /// Largest allowed distance of the face centre from the frame centre, as
+    /// a fraction of half the frame size on either axis (0 = exact centre,
+    /// 1 = frame edge). `None` disables the check.
+    #[serde(default)]
+    pub max_center_offset: Option<f64>,
+}
+
+/// Why a face, or the whole frame, was set aside.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum FaceRejection {
+    TooSmall {
+        size: u32,
+        min: u32,
+    },
+    OffCenter {
+        offset: f64,
+        max: f64,
+    },
+    /// `Largest` mode dropped a smaller face.
+    NotLargest,
+    /// `RejectMultiple` mode found this many acceptable faces.
+    MultipleFaces {
+        count: usize,
+    },
+}
+
+impl FaceRejection {
+    pub fn describe(&self) -> String {
+        match self {
+            FaceRejection::TooSmall { size, min } => {
+                format!("face {size}px is smaller than {min}px")
+            }
+            FaceRejection::OffCenter { offset, max } => {
+                format!("face offset {offset:.2} from the centre exceeds {max:.2}")
+            }
+            FaceRejection::NotLargest => "a larger face is in the frame".into(),
+            FaceRejection::MultipleFaces { count } => {
+                format!("{count} faces in the frame")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FaceSelection {
+    pub faces: Vec<FaceEmbeddingRecord>,
+    pub rejected: Vec<FaceRejection>,
+}
+
+impl FaceSelectionPolicy {
+    pub fn is_permissive(&self) -> bool {
+        self.mode == FaceSelectionMode::All
+            && self.min_face_size == 0
+            && self.max_center_offset.is_none()
+    }
+
+    /// Applies the policy to the faces found in one frame. `frame_size` is
+    /// `(width, height)`; without it the centring check is skipped.
+    pub fn select(
+        &self,
+        faces: Vec<FaceEmbeddingRecord>,
+        frame_size: Option<(u32, u32)>,
+    ) -> FaceSelection {
+        let mut selection = FaceSelection::default();
+        for face in faces {
+            match self.check(&face.bounding_box, frame_size) {
+                Some(rejection) => selection.rejected.push(rejection),
+                None => selection.faces.push(face),
+            }
+        }
+
+        match self.mode {
+            FaceSelectionMode::All => {}
+            FaceSelectionMode::Largest => {
+                if let Some(largest) = selection
+                    .faces
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|(_, face)| area(&face.bounding_box))
+                    .map(|(idx, _)| idx)
+                {
+                    let kept = selection.faces.swap_remove(largest);
+                    selection
+                        .rejected
+                        .extend(selection.faces.drain(..).map(|_| FaceRejection::NotLargest));
+                    selection.faces.push(kept);
+                }
+            }
+            FaceSelectionMode::RejectMultiple => {
+                let count = selection.faces.len();
+                if count > 1 {
+                    selection.faces.clear();
+                    selection
+                        .rejected
+                        .push(FaceRejection::MultipleFaces { count });
+                }
+            }
+        }
+        selection
+    }
+
+    fn check(&self, bbox: &BoundingBox, frame_size: Option<(u32, u32)>) -> Option<FaceRejection> {
+        let size = face_size(bbox);
+        if size < self.min_face_size {
+            return Some(FaceRejection::TooSmall {
+                size,
+                min: self.min_face_size,
+            });
+        }
+        if let (Some(max), Some((width, height))) = (self.max_center_offset, frame_size) {
+            let offset = center_offset(bbox, width, height);
+            if offset > max {
+                return Some(FaceRejection::OffCenter { offset, max });
+            }
+        }
+        None
+    }
+}
+
+/// The shorter side of a face's bounding box in pixels, as compared with
+/// `min_face_size`.
+pub fn face_size(bbox: &BoundingBox) -> u32 {
+    (bbox.right - bbox.left)
+        .min(bbox.bottom - bbox.top)
+        .clamp(0, u32::MAX as i64) as u32
+}
+
+fn area(bbox: &BoundingBox) -> i64 {
+    (bbox.right - bbox.left).max(0) * (bbox.bottom - bbox.top).max(0)
+}
+
+/// Larger of the horizontal and vertical distances between the face centre
+/// and the frame centre, each relative to half the frame size.
+fn center_offset(bbox: &BoundingBox, width: u32, height: u32) -> f64 {
+    let half_width = f64::from(width.max(1)) / 2.0;
+    let half_height = f64::from(height.max(1)) / 2.0;
+    let center_x = (bbox.left + bbox.right) as f64 / 2.0;
+    let center_y = (bbox.top + bbox.bottom) as f64 / 2.0;
+    ((center_x - half_width).abs() / half_width).max((center_y - half_height).abs() / half_height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn face(left: i64, top: i64, side: i64) -> FaceEmbeddingRecord {
+        FaceEmbeddingRecord {
+            bounding_box: BoundingBox {
+                left,
+                top,
+                right: left + side,
+                bottom: top + side,
+            },
+            embedding: vec![side as f64],
+            landmarks: Vec::new(),
+        }
+    }
+
+    const FRAME: Option<(u32, u32)> = Some((640, 480));
+
+    #[test]
+    fn default_policy_keeps_every_face() {
+        let policy = FaceSelectionPolicy::default();
+        assert!(policy.is_permissive());
+        let selection = policy.select(vec![face(0, 0, 40), face(300, 200, 120)], FRAME);
+        assert_eq!(selection.faces.len(), 2);
+        assert!(selection.rejected.is_empty());
+    }
+
+    #[test]
+    fn largest_mode_keeps_the_biggest_face() {
+        let policy = FaceSelectionPolicy {
+            mode: FaceSelectionMode::Largest,
+            ..FaceSelectionPolicy::default()
+        };
+        let selection = policy.select(
+            vec![face(0, 0, 40), face(260, 180, 120), face(500, 10, 60)],
+            FRAME,
+        );
+        assert_eq!(selection.faces.len(), 1);
+        assert_eq!(selection.faces[0].embedding, vec![120.0]);
+        assert_eq!(selection.rejected, vec![FaceRejection::NotLargest; 2]);
+    }
+
+    #[test]
+    fn reject_multiple_counts_faces_left_after_size_filter() {
+        let policy = FaceSelectionPolicy {
+            mode: FaceSelectionMode::RejectMultiple,
+            min_face_size: 80,
+            max_center_offset: None,
+        };
+        let selection = policy.select(vec![face(0, 0, 40), face(260, 180, 120)], FRAME);
+        assert_eq!(selection.faces.len(), 1);
+        assert_eq!(
+            selection.rejected,
+            vec![FaceRejection::TooSmall { size: 40, min: 80 }]
+        );
+
+        let selection = policy.select(vec![face(0, 0, 100), face(260, 180, 120)], FRAME);
+        assert!(selection.faces.is_empty());
+        assert_eq!(
+            selection.rejected,
+            vec![FaceRejection::MultipleFaces { count: 2 }]
+        );
+    }
+
+    #[test]
+    fn centring_check_needs_the_frame_size() {
+        let policy = FaceSelectionPolicy {
+            max_center_offset: Some(0.5),
+            ..FaceSelectionPolicy::default()
+        };
+        // Centred at (320, 240) and at (60, 60).
+        let centred = face(260, 180, 120);
+        let corner = face(20, 20, 80);
+
+        let selection = policy.select(vec![centred.clone(), corner.clone()], FRAME);
+        assert_eq!(selection.faces, vec![centred]);
+        assert!(matches!(
+            selection.rejected[..],
+            [FaceRejection::OffCenter { offset, max }] if offset > 0.8 && max == 0.5
+        ));
+
+        assert_eq!(policy.select(vec![corner], None).faces.len(), 1);
+    }
+}
@@ -26,6 +26,9 @@ pub struct EnrolledEmbedding {
     pub bounding_box: crate::faces::extractor::BoundingBox,
     pub source: String,
     pub created_at: String,
+    /// Set on entries added by an adaptive update after a confident match.
+    #[serde(default)]
+    pub auto_added: bool,
 }
 
 pub trait FaceStore {
@@ -323,6 +326,7 @@ mod tests {
             },
             source: source.into(),
             created_at: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            auto_added: false,
         }
     }
 }
@@ -0,0 +1,1100 @@
+use std::time::{Duration, Instant};
+
+use image::{GrayImage, Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+
+use crate::capture::integrity::{StreamAnomaly, StreamMonitor};
+use crate::capture::ir::{check_ir_format, IrFrameStats, IR_PROBE_FRAMES};
+use crate::capture::{CaptureSession, DeviceSummary, FrameMetadata, SkippedDevice};
+use crate::errors::{AppError, AppResult};
+use crate::faces::antispoof::{
+    analyze_face_region, AntiSpoofSensitivity, SpoofScores, SpoofVerdict,
+};
+use crate::faces::extractor::{BoundingBox, FaceEmbeddingBackend, FaceEmbeddingRecord};
+use crate::faces::fusion::{FusionPolicy, FusionTracker};
+use crate::faces::liveness::{eye_aspect_ratio, LivenessMode, LivenessTracker};
+use crate::faces::metric::SimilarityMetric;
+use crate::faces::selection::{face_size, FaceRejection, FaceSelectionPolicy};
+
+/// Supplies live frames to [`verify_live`].
+pub trait FrameSource {
+    fn next_frame(&mut self) -> AppResult<GrayImage>;
+    fn discard_until(&mut self, until: Instant, cancelled: &dyn Fn() -> bool) -> AppResult<usize>;
+
+    /// Like `next_frame`, but returns `None` once `deadline` passes and fails
+    /// with [`AppError::Cancelled`] once `cancelled` returns `true`. Sources
+    /// that can block should check both while they wait.
+    fn next_frame_until(
+        &mut self,
+        _deadline: Instant,
+        cancelled: &dyn Fn() -> bool,
+    ) -> AppResult<Option<GrayImage>> {
+        if cancelled() {
+            return Err(AppError::Cancelled);
+        }
+        self.next_frame().map(Some)
+    }
+
+    /// What the underlying device reports about itself, when known.
+    fn device(&self) -> Option<&DeviceSummary> {
+        None
+    }
+
+    /// Negotiated pixel format FourCC, when known.
+    fn pixel_format(&self) -> Option<&str> {
+        None
+    }
+
+    /// Buffer metadata of the frame last returned by `next_frame`.
+    fn frame_metadata(&self) -> Option<FrameMetadata> {
+        None
+    }
+}
+
+impl FrameSource for CaptureSession {
+    fn next_frame(&mut self) -> AppResult<GrayImage> {
+        CaptureSession::next_frame(self)
+    }
+
+    fn discard_until(&mut self, until: Instant, cancelled: &dyn Fn() -> bool) -> AppResult<usize> {
+        CaptureSession::discard_until(self, until, cancelled)
+    }
+
+    fn next_frame_until(
+        &mut self,
+        deadline: Instant,
+        cancelled: &dyn Fn() -> bool,
+    ) -> AppResult<Option<GrayImage>> {
+        CaptureSession::next_frame_until(self, deadline, cancelled)
+    }
+
+    fn device(&self) -> Option<&DeviceSummary> {
+        Some(CaptureSession::device(self))
+    }
+
+    fn pixel_format(&self) -> Option<&str> {
+        Some(&self.format().pixel_format)
+    }
+
+    fn frame_metadata(&self) -> Option<FrameMetadata> {
+        self.last_metadata()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VerifySettings {
+    /// Interpreted in the direction of `metric`: a minimum similarity for
+    /// cosine, a maximum distance for the Euclidean metrics.
+    pub similarity_threshold: f64,
+    pub metric: SimilarityMetric,
+    pub frame_interval: Duration,
+    pub jitters: u32,
+    pub liveness: LivenessMode,
+    pub anti_spoof: AntiSpoofSensitivity,
+    pub selection: FaceSelectionPolicy,
+    pub fusion: FusionPolicy,
+    /// Refuse streams that are not monochrome IR or show no IR lighting.
+    pub require_ir: bool,
+    /// Refuse streams whose frames repeat exactly or whose buffer metadata
+    /// does not advance like a live sensor's.
+    pub stream_integrity: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyFailure {
+    NoFaceDetected,
+    ThresholdNotReached,
+    /// A face matched but never showed the eye activity `liveness` requires.
+    LivenessNotConfirmed,
+    /// Every matching frame was rejected by the IR presentation-attack check.
+    SpoofSuspected,
+    /// The capture device failed the pinned identity or virtual-driver check,
+    /// so no frame was evaluated.
+    UntrustedDevice,
+    /// `require_ir` is set and the stream is not a lit monochrome IR stream.
+    NotInfrared,
+    /// `stream_integrity` is set and the stream looked frozen or replayed.
+    FrozenStream,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifyOutcome {
+    pub success: bool,
+    pub best_similarity: f64,
+    pub frames_captured: usize,
+    pub failure: Option<VerifyFailure>,
+    /// Best similarity of every captured frame; `None` where no usable face
+    /// was found.
+    pub frame_scores: Vec<Option<f64>>,
+    /// The face that was accepted; `None` unless `success`.
+    pub matched_face: Option<FaceEmbeddingRecord>,
+}
+
+impl VerifyOutcome {
+    /// A failure decided before any frame was captured.
+    pub fn rejected(failure: VerifyFailure, metric: SimilarityMetric) -> Self {
+        Self {
+            success: false,
+            best_similarity: metric.worst(),
+            frames_captured: 0,
+            failure: Some(failure),
+            frame_scores: Vec::new(),
+            matched_face: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum VerifyEvent {
+    /// The capture device that was opened, and the earlier fallback entries
+    /// passed over. Sent by whoever opened the source, not by [`verify_live`].
+    DeviceSelected {
+        device: String,
+        #[serde(default)]
+        skipped: Vec<SkippedDevice>,
+    },
+    FrameCaptured {
+        frame: usize,
+        width: u32,
+        height: u32,
+    },
+    /// `require_ir` refused the negotiated pixel format before any frame.
+    IrFormatRejected {
+        pixel_format: Option<String>,
+    },
+    /// The stream integrity check refused the frame; verification stops.
+    StreamRejected {
+        frame: usize,
+        anomaly: StreamAnomaly,
+    },
+    /// IR lighting statistics of a frame sampled before the stream was
+    /// confirmed as IR; frames that are not lit are not matched.
+    IrScored {
+        frame: usize,
+        stats: IrFrameStats,
+        illuminated: bool,
+    },
+    NoFaceDetected {
+        frame: usize,
+    },
+    /// Brightness of a frame handed to face detection and the size of the
+    /// largest face found in it, before the selection policy; for live
+    /// feedback such as "move closer".
+    FrameAssessed {
+        frame: usize,
+        mean_intensity: f64,
+        face_size: Option<u32>,
+    },
+    /// Faces the selection policy set aside; the rest of the frame is still used.
+    FacesRejected {
+        frame: usize,
+        rejected: Vec<FaceRejection>,
+    },
+    /// Anti-spoof scores of the matched face; `verdict` is set when rejected.
+    SpoofScored {
+        frame: usize,
+        scores: SpoofScores,
+        verdict: Option<SpoofVerdict>,
+    },
+    /// The matched face moved further than `max_face_shift` since the last
+    /// scored frame, so the fusion window started over.
+    FaceShifted {
+        frame: usize,
+        shift: f64,
+    },
+    /// The frame reached the threshold, but the fusion policy needs more.
+    FusionPending {
+        frame: usize,
+        similarity: f64,
+        matching_frames: usize,
+        window: usize,
+    },
+    /// The frame matched, but liveness is not yet confirmed.
+    LivenessPending {
+        frame: usize,
+        similarity: f64,
+        eye_aspect_ratio: Option<f64>,
+    },
+    Matched {
+        frame: usize,
+        similarity: f64,
+    },
+}
+
+pub trait VerifyObserver {
+    fn on_event(&mut self, event: &VerifyEvent);
+
+    /// Polled before each frame; `true` stops [`verify_live`] with
+    /// [`AppError::Cancelled`].
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+impl<F: FnMut(&VerifyEvent)> VerifyObserver for F {
+    fn on_event(&mut self, event: &VerifyEvent) {
+        self(event)
+    }
+}
+
+/// Samples frames until one matches an enrolled embedding or `deadline` passes.
+///
+/// What counts as a match is decided by the [`FusionPolicy`]: a single frame
+/// above the threshold by default, or k of the last n frames, or the mean
+/// over the last n frames. While a policy is waiting for more matching
+/// frames, they are sampled back to back like during liveness checks.
+///
+/// With a [`LivenessMode`] other than `Off`, every scored frame feeds the
+/// eye-aspect ratio of its best face into a [`LivenessTracker`], whether or
+/// not that frame matched, and success waits until it is satisfied. Once a face has matched, frames are
+/// sampled back to back so short blinks are not skipped by `frame_interval`.
+///
+/// With an [`AntiSpoofSensitivity`] other than `Off`, a matching face whose
+/// region looks like a screen replay or flat print is ignored for that frame.
+///
+/// With `require_ir`, a stream whose pixel format is not monochrome IR fails
+/// with [`VerifyFailure::NotInfrared`] before any frame is read, and so does
+/// one where none of the first [`IR_PROBE_FRAMES`] frames shows IR lighting.
+///
+/// With `stream_integrity`, every frame goes through a [`StreamMonitor`], and
+/// the first anomaly fails with [`VerifyFailure::FrozenStream`].
+pub fn verify_live(
+    frames: &mut dyn FrameSource,
+    backend: &dyn FaceEmbeddingBackend,
+    enrolled: &[Vec<f64>],
+    settings: &VerifySettings,
+    deadline: Instant,
+    observer: &mut dyn VerifyObserver,
+) -> AppResult<VerifyOutcome> {
+    let mut frames_captured = 0usize;
+    let metric = settings.metric;
+    let mut best_similarity = metric.worst();
+    let mut detected_any_face = false;
+    let mut recognized = false;
+    let mut spoof_rejected = false;
+    let mut liveness = LivenessTracker::new(settings.liveness);
+    let mut fusion =
+        FusionTracker::new(settings.fusion, settings.similarity_threshold).with_metric(metric);
+    let mut frame_scores = Vec::new();
+    let mut ir_confirmed = !settings.require_ir;
+    let mut monitor = StreamMonitor::new();
+    if settings.require_ir && check_ir_format(frames.pixel_format()).is_err() {
+        observer.on_event(&VerifyEvent::IrFormatRejected {
+            pixel_format: frames.pixel_format().map(str::to_string),
+        });
+        return Ok(VerifyOutcome::rejected(VerifyFailure::NotInfrared, metric));
+    }
+
+    while Instant::now() < deadline {
+        let Some(image) = frames.next_frame_until(deadline, &|| observer.is_cancelled())? else {
+            break;
+        };
+        frames_captured += 1;
+        observer.on_event(&VerifyEvent::FrameCaptured {
+            frame: frames_captured,
+            width: image.width(),
+            height: image.height(),
+        });
+
+        if settings.stream_integrity {
+            if let Err(anomaly) = monitor.observe(&image, frames.frame_metadata()) {
+                observer.on_event(&VerifyEvent::StreamRejected {
+                    frame: frames_captured,
+                    anomaly,
+                });
+                frame_scores.push(None);
+                return Ok(VerifyOutcome {
+                    frames_captured,
+                    frame_scores,
+                    ..VerifyOutcome::rejected(VerifyFailure::FrozenStream, metric)
+                });
+            }
+        }
+
+        if !ir_confirmed {
+            let stats = IrFrameStats::from_image(&image);
+            ir_confirmed = stats.is_illuminated();
+            observer.on_event(&VerifyEvent::IrScored {
+                frame: frames_captured,
+                stats,
+                illuminated: ir_confirmed,
+            });
+            if !ir_confirmed {
+                frame_scores.push(None);
+                if frames_captured >= IR_PROBE_FRAMES {
+                    return Ok(VerifyOutcome {
+                        frames_captured,
+                        frame_scores,
+                        ..VerifyOutcome::rejected(VerifyFailure::NotInfrared, metric)
+                    });
+                }
+                continue;
+            }
+        }
+
+        let faces = backend.extract(&gray_to_rgb(&image), settings.jitters)?;
+        let detected = faces.len();
+        observer.on_event(&VerifyEvent::FrameAssessed {
+            frame: frames_captured,
+            mean_intensity: mean_intensity(&image),
+            face_size: faces.iter().map(|face| face_size(&face.bounding_box)).max(),
+        });
+        let selection = settings
+            .selection
+            .select(faces, Some((image.width(), image.height())));
+        if !selection.rejected.is_empty() {
+            observer.on_event(&VerifyEvent::FacesRejected {
+                frame: frames_captured,
+                rejected: selection.rejected,
+            });
+        }
+        let faces = selection.faces;
+        let mut frame_best: Option<(f64, usize)> = None;
+        if detected == 0 {
+            observer.on_event(&VerifyEvent::NoFaceDetected {
+                frame: frames_captured,
+            });
+        } else if !faces.is_empty() {
+            detected_any_face = true;
+            for (idx, face) in faces.iter().enumerate() {
+                let similarity = best_similarity_against(&face.embedding, enrolled, metric)?;
+                if metric.is_better(similarity, best_similarity) {
+                    best_similarity = similarity;
+                }
+                if !matches!(frame_best, Some((best, _)) if !metric.is_better(similarity, best)) {
+                    frame_best = Some((similarity, idx));
+                }
+            }
+        }
+        frame_scores.push(frame_best.map(|(similarity, _)| similarity));
+
+        let mut fused_score = None;
+        let mut ear = None;
+        if let Some((similarity, idx)) = frame_best {
+            if let Some(shift) = fusion.check_continuity(&faces[idx].bounding_box) {
+                liveness = LivenessTracker::new(settings.liveness);
+                observer.on_event(&VerifyEvent::FaceShifted {
+                    frame: frames_captured,
+                    shift,
+                });
+            }
+            // Closed eyes often score below the threshold, so a blink is
+            // only seen if non-matching frames of the same face count too.
+            ear = eye_aspect_ratio(&faces[idx].landmarks);
+            if let Some(ear) = ear {
+                liveness.observe(ear);
+            }
+            let spoofed = metric.passes(similarity, settings.similarity_threshold)
+                && spoof_verdict(
+                    &image,
+                    &faces[idx].bounding_box,
+                    settings.anti_spoof,
+                    frames_captured,
+                    observer,
+                )
+                .is_some();
+            spoof_rejected |= spoofed;
+            if !spoofed {
+                fused_score = Some(similarity);
+            }
+        }
+        let accepted = fusion.observe(fused_score);
+        let mut fusion_pending = false;
+
+        if let (Some(similarity), Some((_, idx))) = (fused_score, frame_best) {
+            if accepted {
+                recognized = true;
+                if liveness.is_satisfied() {
+                    observer.on_event(&VerifyEvent::Matched {
+                        frame: frames_captured,
+                        similarity,
+                    });
+                    return Ok(VerifyOutcome {
+                        success: true,
+                        best_similarity: similarity,
+                        frames_captured,
+                        failure: None,
+                        frame_scores,
+                        matched_face: Some(faces[idx].clone()),
+                    });
+                }
+                observer.on_event(&VerifyEvent::LivenessPending {
+                    frame: frames_captured,
+                    similarity,
+                    eye_aspect_ratio: ear,
+                });
+            } else if metric.passes(similarity, settings.similarity_threshold) {
+                fusion_pending = true;
+                observer.on_event(&VerifyEvent::FusionPending {
+                    frame: frames_captured,
+                    similarity,
+                    matching_frames: fusion.matching_frames(),
+                    window: fusion.window_len(),
+                });
+            }
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        if settings.frame_interval > Duration::ZERO && !recognized && !fusion_pending {
+            // Keep dequeuing while we wait so the next sample is a live frame.
+            frames.discard_until((now + settings.frame_interval).min(deadline), &|| {
+                observer.is_cancelled()
+            })?;
+        }
+    }
+
+    let failure = if spoof_rejected {
+        VerifyFailure::SpoofSuspected
+    } else if recognized {
+        VerifyFailure::LivenessNotConfirmed
+    } else if detected_any_face {
+        VerifyFailure::ThresholdNotReached
+    } else {
+        VerifyFailure::NoFaceDetected
+    };
+    Ok(VerifyOutcome {
+        success: false,
+        best_similarity,
+        frames_captured,
+        failure: Some(failure),
+        frame_scores,
+        matched_face: None,
+    })
+}
+
+/// Scores the face region and reports the verdict through `observer`.
+fn spoof_verdict(
+    image: &GrayImage,
+    bbox: &BoundingBox,
+    sensitivity: AntiSpoofSensitivity,
+    frame: usize,
+    observer: &mut dyn VerifyObserver,
+) -> Option<SpoofVerdict> {
+    if sensitivity == AntiSpoofSensitivity::Off {
+        return None;
+    }
+    let scores = analyze_face_region(image, bbox)?;
+    let verdict = scores.verdict(sensitivity);
+    observer.on_event(&VerifyEvent::SpoofScored {
+        frame,
+        scores,
+        verdict,
+    });
+    verdict
+}
+
+/// Closest score, in `metric`'s direction, between `candidate` and any
+/// enrolled embedding.
+pub fn best_similarity_against(
+    candidate: &[f64],
+    enrolled: &[Vec<f64>],
+    metric: SimilarityMetric,
+) -> AppResult<f64> {
+    let mut best = metric.worst();
+    for reference in enrolled {
+        if reference.len() != candidate.len() {
+            return Err(AppError::FrameProcessing(format!(
+                "embedding length mismatch: enrolled {} vs captured {}",
+                reference.len(),
+                candidate.len()
+            )));
+        }
+        let similarity = metric.score(candidate, reference);
+        if metric.is_better(similarity, best) {
+            best = similarity;
+        }
+    }
+    Ok(best)
+}
+
+fn mean_intensity(image: &GrayImage) -> f64 {
+    let pixels = image.as_raw();
+    if pixels.is_empty() {
+        return 0.0;
+    }
+    pixels.iter().map(|&value| u64::from(value)).sum::<u64>() as f64 / pixels.len() as f64
+}
+
+pub fn gray_to_rgb(image: &GrayImage) -> RgbImage {
+    let mut rgb = RgbImage::new(image.width(), image.height());
+    for (x, y, pixel) in rgb.enumerate_pixels_mut() {
+        let v = image.get_pixel(x, y)[0];
+        *pixel = Rgb([v, v, v]);
+    }
+    rgb
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::integrity::MAX_IDENTICAL_FRAMES;
+    use crate::faces::extractor::LandmarkPoint;
+    use crate::faces::liveness::shape_with_ear;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+
+    struct ScriptedFrames {
+        remaining: usize,
+    }
+
+    impl FrameSource for ScriptedFrames {
+        fn next_frame(&mut self) -> AppResult<GrayImage> {
+            if self.remaining == 0 {
+                return Err(AppError::FrameProcessing("no frame".into()));
+            }
+            self.remaining -= 1;
+            Ok(GrayImage::new(32, 32))
+        }
+
+        fn discard_until(
+            &mut self,
+            until: Instant,
+            _cancelled: &dyn Fn() -> bool,
+        ) -> AppResult<usize> {
+            std::thread::sleep(until.saturating_duration_since(Instant::now()));
+            Ok(0)
+        }
+    }
+
+    /// Returns one scripted detection result per call.
+    struct ScriptedBackend {
+        results: RefCell<VecDeque<Vec<FaceEmbeddingRecord>>>,
+    }
+
+    impl ScriptedBackend {
+        fn new(results: Vec<Vec<Vec<f64>>>) -> Self {
+            Self::with_faces(
+                results
+                    .into_iter()
+                    .map(|faces| {
+                        faces
+                            .into_iter()
+                            .map(|embedding| face(embedding, Vec::new()))
+                            .collect()
+                    })
+                    .collect(),
+            )
+        }
+
+        fn with_faces(results: Vec<Vec<FaceEmbeddingRecord>>) -> Self {
+            Self {
+                results: RefCell::new(results.into()),
+            }
+        }
+    }
+
+    impl FaceEmbeddingBackend for ScriptedBackend {
+        fn extract(&self, _image: &RgbImage, _jitters: u32) -> AppResult<Vec<FaceEmbeddingRecord>> {
+            Ok(self.results.borrow_mut().pop_front().unwrap_or_default())
+        }
+    }
+
+    fn face(embedding: Vec<f64>, landmarks: Vec<LandmarkPoint>) -> FaceEmbeddingRecord {
+        FaceEmbeddingRecord {
+            bounding_box: BoundingBox {
+                left: 0,
+                top: 0,
+                right: 32,
+                bottom: 32,
+            },
+            embedding,
+            landmarks,
+        }
+    }
+
+    fn settings(threshold: f64) -> VerifySettings {
+        VerifySettings {
+            similarity_threshold: threshold,
+            metric: SimilarityMetric::Cosine,
+            frame_interval: Duration::ZERO,
+            jitters: 1,
+            liveness: LivenessMode::Off,
+            anti_spoof: AntiSpoofSensitivity::Off,
+            selection: FaceSelectionPolicy::default(),
+            fusion: FusionPolicy::default(),
+            require_ir: false,
+            stream_integrity: true,
+        }
+    }
+
+    #[test]
+    fn verify_live_succeeds_on_first_matching_frame() {
+        let mut frames = ScriptedFrames { remaining: 5 };
+        let backend =
+            ScriptedBackend::new(vec![vec![], vec![vec![0.0, 1.0]], vec![vec![1.0, 0.0]]]);
+        let mut events = Vec::new();
+        let mut observer = |event: &VerifyEvent| events.push(event.clone());
+
+        let outcome = verify_live(
+            &mut frames,
+            &backend,
+            &[vec![1.0, 0.0]],
+            &settings(0.9),
+            Instant::now() + Duration::from_secs(5),
+            &mut observer,
+        )
+        .unwrap();
+
+        assert!(outcome.success);
+        assert_eq!(outcome.frames_captured, 3);
+        assert!((outcome.best_similarity - 1.0).abs() < 1e-9);
+        assert_eq!(
+            outcome.matched_face.map(|face| face.embedding),
+            Some(vec![1.0, 0.0])
+        );
+        assert_eq!(frames.remaining, 2);
+        assert!(events.contains(&VerifyEvent::NoFaceDetected { frame: 1 }));
+        assert!(events.contains(&VerifyEvent::FrameAssessed {
+            frame: 1,
+            mean_intensity: 0.0,
+            face_size: None,
+        }));
+        assert!(events.contains(&VerifyEvent::FrameAssessed {
+            frame: 2,
+            mean_intensity: 0.0,
+            face_size: Some(32),
+        }));
+        assert!(matches!(
+            events.last(),
+            Some(VerifyEvent::Matched { frame: 3, .. })
+        ));
+    }
+
+    /// Cancels after a fixed number of events.
+    struct CancelAfter {
+        events: usize,
+        limit: usize,
+    }
+
+    impl VerifyObserver for CancelAfter {
+        fn on_event(&mut self, _event: &VerifyEvent) {
+            self.events += 1;
+        }
+
+        fn is_cancelled(&self) -> bool {
+            self.events >= self.limit
+        }
+    }
+
+    #[test]
+    fn verify_live_stops_when_observer_cancels() {
+        let mut frames = ScriptedFrames { remaining: 10 };
+        let backend = ScriptedBackend::new(Vec::new());
+        let mut observer = CancelAfter {
+            events: 0,
+            limit: 6,
+        };
+
+        let err = verify_live(
+            &mut frames,
+            &backend,
+            &[vec![1.0, 0.0]],
+            &settings(0.9),
+            Instant::now() + Duration::from_secs(5),
+            &mut observer,
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, AppError::Cancelled));
+        // Three events (captured, assessed, no face) per frame.
+        assert_eq!(frames.remaining, 8);
+    }
+
+    #[test]
+    fn verify_live_waits_for_k_of_n_matching_frames() {
+        let mut frames = ScriptedFrames { remaining: 10 };
+        let backend = ScriptedBackend::new(vec![
+            vec![vec![1.0, 0.0]],
+            vec![],
+            vec![vec![0.0, 1.0]],
+            vec![vec![1.0, 0.0]],
+            vec![vec![1.0, 0.0]],
+        ]);
+        let mut fused = settings(0.9);
+        fused.fusion = FusionPolicy {
+            mode: crate::faces::fusion::FusionMode::KOfN,
+            window: 5,
+            required: 3,
+            max_face_shift: None,
+        };
+        let mut pending = Vec::new();
+        let mut observer = |event: &VerifyEvent| {
+            if let VerifyEvent::FusionPending {
+                matching_frames, ..
+            } = event
+            {
+                pending.push(*matching_frames);
+            }
+        };
+
+        let outcome = verify_live(
+            &mut frames,
+            &backend,
+            &[vec![1.0, 0.0]],
+            &fused,
+            Instant::now() + Duration::from_secs(5),
+            &mut observer,
+        )
+        .unwrap();
+
+        assert!(outcome.success);
+        assert_eq!(outcome.frames_captured, 5);
+        assert_eq!(pending, vec![1, 2]);
+        assert_eq!(outcome.frame_scores.len(), 5);
+        assert_eq!(outcome.frame_scores[1], None);
+        assert!(outcome.frame_scores[2].is_some_and(|score| score.abs() < 1e-9));
+    }
+
+    #[test]
+    fn verify_live_reports_failure_reasons_at_deadline() {
+        let mut frames = ScriptedFrames { remaining: 1 };
+        let backend = ScriptedBackend::new(vec![vec![vec![0.0, 1.0]]]);
+        let mut paced = settings(0.9);
+        paced.frame_interval = Duration::from_secs(1);
+
+        let outcome = verify_live(
+            &mut frames,
+            &backend,
+            &[vec![1.0, 0.0]],
+            &paced,
+            Instant::now() + Duration::from_millis(20),
+            &mut |_: &VerifyEvent| {},
+        )
+        .unwrap();
+        assert!(!outcome.success);
+        assert_eq!(outcome.frames_captured, 1);
+        assert_eq!(outcome.failure, Some(VerifyFailure::ThresholdNotReached));
+        assert!(outcome.best_similarity.abs() < 1e-9);
+
+        let outcome = verify_live(
+            &mut frames,
+            &backend,
+            &[vec![1.0, 0.0]],
+            &paced,
+            Instant::now(),
+            &mut |_: &VerifyEvent| {},
+        )
+        .unwrap();
+        assert_eq!(outcome.frames_captured, 0);
+        assert_eq!(outcome.failure, Some(VerifyFailure::NoFaceDetected));
+    }
+
+    #[test]
+    fn verify_live_matches_below_euclidean_distance_threshold() {
+        let mut frames = ScriptedFrames { remaining: 5 };
+        let backend = ScriptedBackend::new(vec![vec![vec![0.0, 1.0]], vec![vec![0.9, 0.1]]]);
+        let mut euclidean = settings(0.6);
+        euclidean.metric = SimilarityMetric::Euclidean;
+
+        let outcome = verify_live(
+            &mut frames,
+            &backend,
+            &[vec![1.0, 0.0]],
+            &euclidean,
+            Instant::now() + Duration::from_secs(5),
+            &mut |_: &VerifyEvent| {},
+        )
+        .unwrap();
+
+        assert!(outcome.success);
+        assert_eq!(outcome.frames_captured, 2);
+        assert!((outcome.best_similarity - 0.02f64.sqrt()).abs() < 1e-9);
+        assert!(outcome.frame_scores[0].is_some_and(|distance| distance > 1.4));
+    }
+
+    #[test]
+    fn verify_live_rejects_mismatched_embedding_lengths() {
+        let mut frames = ScriptedFrames { remaining: 1 };
+        let backend = ScriptedBackend::new(vec![vec![vec![1.0, 0.0, 0.0]]]);
+        let err = verify_live(
+            &mut frames,
+            &backend,
+            &[vec![1.0, 0.0]],
+            &settings(0.5),
+            Instant::now() + Duration::from_secs(1),
+            &mut |_: &VerifyEvent| {},
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("length mismatch"));
+    }
+
+    #[test]
+    fn verify_live_waits_for_blink_before_matching() {
+        let mut frames = ScriptedFrames { remaining: 10 };
+        let backend = ScriptedBackend::with_faces(
+            [0.3, 0.1, 0.3]
+                .into_iter()
+                .map(|ear| vec![face(vec![1.0, 0.0], shape_with_ear(ear))])
+                .collect(),
+        );
+        let mut blink = settings(0.9);
+        blink.liveness = LivenessMode::Blink;
+        let mut pending = 0;
+        let mut observer = |event: &VerifyEvent| {
+            if matches!(event, VerifyEvent::LivenessPending { .. }) {
+                pending += 1;
+            }
+        };
+
+        let outcome = verify_live(
+            &mut frames,
+            &backend,
+            &[vec![1.0, 0.0]],
+            &blink,
+            Instant::now() + Duration::from_secs(5),
+            &mut observer,
+        )
+        .unwrap();
+
+        assert!(outcome.success);
+        assert_eq!(outcome.frames_captured, 3);
+        assert_eq!(pending, 2);
+    }
+
+    #[test]
+    fn verify_live_counts_blinks_on_frames_below_the_threshold() {
+        let mut frames = ScriptedFrames { remaining: 10 };
+        // The closed-eye frame scores 0.6, below the 0.9 threshold.
+        let backend = ScriptedBackend::with_faces(vec![
+            vec![face(vec![1.0, 0.0], shape_with_ear(0.3))],
+            vec![face(vec![0.6, 0.8], shape_with_ear(0.1))],
+            vec![face(vec![1.0, 0.0], shape_with_ear(0.3))],
+        ]);
+        let mut blink = settings(0.9);
+        blink.liveness = LivenessMode::Blink;
+
+        let outcome = verify_live(
+            &mut frames,
+            &backend,
+            &[vec![1.0, 0.0]],
+            &blink,
+            Instant::now() + Duration::from_secs(5),
+            &mut |_: &VerifyEvent| {},
+        )
+        .unwrap();
+
+        assert!(outcome.success);
+        assert_eq!(outcome.frames_captured, 3);
+        assert_eq!(outcome.frame_scores[1], Some(0.6));
+    }
+
+    #[test]
+    fn verify_live_reports_unconfirmed_liveness_for_static_face() {
+        let mut frames = ScriptedFrames {
+            remaining: usize::MAX,
+        };
+        let backend = ScriptedBackend::with_faces(
+            (0..3)
+                .map(|_| vec![face(vec![1.0, 0.0], shape_with_ear(0.3))])
+                .collect(),
+        );
+        let mut blink = settings(0.9);
+        blink.liveness = LivenessMode::Blink;
+
+        let outcome = verify_live(
+            &mut frames,
+            &backend,
+            &[vec![1.0, 0.0]],
+            &blink,
+            Instant::now() + Duration::from_millis(20),
+            &mut |_: &VerifyEvent| {},
+        )
+        .unwrap();
+
+        assert!(!outcome.success);
+        assert!(outcome.frames_captured >= 3);
+        assert_eq!(outcome.failure, Some(VerifyFailure::LivenessNotConfirmed));
+    }
+
+    #[test]
+    fn verify_live_rejects_matches_on_dark_screen_frames() {
+        // ScriptedFrames yields all-black frames, i.e. what a phone screen
+        // looks like under active IR.
+        let mut frames = ScriptedFrames {
+            remaining: usize::MAX,
+        };
+        let backend = ScriptedBackend::new(vec![vec![vec![1.0, 0.0]]; 3]);
+        let mut strict = settings(0.9);
+        strict.anti_spoof = AntiSpoofSensitivity::Medium;
+        let mut verdicts = Vec::new();
+        let mut observer = |event: &VerifyEvent| {
+            if let VerifyEvent::SpoofScored { verdict, .. } = event {
+                verdicts.push(*verdict);
+            }
+        };
+
+        let outcome = verify_live(
+            &mut frames,
+            &backend,
+            &[vec![1.0, 0.0]],
+            &strict,
+            Instant::now() + Duration::from_millis(20),
+            &mut observer,
+        )
+        .unwrap();
+
+        assert!(!outcome.success);
+        assert_eq!(outcome.failure, Some(VerifyFailure::SpoofSuspected));
+        assert_eq!(verdicts, vec![Some(SpoofVerdict::ScreenReplay); 3]);
+    }
+
+    /// Serves `frame` forever under a fixed pixel format.
+    struct FormattedFrames {
+        pixel_format: &'static str,
+        frame: GrayImage,
+    }
+
+    impl FrameSource for FormattedFrames {
+        fn next_frame(&mut self) -> AppResult<GrayImage> {
+            Ok(self.frame.clone())
+        }
+
+        fn discard_until(
+            &mut self,
+            _until: Instant,
+            _cancelled: &dyn Fn() -> bool,
+        ) -> AppResult<usize> {
+            Ok(0)
+        }
+
+        fn pixel_format(&self) -> Option<&str> {
+            Some(self.pixel_format)
+        }
+    }
+
+    #[test]
+    fn require_ir_refuses_colour_formats_and_unlit_streams() {
+        let mut ir = settings(0.9);
+        ir.require_ir = true;
+        let run = |frames: &mut dyn FrameSource| {
+            let backend = ScriptedBackend::new(vec![vec![vec![1.0, 0.0]]; 8]);
+            verify_live(
+                frames,
+                &backend,
+                &[vec![1.0, 0.0]],
+                &ir,
+                Instant::now() + Duration::from_secs(5),
+                &mut |_: &VerifyEvent| {},
+            )
+            .unwrap()
+        };
+
+        let lit = GrayImage::from_fn(32, 32, |x, _| image::Luma([40 + (x as u8) * 4]));
+        let colour = run(&mut FormattedFrames {
+            pixel_format: "YUYV",
+            frame: lit.clone(),
+        });
+        assert_eq!(colour.failure, Some(VerifyFailure::NotInfrared));
+        assert_eq!(colour.frames_captured, 0);
+
+        let unlit = run(&mut FormattedFrames {
+            pixel_format: "GREY",
+            frame: GrayImage::new(32, 32),
+        });
+        assert_eq!(unlit.failure, Some(VerifyFailure::NotInfrared));
+        assert_eq!(unlit.frames_captured, IR_PROBE_FRAMES);
+        assert_eq!(unlit.frame_scores, vec![None; IR_PROBE_FRAMES]);
+
+        let genuine = run(&mut FormattedFrames {
+            pixel_format: "Y16",
+            frame: lit,
+        });
+        assert!(genuine.success);
+    }
+
+    /// Serves distinct frames with scripted buffer metadata.
+    struct ReplayedFrames {
+        metadata: VecDeque<FrameMetadata>,
+        current: Option<FrameMetadata>,
+    }
+
+    impl FrameSource for ReplayedFrames {
+        fn next_frame(&mut self) -> AppResult<GrayImage> {
+            self.current = self.metadata.pop_front();
+            let seed = self.current.map_or(0, |meta| meta.sequence as u8);
+            Ok(GrayImage::from_fn(32, 32, |x, _| {
+                image::Luma([seed.wrapping_add(x as u8)])
+            }))
+        }
+
+        fn discard_until(
+            &mut self,
+            _until: Instant,
+            _cancelled: &dyn Fn() -> bool,
+        ) -> AppResult<usize> {
+            Ok(0)
+        }
+
+        fn frame_metadata(&self) -> Option<FrameMetadata> {
+            self.current
+        }
+    }
+
+    #[test]
+    fn stream_integrity_stops_frozen_and_stalled_streams() {
+        let run = |frames: &mut dyn FrameSource, settings: &VerifySettings| {
+            let backend = ScriptedBackend::new(vec![vec![vec![0.0, 1.0]]; 8]);
+            let mut anomalies = Vec::new();
+            let outcome = verify_live(
+                frames,
+                &backend,
+                &[vec![1.0, 0.0]],
+                settings,
+                Instant::now() + Duration::from_millis(200),
+                &mut |event: &VerifyEvent| {
+                    if let VerifyEvent::StreamRejected { anomaly, .. } = event {
+                        anomalies.push(anomaly.clone());
+                    }
+                },
+            )
+            .unwrap();
+            (outcome, anomalies)
+        };
+
+        let still = GrayImage::from_fn(32, 32, |x, _| image::Luma([40 + (x as u8) * 4]));
+        let (frozen, anomalies) = run(
+            &mut FormattedFrames {
+                pixel_format: "GREY",
+                frame: still,
+            },
+            &settings(0.9),
+        );
+        assert_eq!(frozen.failure, Some(VerifyFailure::FrozenStream));
+        assert_eq!(frozen.frames_captured, MAX_IDENTICAL_FRAMES);
+        assert_eq!(anomalies, vec![StreamAnomaly::IdenticalFrames { count: 3 }]);
+
+        let stamp = |sequence, millis| FrameMetadata {
+            sequence,
+            timestamp: Some(Duration::from_millis(millis)),
+        };
+        let replay = || ReplayedFrames {
+            metadata: vec![stamp(1, 100), stamp(2, 133), stamp(3, 133), stamp(4, 200)].into(),
+            current: None,
+        };
+        let (stalled, anomalies) = run(&mut replay(), &settings(0.9));
+        assert_eq!(stalled.failure, Some(VerifyFailure::FrozenStream));
+        assert_eq!(stalled.frames_captured, 3);
+        assert!(matches!(
+            anomalies.as_slice(),
+            [StreamAnomaly::TimestampStalled { .. }]
+        ));
+
+        let mut lenient = settings(0.9);
+        lenient.stream_integrity = false;
+        let (unchecked, anomalies) = run(&mut replay(), &lenient);
+        assert_ne!(unchecked.failure, Some(VerifyFailure::FrozenStream));
+        assert!(anomalies.is_empty());
+    }
+
+    #[test]
+    fn verify_events_serialize_with_tag() {
+        let json = serde_json::to_value(VerifyEvent::NoFaceDetected { frame: 2 }).unwrap();
+        assert_eq!(json["event"], "no_face_detected");
+        assert_eq!(json["frame"], 2);
+    }
+}
@@ -0,0 +1,207 @@
+//! Per-user tally of failed face authentications, modelled on `pam_faillock`.
+//!
+//! Each user has a `<dir>/<user>.json` file listing the Unix timestamps of
+//! consecutive face failures. A successful authentication clears it, and
+//! failures older than the configured window stop counting towards a lockout.
+
+use std::fs::{self, DirBuilder};
+use std::io::{self, Write};
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+
+use crate::errors::{AppError, AppResult};
+use crate::faces::validate_user_name;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailTally {
+    /// Unix timestamps (seconds) of consecutive failures, oldest first.
+    pub failures: Vec<u64>,
+}
+
+impl FailTally {
+    /// Number of failures no older than `window`.
+    pub fn recent(&self, window: Duration, now: SystemTime) -> usize {
+        let cutoff = unix_secs(now).saturating_sub(window.as_secs());
+        self.failures.iter().filter(|&&at| at >= cutoff).count()
+    }
+
+    /// Whether `deny` or more failures fall inside `window`. A `deny` of zero
+    /// disables the lockout.
+    pub fn is_locked(&self, deny: u32, window: Duration, now: SystemTime) -> bool {
+        deny > 0 && self.recent(window, now) >= deny as usize
+    }
+
+    /// Seconds until the lockout lifts, or `None` when not locked.
+    pub fn remaining_lock(&self, deny: u32, window: Duration, now: SystemTime) -> Option<u64> {
+        if !self.is_locked(deny, window, now) {
+            return None;
+        }
+        let oldest_counted = self.failures[self.failures.len() - deny as usize];
+        Some((oldest_counted + window.as_secs()).saturating_sub(unix_secs(now)))
+    }
+
+    pub fn last_failure(&self) -> Option<u64> {
+        self.failures.last().copied()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FaillockStore {
+    dir: PathBuf,
+}
+
+impl FaillockStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn tally_path(&self, user: &str) -> AppResult<PathBuf> {
+        validate_user_name(user)?;
+        Ok(self.dir.join(format!("{user}.json")))
+    }
+
+    /// Reads the tally for `user`; a missing file is an empty tally.
+    pub fn load(&self, user: &str) -> AppResult<FailTally> {
+        let path = self.tally_path(user)?;
+        match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|err| AppError::Faillock {
+                path,
+                source: io::Error::new(io::ErrorKind::InvalidData, err),
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(FailTally::default()),
+            Err(source) => Err(AppError::Faillock { path, source }),
+        }
+    }
+
+    /// Appends a failure at `now`, dropping entries that fell out of `window`.
+    pub fn record_failure(
+        &self,
+        user: &str,
+        window: Duration,
+        now: SystemTime,
+    ) -> AppResult<FailTally> {
+        let mut tally = self.load(user)?;
+        let cutoff = unix_secs(now).saturating_sub(window.as_secs());
+        tally.failures.retain(|&at| at >= cutoff);
+        tally.failures.push(unix_secs(now));
+        self.write(user, &tally)?;
+        Ok(tally)
+    }
+
+    /// Deletes the tally for `user`, returning whether one existed.
+    pub fn reset(&self, user: &str) -> AppResult<bool> {
+        let path = self.tally_path(user)?;
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(source) => Err(AppError::Faillock { path, source }),
+        }
+    }
+
+    fn write(&self, user: &str, tally: &FailTally) -> AppResult<()> {
+        let path = self.tally_path(user)?;
+        let wrap = |source| AppError::Faillock {
+            path: path.clone(),
+            source,
+        };
+        DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(&self.dir)
+            .map_err(wrap)?;
+
+        // NamedTempFile is created with mode 0600, so the tally is never
+        // readable by other users, even before the rename.
+        let mut tmp = NamedTempFile::new_in(&self.dir).map_err(wrap)?;
+        let payload = serde_json::to_vec(tally)?;
+        tmp.write_all(&payload)
+            .and_then(|_| tmp.as_file().sync_all())
+            .map_err(wrap)?;
+        let file = tmp.persist(&path).map_err(|err| wrap(err.error))?;
+        file.set_permissions(fs::Permissions::from_mode(0o600))
+            .map_err(wrap)
+    }
+}
+
+fn unix_secs(at: SystemTime) -> u64 {
+    at.duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn lockout_after_deny_failures_inside_window() {
+        let dir = tempdir().unwrap();
+        let store = FaillockStore::new(dir.path().join("faillock"));
+        let window = Duration::from_secs(600);
+
+        for secs in [1_000, 1_100] {
+            let tally = store.record_failure("alice", window, at(secs)).unwrap();
+            assert!(!tally.is_locked(3, window, at(secs)));
+        }
+        let tally = store.record_failure("alice", window, at(1_200)).unwrap();
+        assert!(tally.is_locked(3, window, at(1_200)));
+        assert_eq!(tally.remaining_lock(3, window, at(1_200)), Some(400));
+        assert!(!tally.is_locked(0, window, at(1_200)));
+
+        // The first failure ages out of the window.
+        assert!(!store.load("alice").unwrap().is_locked(3, window, at(1_601)));
+
+        let mode = fs::metadata(store.tally_path("alice").unwrap())
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn record_failure_prunes_expired_entries() {
+        let dir = tempdir().unwrap();
+        let store = FaillockStore::new(dir.path());
+        let window = Duration::from_secs(60);
+
+        store.record_failure("bob", window, at(100)).unwrap();
+        let tally = store.record_failure("bob", window, at(500)).unwrap();
+        assert_eq!(tally.failures, vec![500]);
+        assert_eq!(tally.last_failure(), Some(500));
+    }
+
+    #[test]
+    fn reset_clears_tally_and_reports_presence() {
+        let dir = tempdir().unwrap();
+        let store = FaillockStore::new(dir.path());
+
+        assert!(!store.reset("carol").unwrap());
+        store
+            .record_failure("carol", Duration::from_secs(60), at(10))
+            .unwrap();
+        assert!(store.reset("carol").unwrap());
+        assert_eq!(store.load("carol").unwrap(), FailTally::default());
+    }
+
+    #[test]
+    fn rejects_path_like_user_names() {
+        let store = FaillockStore::new("/nonexistent");
+        assert!(matches!(
+            store.load("../etc/passwd"),
+            Err(AppError::InvalidUser { .. })
+        ));
+    }
+}
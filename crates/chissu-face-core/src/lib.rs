@@ -1,4 +1,7 @@
+pub mod audit;
 pub mod capture;
+pub mod device_pin;
 pub mod errors;
 pub mod faces;
+pub mod faillock;
 pub mod secret_service;
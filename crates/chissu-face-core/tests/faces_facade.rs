@@ -7,6 +7,7 @@ use chissu_face_core::faces::enrollment::{
     KeyProvider,
 };
 use chissu_face_core::faces::extractor::{BoundingBox, FaceEmbeddingRecord, FaceExtractionSummary};
+use chissu_face_core::faces::selection::FaceSelectionPolicy;
 use chissu_face_core::faces::store::{EnrolledEmbedding, FaceStore, StoreDirResolver};
 use chissu_face_core::secret_service::{EmbeddingKey, EmbeddingKeyStatus};
 use tempfile::TempDir;
@@ -30,6 +31,7 @@ fn integration_enroll_and_remove_with_stubs() {
         user: "alice".into(),
         embedding: embedding_path.clone(),
         store_dir: Some(tmp.path().to_path_buf()),
+        selection: FaceSelectionPolicy::default(),
     };
 
     let outcome =
@@ -109,6 +111,8 @@ fn summary_with_embeddings(source: &str, embeddings: Vec<Vec<f64>>) -> FaceExtra
         success: true,
         image_path: format!("{source}.png"),
         output_path: format!("{source}.json"),
+        image_width: 0,
+        image_height: 0,
         num_faces: embeddings.len(),
         faces: embeddings
             .into_iter()
@@ -120,6 +124,7 @@ fn summary_with_embeddings(source: &str, embeddings: Vec<Vec<f64>>) -> FaceExtra
                     bottom: 1,
                 },
                 embedding,
+                landmarks: Vec::new(),
             })
             .collect(),
         landmark_model: "landmark.dat".into(),
@@ -0,0 +1,283 @@
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chissu_config::{ResolvedConfig, VideoDevice};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModuleArgs {
+    pub threshold: Option<f64>,
+    pub timeout_secs: Option<u64>,
+    pub device: Option<String>,
+    pub config: Option<PathBuf>,
+    pub debug: bool,
+    pub unknown: Vec<String>,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ModuleArgError {
+    #[error("module argument '{arg}' requires a value")]
+    MissingValue { arg: String },
+    #[error("module argument '{arg}' has invalid value '{value}': {reason}")]
+    InvalidValue {
+        arg: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl ModuleArgs {
+    pub fn parse<I, S>(args: I) -> Result<Self, ModuleArgError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut parsed = Self::default();
+        for raw in args {
+            let raw = raw.as_ref().trim();
+            if raw.is_empty() {
+                continue;
+            }
+            let (key, value) = match raw.split_once('=') {
+                Some((key, value)) => (key, Some(value)),
+                None => (raw, None),
+            };
+            match key {
+                "threshold" => {
+                    let value = require_value(key, value)?;
+                    let threshold = value
+                        .parse::<f64>()
+                        .map_err(|err| invalid(key, value, err))?;
+                    if !threshold.is_finite() {
+                        return Err(invalid(key, value, "expected a finite number"));
+                    }
+                    parsed.threshold = Some(threshold);
+                }
+                "timeout" => {
+                    let value = require_value(key, value)?;
+                    let secs = value
+                        .parse::<u64>()
+                        .map_err(|err| invalid(key, value, err))?;
+                    parsed.timeout_secs = Some(secs);
+                }
+                "device" => {
+                    parsed.device = Some(require_value(key, value)?.to_string());
+                }
+                "config" => {
+                    parsed.config = Some(PathBuf::from(require_value(key, value)?));
+                }
+                "debug" if value.is_none() => parsed.debug = true,
+                _ => parsed.unknown.push(raw.to_string()),
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Collects `argv` as handed to `pam_sm_authenticate`.
+    ///
+    /// # Safety
+    /// `argv` must point to `argc` valid, NUL-terminated strings (or be null).
+    pub unsafe fn from_raw(
+        argc: c_int,
+        argv: *const *const c_char,
+    ) -> Result<Self, ModuleArgError> {
+        let mut args = Vec::new();
+        if !argv.is_null() {
+            for idx in 0..argc.max(0) as usize {
+                let ptr = *argv.add(idx);
+                if ptr.is_null() {
+                    continue;
+                }
+                args.push(CStr::from_ptr(ptr).to_string_lossy().into_owned());
+            }
+        }
+        Self::parse(args)
+    }
+
+    /// Overrides `config`; a `threshold=` outside the range of the scope's
+    /// similarity metric is refused rather than applied.
+    pub fn apply(&self, config: &mut ResolvedConfig) -> Result<(), ModuleArgError> {
+        if let Some(threshold) = self.threshold {
+            let range = config.similarity_metric.threshold_range();
+            if !range.contains(&threshold) {
+                return Err(invalid(
+                    "threshold",
+                    &threshold.to_string(),
+                    format!(
+                        "outside {}..={} for the {:?} metric",
+                        range.start(),
+                        range.end(),
+                        config.similarity_metric
+                    ),
+                ));
+            }
+            config.similarity_threshold = threshold;
+        }
+        if let Some(secs) = self.timeout_secs {
+            config.capture_timeout = Duration::from_secs(secs.max(1));
+        }
+        if let Some(device) = &self.device {
+            config.video_device = device.clone();
+            config.video_devices = vec![VideoDevice {
+                device: device.clone(),
+                pixel_format: config.pixel_format.clone(),
+                warmup_frames: config.warmup_frames,
+            }];
+        }
+        Ok(())
+    }
+
+    pub fn overrides_summary(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(threshold) = self.threshold {
+            parts.push(format!("threshold={threshold}"));
+        }
+        if let Some(secs) = self.timeout_secs {
+            parts.push(format!("timeout={secs}"));
+        }
+        if let Some(device) = &self.device {
+            parts.push(format!("device={device}"));
+        }
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(" "))
+        }
+    }
+}
+
+fn require_value<'a>(arg: &str, value: Option<&'a str>) -> Result<&'a str, ModuleArgError> {
+    match value.map(str::trim) {
+        Some(value) if !value.is_empty() => Ok(value),
+        _ => Err(ModuleArgError::MissingValue { arg: arg.into() }),
+    }
+}
+
+fn invalid(arg: &str, value: &str, reason: impl ToString) -> ModuleArgError {
+    ModuleArgError::InvalidValue {
+        arg: arg.into(),
+        value: value.into(),
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chissu_config::SimilarityMetric;
+    use std::ffi::CString;
+
+    #[test]
+    fn parses_known_arguments() {
+        let args = ModuleArgs::parse([
+            "threshold=0.93",
+            "timeout=3",
+            "device=/dev/video2",
+            "config=/etc/chissu-pam/sudo.toml",
+            "debug",
+        ])
+        .unwrap();
+        assert_eq!(args.threshold, Some(0.93));
+        assert_eq!(args.timeout_secs, Some(3));
+        assert_eq!(args.device.as_deref(), Some("/dev/video2"));
+        assert_eq!(
+            args.config,
+            Some(PathBuf::from("/etc/chissu-pam/sudo.toml"))
+        );
+        assert!(args.debug);
+        assert!(args.unknown.is_empty());
+    }
+
+    #[test]
+    fn collects_unknown_arguments() {
+        let args = ModuleArgs::parse(["try_first_pass", "color=blue", "debug=yes"]).unwrap();
+        assert_eq!(
+            args.unknown,
+            vec!["try_first_pass", "color=blue", "debug=yes"]
+        );
+        assert!(!args.debug);
+    }
+
+    #[test]
+    fn rejects_malformed_values() {
+        let err = ModuleArgs::parse(["threshold=high"]).unwrap_err();
+        assert!(matches!(err, ModuleArgError::InvalidValue { ref arg, .. } if arg == "threshold"));
+
+        let err = ModuleArgs::parse(["threshold=NaN"]).unwrap_err();
+        assert!(matches!(err, ModuleArgError::InvalidValue { .. }));
+
+        let err = ModuleArgs::parse(["timeout=-1"]).unwrap_err();
+        assert!(matches!(err, ModuleArgError::InvalidValue { ref arg, .. } if arg == "timeout"));
+
+        let err = ModuleArgs::parse(["device="]).unwrap_err();
+        assert_eq!(
+            err,
+            ModuleArgError::MissingValue {
+                arg: "device".into()
+            }
+        );
+    }
+
+    #[test]
+    fn apply_overrides_resolved_config() {
+        let mut config = ResolvedConfig::default();
+        let args = ModuleArgs::parse(["threshold=0.5", "timeout=0", "device=/dev/video7"]).unwrap();
+        args.apply(&mut config).unwrap();
+        assert_eq!(config.similarity_threshold, 0.5);
+        assert_eq!(config.capture_timeout, Duration::from_secs(1));
+        assert_eq!(config.video_device, "/dev/video7");
+        assert_eq!(config.video_devices.len(), 1);
+        assert_eq!(config.video_devices[0].device, "/dev/video7");
+    }
+
+    #[test]
+    fn apply_refuses_thresholds_outside_the_metric_range() {
+        let mut config = ResolvedConfig::default();
+        for value in ["-5", "1.5"] {
+            let args = ModuleArgs::parse([format!("threshold={value}")]).unwrap();
+            let err = args.apply(&mut config).unwrap_err();
+            assert!(
+                matches!(err, ModuleArgError::InvalidValue { ref arg, .. } if arg == "threshold")
+            );
+        }
+        assert_eq!(
+            config.similarity_threshold,
+            chissu_config::DEFAULT_SIMILARITY_THRESHOLD
+        );
+
+        config.similarity_metric = SimilarityMetric::Euclidean;
+        let args = ModuleArgs::parse(["threshold=1.5"]).unwrap();
+        args.apply(&mut config).unwrap();
+        assert_eq!(config.similarity_threshold, 1.5);
+        let args = ModuleArgs::parse(["threshold=-0.1"]).unwrap();
+        assert!(args.apply(&mut config).is_err());
+    }
+
+    #[test]
+    fn apply_without_arguments_keeps_config() {
+        let mut config = ResolvedConfig::default();
+        ModuleArgs::default().apply(&mut config).unwrap();
+        assert_eq!(
+            config.similarity_threshold,
+            chissu_config::DEFAULT_SIMILARITY_THRESHOLD
+        );
+        assert_eq!(config.video_device, chissu_config::DEFAULT_VIDEO_DEVICE);
+    }
+
+    #[test]
+    fn from_raw_reads_argv() {
+        let owned = [
+            CString::new("threshold=0.8").unwrap(),
+            CString::new("debug").unwrap(),
+        ];
+        let ptrs: Vec<*const c_char> = owned.iter().map(|arg| arg.as_ptr()).collect();
+        let args = unsafe { ModuleArgs::from_raw(ptrs.len() as c_int, ptrs.as_ptr()) }.unwrap();
+        assert_eq!(args.threshold, Some(0.8));
+        assert!(args.debug);
+
+        let empty = unsafe { ModuleArgs::from_raw(0, std::ptr::null()) }.unwrap();
+        assert_eq!(empty, ModuleArgs::default());
+    }
+}
@@ -0,0 +1,201 @@
+//! SIGINT and SIGTERM handling while `pam_sm_authenticate` runs.
+//!
+//! A polkit agent or screen locker that gives up usually signals the process
+//! that is authenticating. The handler installed here only records the
+//! signal. Capture loops and the daemon client wait in short poll slices and
+//! check [`caught`] between them, then unwind, so the V4L2 stream is stopped
+//! and unmapped before PAM returns. Nothing relies on `EINTR`: the handler
+//! keeps the caller's `sa_flags`, including `SA_RESTART`, and the caller's own
+//! dispositions are restored when the guard drops.
+
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use nix::sys::signal::{raise, sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal};
+
+/// Last signal recorded by [`record`]; zero when none. Process-wide, like
+/// the dispositions themselves.
+static CAUGHT: AtomicI32 = AtomicI32::new(0);
+
+const SIGNALS: [Signal; 2] = [Signal::SIGINT, Signal::SIGTERM];
+
+extern "C" fn record(signal: libc::c_int) {
+    CAUGHT.store(signal, Ordering::SeqCst);
+}
+
+/// The signal caught since the guard was installed, if any.
+pub(crate) fn caught() -> Option<Signal> {
+    Signal::try_from(CAUGHT.load(Ordering::SeqCst)).ok()
+}
+
+pub(crate) struct InterruptGuard {
+    previous: Vec<(Signal, SigAction)>,
+    redeliver: bool,
+}
+
+impl InterruptGuard {
+    /// Installs the handler for each signal the caller does not ignore; an
+    /// ignored signal stays ignored. The handler takes over the caller's
+    /// `sa_flags`, and `SA_RESTART` where the caller left the default, so
+    /// system calls on other threads are not interrupted by the module.
+    pub(crate) fn install() -> Self {
+        CAUGHT.store(0, Ordering::SeqCst);
+        let mut previous = Vec::new();
+        for signal in SIGNALS {
+            let Some(current) = disposition(signal) else {
+                continue;
+            };
+            if current.sa_sigaction == libc::SIG_IGN {
+                continue;
+            }
+            let mut flags = SaFlags::from_bits_truncate(current.sa_flags);
+            if current.sa_sigaction == libc::SIG_DFL {
+                flags |= SaFlags::SA_RESTART;
+            }
+            let handler = SigAction::new(SigHandler::Handler(record), flags, SigSet::empty());
+            // SAFETY: `record` only stores to an atomic, which is
+            // async-signal-safe.
+            if let Ok(old) = unsafe { sigaction(signal, &handler) } {
+                previous.push((signal, old));
+            }
+        }
+        Self {
+            previous,
+            redeliver: false,
+        }
+    }
+
+    /// Raises the caught signal again once the caller's handlers are back,
+    /// so a caller with a handler of its own still sees the signal, whatever
+    /// PAM code the module returns. A caller that left the default
+    /// disposition would be terminated before reading that code, so it only
+    /// gets the code.
+    pub(crate) fn redeliver(&mut self) {
+        self.redeliver = true;
+    }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        let mut handled = None;
+        for (signal, old) in self.previous.drain(..).rev() {
+            if Some(signal) == caught() {
+                handled = Some(matches!(
+                    old.handler(),
+                    SigHandler::Handler(_) | SigHandler::SigAction(_)
+                ));
+            }
+            // SAFETY: restores the disposition returned by `sigaction` above.
+            let _ = unsafe { sigaction(signal, &old) };
+        }
+        if self.redeliver && handled == Some(true) {
+            if let Some(signal) = caught() {
+                let _ = raise(signal);
+            }
+        }
+    }
+}
+
+/// The current disposition of `signal`, or `None` if it cannot be read.
+fn disposition(signal: Signal) -> Option<libc::sigaction> {
+    // SAFETY: a null `act` only queries the current disposition.
+    unsafe {
+        let mut current: libc::sigaction = mem::zeroed();
+        (libc::sigaction(signal as libc::c_int, ptr::null(), &mut current) == 0).then_some(current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    #[serial]
+    fn records_signals_and_restores_previous_handlers() {
+        {
+            let _guard = InterruptGuard::install();
+            assert_eq!(caught(), None);
+            raise(Signal::SIGTERM).unwrap();
+            assert_eq!(caught(), Some(Signal::SIGTERM));
+        }
+        let restored = disposition(Signal::SIGTERM).unwrap();
+        assert_eq!(restored.sa_sigaction, libc::SIG_DFL);
+    }
+
+    #[test]
+    #[serial]
+    fn keeps_the_callers_flags() {
+        let handler = SigAction::new(
+            SigHandler::Handler(previous),
+            SaFlags::SA_RESTART | SaFlags::SA_NODEFER,
+            SigSet::empty(),
+        );
+        // SAFETY: `previous` only stores to an atomic.
+        let original = unsafe { sigaction(Signal::SIGTERM, &handler).unwrap() };
+        {
+            let _guard = InterruptGuard::install();
+            let installed = disposition(Signal::SIGTERM).unwrap();
+            assert_eq!(installed.sa_sigaction, record as libc::sighandler_t);
+            let flags = SaFlags::from_bits_truncate(installed.sa_flags);
+            assert!(flags.contains(SaFlags::SA_RESTART | SaFlags::SA_NODEFER));
+        }
+        // SAFETY: puts back the disposition saved above.
+        unsafe { sigaction(Signal::SIGTERM, &original).unwrap() };
+
+        // The default disposition has no flags to keep; SA_RESTART is added.
+        let _guard = InterruptGuard::install();
+        let installed = disposition(Signal::SIGINT).unwrap();
+        assert!(SaFlags::from_bits_truncate(installed.sa_flags).contains(SaFlags::SA_RESTART));
+    }
+
+    static PREVIOUS_SAW: AtomicI32 = AtomicI32::new(0);
+
+    extern "C" fn previous(signal: libc::c_int) {
+        PREVIOUS_SAW.store(signal, Ordering::SeqCst);
+    }
+
+    #[test]
+    #[serial]
+    fn redelivers_caught_signals_to_the_previous_handler() {
+        let handler = SigAction::new(
+            SigHandler::Handler(previous),
+            SaFlags::empty(),
+            SigSet::empty(),
+        );
+        // SAFETY: `previous` only stores to an atomic.
+        let original = unsafe { sigaction(Signal::SIGTERM, &handler) }.unwrap();
+        PREVIOUS_SAW.store(0, Ordering::SeqCst);
+        {
+            let mut guard = InterruptGuard::install();
+            raise(Signal::SIGTERM).unwrap();
+            assert_eq!(PREVIOUS_SAW.load(Ordering::SeqCst), 0);
+            guard.redeliver();
+        }
+        assert_eq!(PREVIOUS_SAW.load(Ordering::SeqCst), libc::SIGTERM);
+        // SAFETY: puts back the disposition saved above.
+        unsafe { sigaction(Signal::SIGTERM, &original).unwrap() };
+    }
+
+    #[test]
+    #[serial]
+    fn leaves_default_dispositions_to_the_return_code() {
+        let default = SigAction::new(SigHandler::SigDfl, SaFlags::empty(), SigSet::empty());
+        // SAFETY: SIG_DFL installs no handler.
+        let original = unsafe { sigaction(Signal::SIGINT, &default).unwrap() };
+        {
+            let mut guard = InterruptGuard::install();
+            raise(Signal::SIGINT).unwrap();
+            guard.redeliver();
+        }
+        // Raising SIGINT under SIG_DFL would have ended the test process.
+        assert_eq!(caught(), Some(Signal::SIGINT));
+        assert_eq!(
+            disposition(Signal::SIGINT).unwrap().sa_sigaction,
+            libc::SIG_DFL
+        );
+        // SAFETY: puts back the disposition saved above.
+        unsafe { sigaction(Signal::SIGINT, &original).unwrap() };
+    }
+}
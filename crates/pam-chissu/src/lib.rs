@@ -1,34 +1,60 @@
+mod args;
+mod interrupt;
 mod logind;
+mod messages;
+mod progress;
 mod secret_helper;
 
 use std::env;
 use std::ffi::{c_void, CStr, CString};
+use std::fmt;
+use std::fs;
 use std::os::raw::c_char;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::ptr;
 use std::slice;
-use std::thread::sleep;
-use std::time::{Duration, Instant};
-
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Once;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use args::ModuleArgs;
+use chissu_authd::{
+    AuthdClient, ClientError as AuthdClientError, RequestedDevice, VerifyRequest, PROTOCOL_VERSION,
+};
 use chissu_config::{
-    self, ConfigError, ResolvedConfig, ResolvedConfigWithSource, SecretServiceSessionMode,
+    self, AntiSpoofSensitivity as ConfigAntiSpoof, AuthMode, ConfigError, DeviceErrorAction,
+    DevicePinMode, FaceSelection as ConfigFaceSelection, FaillockAction, Fusion as ConfigFusion,
+    InterruptAction, LivenessMode as ConfigLivenessMode, ResolvedConfig, ResolvedConfigWithSource,
+    SecretServiceSessionMode, SessionPolicy, SimilarityMetric as ConfigSimilarityMetric,
 };
-use chissu_face_core::capture::{capture_frame_in_memory, CaptureConfig, DeviceLocator};
-use chissu_face_core::errors::AppError;
+use chissu_face_core::audit::{audit_timestamp, AuditLog, AuditOutcome, AuditRecord};
+use chissu_face_core::capture::{
+    open_first_available, CaptureConfig, CaptureSession, DeviceLocator, FirstAvailable,
+};
+use chissu_face_core::device_pin::{DevicePinStore, DeviceTrustPolicy};
+use chissu_face_core::errors::{AppError, DeviceFault};
 use chissu_face_core::faces::{
-    cosine_similarity, load_enrolled_embeddings, validate_user_name, DlibBackend,
-    EnrolledEmbedding, EnvModelPathResolver, FaceEmbeddingBackend, FaceExtractionConfig,
-    ModelPathResolver,
+    apply_adaptive_update, load_enrolled_embeddings, user_store_path, validate_user_name,
+    verify_live, AdaptiveUpdatePolicy, AntiSpoofSensitivity, DlibBackend, EnrolledEmbedding,
+    EnvModelPathResolver, FaceExtractionConfig, FaceSelectionMode, FaceSelectionPolicy,
+    FilesystemFaceStore, FusionMode, FusionPolicy, LivenessMode, ModelPathResolver,
+    SimilarityMetric, VerifyEvent, VerifyFailure, VerifyObserver, VerifyOutcome, VerifySettings,
 };
+use chissu_face_core::faillock::FaillockStore;
 use chissu_face_core::secret_service::default_service_name;
-use image::{Rgb, RgbImage};
+use interrupt::InterruptGuard;
 use libc::{c_int, free};
-use logind::{EffectiveSessionMode, LogindInspector};
+use logind::{EffectiveSessionMode, LogindInspector, ProcessSession};
+use messages::{Message, MessageContext};
+use nix::sys::signal::Signal;
 use nix::unistd::{getegid, geteuid, User};
 use pam_sys::{
-    get_item, get_user, ConvClosure, PamConversation, PamHandle, PamItemType, PamMessage,
-    PamMessageStyle, PamResponse, PamReturnCode,
+    get_item, get_user, set_item, ConvClosure, PamConversation, PamFlag, PamHandle, PamItemType,
+    PamMessage, PamMessageStyle, PamResponse, PamReturnCode,
 };
+use progress::ProgressReporter;
 use secret_helper::{
     run_secret_service_helper, HelperEnvOverrides, HelperError as SecretHelperError, HelperResponse,
 };
@@ -38,8 +64,6 @@ use thiserror::Error;
 type PamResult<T> = Result<T, AuthError>;
 
 const SYSLOG_IDENTIFIER: &str = "pam_chissu";
-const SECRET_SERVICE_FALLBACK_PROMPT: &str =
-    "Face authentication unavailable. Falling back to password.";
 
 #[derive(Debug, Error)]
 enum AuthError {
@@ -51,17 +75,83 @@ enum AuthError {
     Core(#[from] AppError),
     #[error("Secret Service unavailable: {0}")]
     SecretServiceUnavailable(String),
+    #[error("{0}")]
+    Skipped(String),
+    #[error("{0}")]
+    Daemon(String),
+    #[error("camera {}: {message}", .fault.describe())]
+    DeviceUnavailable { fault: DeviceFault, message: String },
+    /// A password was typed at the concurrent prompt; it becomes `PAM_AUTHTOK`.
+    #[error("password entered at the concurrent prompt")]
+    PasswordEntered(AuthToken),
+    /// An error under `mode = "observe"`, logged and turned into `PAM_IGNORE`.
+    #[error("{0}")]
+    Observed(Box<AuthError>),
+    /// SIGINT or SIGTERM stopped the capture; `action` decides the return.
+    #[error("interrupted by {}", .signal.as_str())]
+    Interrupted {
+        signal: Signal,
+        action: InterruptAction,
+    },
+}
+
+impl AuthError {
+    /// Keeps camera-unavailable errors apart from other capture failures so
+    /// the `device_*_action` policy can apply to them.
+    fn from_capture(err: AppError) -> Self {
+        match err.device_fault() {
+            Some(fault) => AuthError::DeviceUnavailable {
+                fault,
+                message: err.to_string(),
+            },
+            None => AuthError::Core(err),
+        }
+    }
+}
+
+/// A conversation reply, wiped on drop.
+struct AuthToken(Vec<u8>);
+
+impl AuthToken {
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Debug for AuthToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AuthToken(<redacted>)")
+    }
+}
+
+impl Drop for AuthToken {
+    fn drop(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+fn wipe(bytes: &mut [u8]) {
+    for byte in bytes.iter_mut() {
+        // Volatile so the compiler cannot drop the store as dead.
+        unsafe { ptr::write_volatile(byte, 0) };
+    }
 }
 
 #[derive(Debug)]
 struct PamRequest {
+    service: String,
     user: String,
     tty: Option<String>,
+    /// `PAM_RHOST`, set by remote services such as `sshd`.
+    rhost: Option<String>,
 }
 
 struct PamConversationMessenger {
     conv: Option<ConvClosure>,
     data_ptr: *mut c_void,
+    context: MessageContext,
+    /// `PAM_SILENT`: info and error messages are dropped, prompts still go out.
+    silent: bool,
 }
 
 impl PamConversationMessenger {
@@ -88,6 +178,8 @@ impl PamConversationMessenger {
             Some(callback) => Self {
                 conv: Some(callback),
                 data_ptr: conv_struct.data_ptr,
+                context: MessageContext::default(),
+                silent: false,
             },
             None => {
                 logger
@@ -101,6 +193,46 @@ impl PamConversationMessenger {
         Self {
             conv: None,
             data_ptr: ptr::null_mut(),
+            context: MessageContext::default(),
+            silent: false,
+        }
+    }
+
+    /// Honours `PAM_SILENT`, which covers informational and error messages;
+    /// `concurrent_prompt` and `confirm_match` still prompt.
+    fn silence(&mut self) {
+        self.silent = true;
+    }
+
+    fn set_context(&mut self, context: MessageContext) {
+        self.context = context;
+    }
+
+    /// Picks up the threshold and `[messages]` templates of the resolved scope.
+    fn configure(&mut self, config: &ResolvedConfig) {
+        self.context.threshold = Some(config.similarity_threshold);
+        self.context.templates = config.messages.clone();
+    }
+
+    /// Renders `message` from its template and sends it, unless the template
+    /// is empty.
+    fn notify(
+        &mut self,
+        logger: &mut PamLogger,
+        message: Message,
+        similarity: Option<f64>,
+        frames: usize,
+    ) {
+        let Some(text) = self.context.render(message, similarity, frames) else {
+            logger.debug(&format!(
+                "Conversation message {message:?} disabled by template"
+            ));
+            return;
+        };
+        if message.is_error() {
+            self.send_error_msg(logger, &text);
+        } else {
+            self.send_text_info(logger, &text);
         }
     }
 
@@ -113,14 +245,72 @@ impl PamConversationMessenger {
     }
 
     fn send(&mut self, logger: &mut PamLogger, style: PamMessageStyle, message: &str) {
+        if self.silent {
+            return;
+        }
+        if let Err(err) = self.converse(style, message) {
+            logger.warn(&err);
+        }
+    }
+
+    /// Whether prompts can be sent at all; false without a conversation
+    /// callback.
+    fn can_prompt(&self) -> bool {
+        self.conv.is_some()
+    }
+
+    fn prompt_echo_off(&mut self, message: &str) -> Result<Option<AuthToken>, String> {
+        self.converse(PamMessageStyle::PROMPT_ECHO_OFF, message)
+    }
+
+    fn prompt_echo_on(&mut self, message: &str) -> Result<Option<AuthToken>, String> {
+        self.converse(PamMessageStyle::PROMPT_ECHO_ON, message)
+    }
+
+    /// Like [`prompt_echo_on`](Self::prompt_echo_on), but gives up after
+    /// `timeout` and returns `None`. The prompt runs on its own thread, which
+    /// is left waiting on the application when time runs out; its answer is
+    /// then wiped unread.
+    fn prompt_echo_on_within(
+        &self,
+        message: &str,
+        timeout: Duration,
+    ) -> Option<Result<Option<AuthToken>, String>> {
+        let conversation = DetachedConversation(Self {
+            conv: self.conv,
+            data_ptr: self.data_ptr,
+            context: MessageContext::default(),
+            silent: false,
+        });
+        let message = message.to_string();
+        let (sender, receiver) = mpsc::channel();
+        pin_module();
+        thread::spawn(move || {
+            let _ = sender.send(conversation.prompt_echo_on(&message));
+        });
+        match receiver.recv_timeout(timeout) {
+            Ok(answer) => Some(answer),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => {
+                Some(Err("conversation thread ended without an answer".into()))
+            }
+        }
+    }
+
+    /// Sends one message and returns the application's reply, if any. The
+    /// reply is copied and wiped before the conversation buffer is freed.
+    fn converse(
+        &mut self,
+        style: PamMessageStyle,
+        message: &str,
+    ) -> Result<Option<AuthToken>, String> {
         let callback = match self.conv {
             Some(conv) => conv,
-            None => return,
+            None => return Ok(None),
         };
 
         let Ok(c_message) = CString::new(message) else {
-            logger.warn("PAM conversation message contained an interior null byte; skipped");
-            return;
+            return Err("PAM conversation message contained an interior null byte; skipped".into());
         };
 
         let mut pam_message = PamMessage {
@@ -130,11 +320,16 @@ impl PamConversationMessenger {
         let mut pam_message_ptr: *mut PamMessage = &mut pam_message;
         let mut response_ptr: *mut PamResponse = ptr::null_mut();
         let status = callback(1, &mut pam_message_ptr, &mut response_ptr, self.data_ptr);
+        let mut reply = None;
         unsafe {
             if !response_ptr.is_null() {
                 let responses = slice::from_raw_parts_mut(response_ptr, 1);
                 for response in responses {
                     if !response.resp.is_null() {
+                        let len = CStr::from_ptr(response.resp).to_bytes().len();
+                        let bytes = slice::from_raw_parts_mut(response.resp as *mut u8, len);
+                        reply = Some(AuthToken(bytes.to_vec()));
+                        wipe(bytes);
                         free(response.resp as *mut c_void);
                     }
                 }
@@ -144,10 +339,11 @@ impl PamConversationMessenger {
 
         if status != PamReturnCode::SUCCESS as c_int {
             let code = PamReturnCode::from(status);
-            logger.warn(&format!(
+            return Err(format!(
                 "PAM conversation callback returned {code:?} while sending {style:?}"
             ));
         }
+        Ok(reply)
     }
 
     #[cfg(test)]
@@ -155,13 +351,53 @@ impl PamConversationMessenger {
         Self {
             conv: Some(callback),
             data_ptr: ptr::null_mut(),
+            context: MessageContext::default(),
+            silent: false,
         }
     }
 }
 
+/// A copy of the conversation callback handed to a prompt thread that may
+/// outlive the PAM call.
+struct DetachedConversation(PamConversationMessenger);
+
+// SAFETY: the copy holds the application's callback and its opaque data
+// pointer, which Rust code never dereferences; only the prompt thread uses it.
+unsafe impl Send for DetachedConversation {}
+
+impl DetachedConversation {
+    fn prompt_echo_on(mut self, message: &str) -> Result<Option<AuthToken>, String> {
+        self.0.prompt_echo_on(message)
+    }
+}
+
+/// Keeps this module mapped until the process exits, so a prompt thread left
+/// waiting on the application cannot return into code that `pam_end`
+/// unloaded.
+fn pin_module() {
+    static PINNED: Once = Once::new();
+    PINNED.call_once(|| {
+        // SAFETY: dladdr only reads loader state for an address inside this
+        // module. dlopen with RTLD_NOLOAD takes a reference to the already
+        // loaded module, and RTLD_NODELETE keeps it mapped after dlclose.
+        unsafe {
+            let mut info: libc::Dl_info = std::mem::zeroed();
+            if libc::dladdr(pin_module as *const c_void, &mut info) != 0
+                && !info.dli_fname.is_null()
+            {
+                libc::dlopen(
+                    info.dli_fname,
+                    libc::RTLD_NOW | libc::RTLD_NOLOAD | libc::RTLD_NODELETE,
+                );
+            }
+        }
+    });
+}
+
 struct PamLogger {
     service: String,
     logger: Option<Logger<LoggerBackend, Formatter3164>>,
+    promote_debug: bool,
 }
 
 impl PamLogger {
@@ -171,9 +407,22 @@ impl PamLogger {
         Self {
             service: service.to_string(),
             logger,
+            promote_debug: false,
         }
     }
 
+    /// Emits debug lines at INFO priority so they survive default syslog filtering.
+    fn enable_debug(&mut self) {
+        self.promote_debug = true;
+    }
+
+    /// A separate syslog connection with the same settings, for a worker thread.
+    fn worker(&self) -> Self {
+        let mut logger = Self::new(&self.service);
+        logger.promote_debug = self.promote_debug;
+        logger
+    }
+
     fn formatter() -> Formatter3164 {
         Formatter3164 {
             facility: Facility::LOG_AUTHPRIV,
@@ -196,7 +445,11 @@ impl PamLogger {
     }
 
     fn debug(&mut self, message: &str) {
-        self.send(|logger, msg| logger.debug(msg), "DEBUG", message);
+        if self.promote_debug {
+            self.send(|logger, msg| logger.info(msg), "DEBUG", message);
+        } else {
+            self.send(|logger, msg| logger.debug(msg), "DEBUG", message);
+        }
     }
 
     fn send<F>(&mut self, mut emit: F, level: &str, message: &str)
@@ -217,6 +470,57 @@ enum FailureReason {
     EmbeddingsMissing,
     NoFaceDetected,
     ThresholdNotReached,
+    LivenessNotConfirmed,
+    SpoofSuspected,
+    UntrustedDevice,
+    NotInfrared,
+    FrozenStream,
+    /// The match was not confirmed within `confirm_timeout_secs`.
+    ConfirmationTimeout,
+    LockedOut,
+}
+
+impl FailureReason {
+    /// Stable name written to the audit log.
+    fn as_str(self) -> &'static str {
+        match self {
+            FailureReason::EmbeddingsMissing => "embeddings_missing",
+            FailureReason::NoFaceDetected => "no_face_detected",
+            FailureReason::ThresholdNotReached => "threshold_not_reached",
+            FailureReason::LivenessNotConfirmed => "liveness_not_confirmed",
+            FailureReason::SpoofSuspected => "spoof_suspected",
+            FailureReason::UntrustedDevice => "untrusted_device",
+            FailureReason::NotInfrared => "not_infrared",
+            FailureReason::FrozenStream => "frozen_stream",
+            FailureReason::ConfirmationTimeout => "confirmation_timeout",
+            FailureReason::LockedOut => "locked_out",
+        }
+    }
+
+    fn message(self) -> Message {
+        match self {
+            FailureReason::EmbeddingsMissing => Message::EmbeddingsMissing,
+            FailureReason::NoFaceDetected => Message::NoFaceDetected,
+            FailureReason::ThresholdNotReached => Message::ThresholdNotReached,
+            FailureReason::LivenessNotConfirmed => Message::LivenessNotConfirmed,
+            FailureReason::SpoofSuspected => Message::SpoofSuspected,
+            FailureReason::UntrustedDevice => Message::UntrustedDevice,
+            FailureReason::NotInfrared => Message::NotInfrared,
+            FailureReason::FrozenStream => Message::FrozenStream,
+            FailureReason::ConfirmationTimeout => Message::ConfirmationTimeout,
+            FailureReason::LockedOut => Message::LockedOut,
+        }
+    }
+
+    /// Whether the attempt reached the camera and counts towards the faillock tally.
+    fn counts_towards_faillock(self) -> bool {
+        !matches!(
+            self,
+            FailureReason::EmbeddingsMissing
+                | FailureReason::ConfirmationTimeout
+                | FailureReason::LockedOut
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -225,6 +529,10 @@ struct AuthResult {
     frames_captured: usize,
     best_similarity: f64,
     failure_reason: Option<FailureReason>,
+    /// Per-frame best similarity; `None` for frames without a usable face.
+    frame_scores: Vec<Option<f64>>,
+    /// Decided under `mode = "observe"`: logged, then `PAM_IGNORE` returned.
+    observed: bool,
 }
 
 impl AuthResult {
@@ -234,6 +542,8 @@ impl AuthResult {
             frames_captured: frames,
             best_similarity,
             failure_reason: None,
+            frame_scores: Vec::new(),
+            observed: false,
         }
     }
 
@@ -243,8 +553,70 @@ impl AuthResult {
             frames_captured: frames,
             best_similarity,
             failure_reason: Some(reason),
+            frame_scores: Vec::new(),
+            observed: false,
         }
     }
+
+    /// `scores=[0.9312, -, 0.9475]` for the log line, or nothing when no
+    /// frame was captured.
+    fn scores_summary(&self) -> String {
+        if self.frame_scores.is_empty() {
+            return String::new();
+        }
+        let scores: Vec<String> = self
+            .frame_scores
+            .iter()
+            .map(|score| score.map_or_else(|| "-".to_string(), |score| format!("{score:.4}")))
+            .collect();
+        format!(", scores=[{}]", scores.join(", "))
+    }
+
+    /// Syslog line for an observe-mode decision. The `[observe]` marker and
+    /// `decision=` field keep these lines apart from enforced results.
+    fn observed_summary(&self) -> String {
+        let decision = match self.failure_reason {
+            None if self.success => "success".to_string(),
+            reason => format!(
+                "failure reason={}",
+                reason
+                    .unwrap_or(FailureReason::ThresholdNotReached)
+                    .as_str()
+            ),
+        };
+        format!(
+            "[observe] decision={decision} frames={} best_similarity={:.4}{}; returning PAM_IGNORE",
+            self.frames_captured,
+            self.best_similarity,
+            self.scores_summary()
+        )
+    }
+}
+
+impl From<VerifyOutcome> for AuthResult {
+    fn from(outcome: VerifyOutcome) -> Self {
+        let mut result = match outcome.failure {
+            None if outcome.success => {
+                Self::success(outcome.best_similarity, outcome.frames_captured)
+            }
+            failure => {
+                let reason = match failure {
+                    Some(VerifyFailure::NoFaceDetected) => FailureReason::NoFaceDetected,
+                    Some(VerifyFailure::LivenessNotConfirmed) => {
+                        FailureReason::LivenessNotConfirmed
+                    }
+                    Some(VerifyFailure::SpoofSuspected) => FailureReason::SpoofSuspected,
+                    Some(VerifyFailure::UntrustedDevice) => FailureReason::UntrustedDevice,
+                    Some(VerifyFailure::NotInfrared) => FailureReason::NotInfrared,
+                    Some(VerifyFailure::FrozenStream) => FailureReason::FrozenStream,
+                    _ => FailureReason::ThresholdNotReached,
+                };
+                Self::failure(reason, outcome.best_similarity, outcome.frames_captured)
+            }
+        };
+        result.frame_scores = outcome.frame_scores;
+        result
+    }
 }
 
 /// # Safety
@@ -252,10 +624,11 @@ impl AuthResult {
 #[no_mangle]
 pub unsafe extern "C" fn pam_sm_authenticate(
     pamh: *mut PamHandle,
-    _flags: c_int,
-    _argc: c_int,
-    _argv: *const *const c_char,
+    flags: c_int,
+    argc: c_int,
+    argv: *const *const c_char,
 ) -> c_int {
+    let mut audit = AuditTrail::new();
     let (service, service_err) = match unsafe { get_service_name(pamh) } {
         Ok(name) => (name, None),
         Err(err) => ("unknown".to_string(), Some(err)),
@@ -265,6 +638,14 @@ pub unsafe extern "C" fn pam_sm_authenticate(
         logger.warn(&format!("Failed to read PAM service name: {err}"));
     }
 
+    let module_args = match unsafe { parse_module_args(argc, argv, &mut logger) } {
+        Ok(args) => args,
+        Err(code) => return code as c_int,
+    };
+    for arg in &module_args.unknown {
+        logger.warn(&format!("Ignoring unknown module argument '{arg}'"));
+    }
+
     let user = match unsafe { get_user_name(pamh) } {
         Ok(user) => user,
         Err(err) => {
@@ -274,41 +655,152 @@ pub unsafe extern "C" fn pam_sm_authenticate(
     };
 
     let mut messenger = PamConversationMessenger::new(pamh, &mut logger);
+    if is_silent(flags) {
+        logger.debug("PAM_SILENT set; conversation messages suppressed");
+        messenger.silence();
+    }
 
     let tty = unsafe { get_tty_name(pamh) };
     if let Some(ref tty_name) = tty {
         logger.debug(&format!("PAM provided tty '{tty_name}'"));
     }
+    let rhost = unsafe { get_remote_host(pamh) };
+    if let Some(ref host) = rhost {
+        logger.debug(&format!("PAM provided rhost '{host}'"));
+    }
 
-    let request = PamRequest { user, tty };
+    let request = PamRequest {
+        service: service.clone(),
+        user,
+        tty,
+        rhost,
+    };
+    messenger.set_context(MessageContext::new(&request.user, &service));
     logger.info(&format!(
         "Starting face authentication for user '{}'.",
         request.user
     ));
 
-    let outcome = match authenticate_user(&request, &mut logger, &mut messenger) {
+    let mut signals = InterruptGuard::install();
+    let outcome = match authenticate_user(
+        &request,
+        &module_args,
+        &mut audit,
+        &mut logger,
+        &mut messenger,
+    ) {
         Ok(result) => result,
+        Err(AuthError::Interrupted { signal, action }) => {
+            // A caller with its own handler gets the signal back either way.
+            signals.redeliver();
+            let code = match action {
+                InterruptAction::Abort => PamReturnCode::ABORT,
+                InterruptAction::Ignore => PamReturnCode::IGNORE,
+            };
+            logger.info(&format!(
+                "Face authentication interrupted by {}; camera released, returning PAM_{code:?}",
+                signal.as_str()
+            ));
+            audit.record(
+                &request,
+                AuditOutcome::Ignored,
+                None,
+                Some(format!("interrupted by {}", signal.as_str())),
+                &mut logger,
+            );
+            return code as c_int;
+        }
         Err(AuthError::SecretServiceUnavailable(reason)) => {
             notify_secret_service_unavailable(&reason, &mut logger, &mut messenger);
+            audit.record(
+                &request,
+                AuditOutcome::Ignored,
+                None,
+                Some(format!("Secret Service unavailable: {reason}")),
+                &mut logger,
+            );
+            return PamReturnCode::IGNORE as c_int;
+        }
+        Err(AuthError::PasswordEntered(token)) => {
+            let detail = match unsafe { set_authtok(pamh, &token) } {
+                Ok(()) => {
+                    logger.info("Password entered at the concurrent prompt; camera cancelled and PAM_AUTHTOK set for the next module");
+                    "password entered at the concurrent prompt".to_string()
+                }
+                Err(err) => {
+                    logger.warn(&format!("Failed to hand the typed password on: {err}"));
+                    format!("password entered at the concurrent prompt, not handed on: {err}")
+                }
+            };
+            audit.record(
+                &request,
+                AuditOutcome::Ignored,
+                None,
+                Some(detail),
+                &mut logger,
+            );
+            return PamReturnCode::IGNORE as c_int;
+        }
+        Err(AuthError::Skipped(reason)) => {
+            logger.info(&format!("Face authentication skipped: {reason}"));
+            audit.record(
+                &request,
+                AuditOutcome::Ignored,
+                None,
+                Some(reason),
+                &mut logger,
+            );
+            return PamReturnCode::IGNORE as c_int;
+        }
+        Err(AuthError::Observed(err)) => {
+            logger.info(&format!(
+                "[observe] decision=error ({err}); returning PAM_IGNORE"
+            ));
+            audit.record(
+                &request,
+                AuditOutcome::Error,
+                None,
+                Some(err.to_string()),
+                &mut logger,
+            );
             return PamReturnCode::IGNORE as c_int;
         }
         Err(err) => {
             logger.error(&format!("Authentication aborted: {err}"));
+            audit.record(
+                &request,
+                AuditOutcome::Error,
+                None,
+                Some(err.to_string()),
+                &mut logger,
+            );
             return PamReturnCode::SYSTEM_ERR as c_int;
         }
     };
 
+    let audit_outcome = if outcome.success {
+        AuditOutcome::Success
+    } else {
+        AuditOutcome::Failure
+    };
+    audit.record(&request, audit_outcome, Some(&outcome), None, &mut logger);
+
+    if outcome.observed {
+        logger.info(&outcome.observed_summary());
+        return PamReturnCode::IGNORE as c_int;
+    }
     if outcome.success {
         logger.info(&format!(
-            "Authentication success (frames={}, best_similarity={:.4}).",
-            outcome.frames_captured, outcome.best_similarity
+            "Authentication success (frames={}, best_similarity={:.4}{}).",
+            outcome.frames_captured,
+            outcome.best_similarity,
+            outcome.scores_summary()
         ));
-        messenger.send_text_info(
+        messenger.notify(
             &mut logger,
-            &format!(
-                "Face authentication succeeded for user '{}' via service '{}'.",
-                request.user, service
-            ),
+            Message::Success,
+            Some(outcome.best_similarity),
+            outcome.frames_captured,
         );
         PamReturnCode::SUCCESS as c_int
     } else {
@@ -319,31 +811,84 @@ pub unsafe extern "C" fn pam_sm_authenticate(
             FailureReason::EmbeddingsMissing => "no enrolled embeddings",
             FailureReason::NoFaceDetected => "no face detected in captured frames",
             FailureReason::ThresholdNotReached => "no embedding met similarity threshold",
-        };
-        logger.warn(&format!(
-            "Authentication failed: {} (frames={}, best_similarity={:.4}).",
-            reason, outcome.frames_captured, outcome.best_similarity
-        ));
-        let prompt = match outcome
-            .failure_reason
-            .unwrap_or(FailureReason::ThresholdNotReached)
-        {
-            FailureReason::EmbeddingsMissing => format!(
-                "Face authentication unavailable: no enrolled embeddings for '{}'.",
-                request.user
-            ),
-            FailureReason::NoFaceDetected => {
-                "No face detected before timeout; stay in frame and retry.".to_string()
+            FailureReason::LivenessNotConfirmed => {
+                "face matched but liveness check saw no blink or eye motion"
             }
-            FailureReason::ThresholdNotReached => {
-                "Face detected but similarity below threshold; please retry.".to_string()
+            FailureReason::SpoofSuspected => {
+                "matching frames looked like a screen replay or printed photo"
             }
+            FailureReason::UntrustedDevice => {
+                "camera failed the pinned identity or virtual-driver check"
+            }
+            FailureReason::NotInfrared => "require_ir is set and the stream is not a lit IR stream",
+            FailureReason::FrozenStream => "camera stream looked frozen or replayed",
+            FailureReason::ConfirmationTimeout => "face matched but was not confirmed in time",
+            FailureReason::LockedOut => "locked out after repeated face failures",
         };
-        messenger.send_error_msg(&mut logger, &prompt);
+        logger.warn(&format!(
+            "Authentication failed: {} (frames={}, best_similarity={:.4}{}).",
+            reason,
+            outcome.frames_captured,
+            outcome.best_similarity,
+            outcome.scores_summary()
+        ));
+        messenger.notify(
+            &mut logger,
+            outcome
+                .failure_reason
+                .unwrap_or(FailureReason::ThresholdNotReached)
+                .message(),
+            Some(outcome.best_similarity),
+            outcome.frames_captured,
+        );
         PamReturnCode::AUTH_ERR as c_int
     }
 }
 
+/// Clears the faillock tally once the whole auth stack has succeeded, e.g.
+/// after a password login. Returns `PAM_IGNORE` so it never affects the
+/// account decision, unless the module arguments are invalid.
+///
+/// # Safety
+/// The PAM stack guarantees `pamh` is a valid pointer for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn pam_sm_acct_mgmt(
+    pamh: *mut PamHandle,
+    _flags: c_int,
+    argc: c_int,
+    argv: *const *const c_char,
+) -> c_int {
+    let service = unsafe { get_service_name(pamh) }.unwrap_or_else(|_| "unknown".to_string());
+    let mut logger = PamLogger::new(&service);
+    let module_args = match unsafe { parse_module_args(argc, argv, &mut logger) } {
+        Ok(args) => args,
+        Err(code) => return code as c_int,
+    };
+    let user = match unsafe { get_user_name(pamh) } {
+        Ok(user) => user,
+        Err(err) => {
+            logger.warn(&format!("Failed to read PAM user: {err}"));
+            return PamReturnCode::IGNORE as c_int;
+        }
+    };
+
+    match load_config(module_args.config.as_deref(), &service, &user) {
+        Ok(loaded) if loaded.resolved.faillock_deny > 0 => {
+            let store = FaillockStore::new(&loaded.resolved.faillock_dir);
+            match store.reset(&user) {
+                Ok(true) => logger.info(&format!(
+                    "Cleared face faillock tally for user '{user}' after successful authentication"
+                )),
+                Ok(false) => {}
+                Err(err) => logger.warn(&format!("Failed to reset faillock tally: {err}")),
+            }
+        }
+        Ok(_) => {}
+        Err(err) => logger.warn(&format!("Failed to load configuration: {err}")),
+    }
+    PamReturnCode::IGNORE as c_int
+}
+
 /// # Safety
 /// The PAM stack guarantees `pamh` (even if unused) remains a valid pointer for the call duration.
 #[no_mangle]
@@ -358,20 +903,122 @@ pub unsafe extern "C" fn pam_sm_setcred(
 
 fn authenticate_user(
     request: &PamRequest,
+    module_args: &ModuleArgs,
+    audit: &mut AuditTrail,
     logger: &mut PamLogger,
     messenger: &mut PamConversationMessenger,
 ) -> PamResult<AuthResult> {
     validate_user_name(&request.user)?;
 
     let ResolvedConfigWithSource {
-        resolved: config,
+        resolved: mut config,
         source,
-    } = load_config()?;
+    } = load_config(
+        module_args.config.as_deref(),
+        &request.service,
+        &request.user,
+    )?;
     if let Some(path) = source {
         logger.info(&format!("Loaded configuration from {}", path.display()));
     } else {
         logger.info("No configuration file found; using built-in defaults");
     }
+    module_args
+        .apply(&mut config)
+        .map_err(|err| AuthError::Config(format!("Invalid module arguments: {err}")))?;
+    audit.configure(&config);
+    messenger.configure(&config);
+    if !config.enabled {
+        return Err(AuthError::Skipped(format!(
+            "disabled by configuration for service '{}' and user '{}'",
+            request.service, request.user
+        )));
+    }
+    if let Some(summary) = module_args.overrides_summary() {
+        logger.info(&format!("Applied module argument overrides: {summary}"));
+    }
+    check_session_policy(&config, request, logger)?;
+    let observe = config.mode == AuthMode::Observe;
+    if observe {
+        logger.info("Observe mode: the face decision is logged and PAM_IGNORE returned");
+    }
+    authenticate_configured(&config, request, observe, audit, logger, messenger).map_err(|err| {
+        if observe {
+            observed_error(err)
+        } else {
+            err
+        }
+    })
+}
+
+/// Keeps an error from deciding the stack under `mode = "observe"`. Errors
+/// that already return `PAM_IGNORE` pass through; an interrupt still passes
+/// its signal on, but as `interrupt_action = "ignore"`.
+fn observed_error(err: AuthError) -> AuthError {
+    match err {
+        AuthError::Skipped(_)
+        | AuthError::SecretServiceUnavailable(_)
+        | AuthError::PasswordEntered(_)
+        | AuthError::Observed(_) => err,
+        AuthError::Interrupted { signal, .. } => AuthError::Interrupted {
+            signal,
+            action: InterruptAction::Ignore,
+        },
+        err => AuthError::Observed(Box::new(err)),
+    }
+}
+
+/// The part of [`authenticate_user`] that runs once the scoped configuration
+/// is known and face authentication is enabled for it.
+fn authenticate_configured(
+    config: &ResolvedConfig,
+    request: &PamRequest,
+    observe: bool,
+    audit: &mut AuditTrail,
+    logger: &mut PamLogger,
+    messenger: &mut PamConversationMessenger,
+) -> PamResult<AuthResult> {
+    let finish = |mut result: AuthResult| -> PamResult<AuthResult> {
+        result.observed = observe;
+        Ok(result)
+    };
+    logger.debug(&format!(
+        "Effective settings: threshold={} metric={:?} timeout={}s devices={} liveness={:?} anti_spoof={:?}",
+        config.similarity_threshold,
+        config.similarity_metric,
+        config.capture_timeout.as_secs(),
+        config
+            .video_devices
+            .iter()
+            .map(|device| device.device.as_str())
+            .collect::<Vec<_>>()
+            .join(","),
+        config.liveness,
+        config.anti_spoof
+    ));
+
+    // Observed attempts neither hit nor feed the lockout.
+    let faillock =
+        (config.faillock_deny > 0 && !observe).then(|| FaillockStore::new(&config.faillock_dir));
+    if let Some(store) = &faillock {
+        if let Some(locked) = check_faillock(store, config, request, logger)? {
+            return Ok(locked);
+        }
+    }
+
+    let device =
+        probe_device(config).map_err(|err| apply_device_policy(err, config, logger, messenger))?;
+    audit.set_device(device);
+    let trust = match device_trust_policy(config, request, logger)? {
+        Some(trust) => trust,
+        None => {
+            return finish(AuthResult::failure(
+                FailureReason::UntrustedDevice,
+                similarity_metric(config.similarity_metric).worst(),
+                0,
+            ))
+        }
+    };
 
     let mut embedding_key: Option<Vec<u8>> = None;
     let mut helper_env: Option<HelperEnvOverrides> = None;
@@ -395,9 +1042,9 @@ fn authenticate_user(
                     "Embedding key missing for user '{}': {message}",
                     request.user
                 ));
-                return Ok(AuthResult::failure(
+                return finish(AuthResult::failure(
                     FailureReason::EmbeddingsMissing,
-                    f64::NEG_INFINITY,
+                    similarity_metric(config.similarity_metric).worst(),
                     0,
                 ));
             }
@@ -411,491 +1058,2234 @@ fn authenticate_user(
     }
 
     let embeddings = load_embedding_store(
-        &config,
+        config,
         request,
         logger,
         &mut embedding_key,
         helper_env.as_ref(),
     )?;
     if embeddings.is_empty() {
-        return Ok(AuthResult::failure(
+        return finish(AuthResult::failure(
             FailureReason::EmbeddingsMissing,
-            f64::NEG_INFINITY,
+            similarity_metric(config.similarity_metric).worst(),
             0,
         ));
     }
 
-    let embedding_len = verify_enrolled_embeddings(&embeddings)?;
+    verify_enrolled_embeddings(&embeddings)?;
+    let enrolled: Vec<Vec<f64>> = embeddings
+        .into_iter()
+        .map(|record| record.embedding)
+        .collect();
 
-    let capture_config = build_capture_config(&config);
-    let embedder = build_embedder(&config)?;
-
-    let deadline = Instant::now() + config.capture_timeout;
-    let mut frames_captured = 0usize;
-    let mut best_similarity = f64::NEG_INFINITY;
-    let mut detected_any_face = false;
-    let mut retry_hint_sent = false;
-
-    while Instant::now() < deadline {
-        frames_captured += 1;
-        match capture_frame_in_memory(&capture_config) {
-            Ok(frame) => {
-                logger.debug(&format!(
-                    "Captured frame {} from {} ({}x{})",
-                    frames_captured, frame.device.path, frame.format.width, frame.format.height
-                ));
-                for entry in &frame.logs {
-                    logger.debug(entry);
-                }
-
-                let rgb = gray_to_rgb(&frame.image);
-                let faces = embedder.extract(&rgb, config.jitters)?;
-                if faces.is_empty() {
-                    logger.debug("No faces detected in frame");
-                    if !retry_hint_sent {
-                        messenger.send_error_msg(
-                            logger,
-                            "No face detected yet; align with the camera while we retry...",
-                        );
-                        retry_hint_sent = true;
-                    }
-                } else {
-                    detected_any_face = true;
-                    for rec in faces {
-                        if rec.embedding.len() != embedding_len {
-                            return Err(AuthError::Config(format!(
-                                "Embedding length mismatch: enrolled {} vs captured {}",
-                                embedding_len,
-                                rec.embedding.len()
-                            )));
-                        }
-                        let similarity = best_similarity_against_store(&rec.embedding, &embeddings);
-                        if similarity > best_similarity {
-                            best_similarity = similarity;
-                        }
-                        if similarity >= config.similarity_threshold {
-                            logger.info(&format!(
-                        "Detected matching embedding (similarity={similarity:.4}) after {frames_captured} frame(s)"
-                    ));
-                            return Ok(AuthResult::success(similarity, frames_captured));
-                        }
-                    }
-                }
-            }
-            Err(err) => {
-                logger.error(&format!("Failed to capture frame: {err}"));
-                return Err(AuthError::Core(err));
-            }
-        }
-
-        let now = Instant::now();
-        if now >= deadline {
-            break;
+    let mut opened = None;
+    let outcome = if config.concurrent_prompt && messenger.can_prompt() {
+        verify_with_prompt(
+            config,
+            request,
+            &trust,
+            &enrolled,
+            logger,
+            messenger,
+            &mut opened,
+        )
+    } else {
+        if config.concurrent_prompt {
+            logger.debug("concurrent_prompt set but no conversation is available; capturing first");
         }
-        if config.frame_interval > Duration::ZERO {
-            let remaining = deadline.saturating_duration_since(now);
-            let sleep_for = if config.frame_interval < remaining {
-                config.frame_interval
-            } else {
-                remaining
-            };
-            if sleep_for > Duration::ZERO {
-                sleep(sleep_for);
-            }
+        match verify_via_daemon(
+            config,
+            request,
+            &trust,
+            &enrolled,
+            logger,
+            messenger,
+            None,
+            &mut opened,
+        ) {
+            Some(result) => result,
+            None => verify_in_process(
+                config,
+                &trust,
+                &enrolled,
+                logger,
+                messenger,
+                None,
+                &mut opened,
+            ),
         }
-    }
-
-    let reason = if !detected_any_face {
-        FailureReason::NoFaceDetected
+    };
+    // A busy entry may have been passed over after the probe.
+    if let Some(device) = opened {
+        audit.set_device(device);
+    }
+    let outcome = outcome.map_err(|err| match (err, interrupt::caught()) {
+        (AuthError::Core(AppError::Cancelled), Some(signal)) => AuthError::Interrupted {
+            signal,
+            action: config.interrupt_action,
+        },
+        (err, _) => apply_device_policy(err, config, logger, messenger),
+    })?;
+    let confirmed = if outcome.success && config.confirm_match && !observe {
+        confirm_match(config, &outcome, logger, messenger)?
     } else {
-        FailureReason::ThresholdNotReached
+        true
     };
-    Ok(AuthResult::failure(
-        reason,
-        best_similarity,
-        frames_captured,
-    ))
-}
-
-fn verify_enrolled_embeddings(embeddings: &[EnrolledEmbedding]) -> PamResult<usize> {
-    let expected = embeddings
-        .first()
-        .ok_or_else(|| AuthError::Config("embedding store unexpectedly empty".into()))?
-        .embedding
-        .len();
-    if expected == 0 {
-        return Err(AuthError::Config(
-            "stored embeddings have zero length".into(),
-        ));
+    if config.adaptive_update && outcome.success && confirmed && !observe {
+        adapt_templates(config, request, embedding_key.as_deref(), &outcome, logger);
     }
-    for record in embeddings {
-        if record.embedding.len() != expected {
-            return Err(AuthError::Config(format!(
-                "embedding length mismatch: expected {}, found {}",
-                expected,
-                record.embedding.len()
-            )));
-        }
+    let mut result = AuthResult::from(outcome);
+    if !confirmed {
+        result.success = false;
+        result.failure_reason = Some(FailureReason::ConfirmationTimeout);
     }
-    Ok(expected)
-}
-
-fn best_similarity_against_store(candidate: &[f64], store: &[EnrolledEmbedding]) -> f64 {
-    let mut best = f64::NEG_INFINITY;
-    for record in store {
-        let similarity = cosine_similarity(candidate, &record.embedding);
-        if similarity > best {
-            best = similarity;
-        }
+    if let Some(store) = &faillock {
+        update_faillock(store, config, request, &result, logger);
     }
-    best
+    finish(result)
 }
 
-fn build_capture_config(config: &ResolvedConfig) -> CaptureConfig {
-    CaptureConfig {
-        device: DeviceLocator::from_option(Some(config.video_device.clone())),
-        pixel_format: config.pixel_format.clone(),
-        width: None,
-        height: None,
-        exposure: None,
-        gain: None,
-        auto_exposure: false,
-        auto_gain: false,
-        warmup_frames: config.warmup_frames,
-        output: None,
+/// Asks for Enter after a match so that merely glancing at the screen does
+/// not authenticate. Returns `false` as soon as `confirm_timeout` passes
+/// without an answer. Conversations that cannot prompt, failed prompts and
+/// typed answers skip the module rather than succeed.
+fn confirm_match(
+    config: &ResolvedConfig,
+    outcome: &VerifyOutcome,
+    logger: &mut PamLogger,
+    messenger: &mut PamConversationMessenger,
+) -> PamResult<bool> {
+    if !messenger.can_prompt() {
+        return Err(AuthError::Skipped(
+            "face matched but confirm_match is set and the conversation cannot prompt".into(),
+        ));
     }
-}
-
-fn build_embedder(config: &ResolvedConfig) -> PamResult<DlibBackend> {
-    let extractor_config = FaceExtractionConfig {
-        image: PathBuf::new(),
-        landmark_model: config.landmark_model.clone(),
-        encoder_model: config.encoder_model.clone(),
-        output: None,
-        jitters: config.jitters,
+    let prompt = messenger
+        .context
+        .render(
+            Message::ConfirmPrompt,
+            Some(outcome.best_similarity),
+            outcome.frames_captured,
+        )
+        .unwrap_or_default();
+    let started = Instant::now();
+    let Some(answer) = messenger.prompt_echo_on_within(&prompt, config.confirm_timeout) else {
+        logger.warn(&format!(
+            "Face match not confirmed within confirm_timeout_secs = {}; a later answer is ignored",
+            config.confirm_timeout.as_secs()
+        ));
+        return Ok(false);
     };
-    let models = EnvModelPathResolver
-        .resolve(&extractor_config)
-        .map_err(AuthError::from)?;
-    DlibBackend::new(&models).map_err(AuthError::from)
-}
-
-fn gray_to_rgb(image: &image::GrayImage) -> RgbImage {
-    let mut rgb = RgbImage::new(image.width(), image.height());
-    for (x, y, pixel) in rgb.enumerate_pixels_mut() {
-        let v = image.get_pixel(x, y)[0];
-        *pixel = Rgb([v, v, v]);
+    let elapsed = started.elapsed();
+    match answer {
+        Err(reason) => Err(AuthError::Skipped(format!(
+            "face matched but the confirmation prompt failed: {reason}"
+        ))),
+        Ok(Some(token)) if !token.is_empty() => Err(AuthError::Skipped(
+            "face matched but the confirmation prompt was answered with text".into(),
+        )),
+        Ok(_) => {
+            logger.info(&format!(
+                "Face match confirmed after {}ms",
+                elapsed.as_millis()
+            ));
+            Ok(true)
+        }
     }
-    rgb
 }
 
-fn load_embedding_store(
+/// Learns the matched face when it cleared the threshold by
+/// `adaptive_update_margin`. Failures are logged and never change the result.
+fn adapt_templates(
     config: &ResolvedConfig,
     request: &PamRequest,
+    embedding_key: Option<&[u8]>,
+    outcome: &VerifyOutcome,
     logger: &mut PamLogger,
-    embedding_key: &mut Option<Vec<u8>>,
-    helper_env: Option<&HelperEnvOverrides>,
-) -> PamResult<Vec<EnrolledEmbedding>> {
-    loop {
-        match load_enrolled_embeddings(
-            Some(config.embedding_store_dir.as_path()),
-            &request.user,
-            embedding_key.as_deref(),
-        ) {
-            Ok(embeddings) => return Ok(embeddings),
-            Err(AppError::EncryptedStoreRequiresKey { .. }) => {
-                if embedding_key.is_some() {
-                    return Err(AuthError::SecretServiceUnavailable(
-                        "Secret Service key failed to decrypt embedding store".into(),
-                    ));
-                }
-                match run_secret_service_helper(&request.user, config.capture_timeout, helper_env) {
-                    Ok(HelperResponse::Key(bytes)) => {
-                        logger.info(&format!(
-                            "Secret Service helper returned embedding key ({} bytes) for user '{}' via service '{}' — retrying store load",
-                            bytes.len(),
-                            request.user,
-                            default_service_name(),
-                        ));
-                        *embedding_key = Some(bytes);
-                    }
-                    Ok(HelperResponse::Missing { message }) => {
-                        logger.warn(&format!(
-                            "Embedding key missing for user '{}': {message}",
-                            request.user
-                        ));
-                        return Err(AuthError::SecretServiceUnavailable(message));
-                    }
-                    Err(err) => return Err(map_secret_helper_error(err)),
-                }
-            }
-            Err(err) => return Err(AuthError::Core(err)),
-        }
-    }
-}
-
-fn notify_secret_service_unavailable(
-    reason: &str,
-    logger: &mut PamLogger,
-    messenger: &mut PamConversationMessenger,
 ) {
-    logger.info(&format!(
-        "Secret Service unavailable; skipping face authentication: {reason}"
-    ));
-    messenger.send_error_msg(logger, SECRET_SERVICE_FALLBACK_PROMPT);
-}
-
-fn map_secret_helper_error(err: SecretHelperError) -> AuthError {
-    match err {
-        SecretHelperError::SecretServiceUnavailable(message) => {
-            AuthError::SecretServiceUnavailable(message)
-        }
-        SecretHelperError::PrivilegeDrop(failure) if failure.is_eperm() => {
-            AuthError::SecretServiceUnavailable(format!(
-                "Secret Service helper {}",
-                failure.message()
-            ))
-        }
-        SecretHelperError::PrivilegeDrop(failure) => {
-            AuthError::Pam(format!("Secret Service helper {}", failure.message()))
-        }
-        SecretHelperError::IpcFailure(message) => {
-            AuthError::Pam(format!("Secret Service helper failed: {message}"))
-        }
-    }
-}
-
-fn log_privilege_drop_context(user: &str, logger: &mut PamLogger) {
-    let Ok(Some(info)) = User::from_name(user) else {
+    let Some(face) = &outcome.matched_face else {
+        logger.debug("Adaptive update skipped: matcher did not return the face");
         return;
     };
-
-    let current_uid = geteuid();
-    let current_gid = getegid();
-    if current_uid == info.uid && current_gid == info.gid {
-        logger.info(&format!(
-            "Secret Service helper privilege drop skipped for user '{user}': already running as target uid/gid"
+    let policy = AdaptiveUpdatePolicy {
+        margin: config.adaptive_update_margin,
+        max_entries: config.adaptive_update_max_entries as usize,
+    };
+    let metric = similarity_metric(config.similarity_metric);
+    if !policy.qualifies(metric, outcome.best_similarity, config.similarity_threshold) {
+        logger.debug(&format!(
+            "Adaptive update skipped: similarity {:.4} within {} of the threshold",
+            outcome.best_similarity, policy.margin
         ));
+        return;
     }
-}
-
-fn load_config() -> PamResult<ResolvedConfigWithSource> {
-    chissu_config::load_resolved_config().map_err(map_config_error)
-}
-
-fn prepare_helper_env(
-    request: &PamRequest,
-    session_mode: SecretServiceSessionMode,
-    logger: &mut PamLogger,
-) -> Option<HelperEnvOverrides> {
-    let uid = match lookup_uid(&request.user) {
-        Ok(uid) => uid,
+    let path = user_store_path(Some(config.embedding_store_dir.as_path()), &request.user);
+    match apply_adaptive_update(&FilesystemFaceStore, &path, embedding_key, face, &policy) {
+        Ok(update) => {
+            logger.info(&format!(
+                "Adaptive update added embedding {} for user '{}' ({} auto-added kept, {} replaced)",
+                update.added_id,
+                request.user,
+                update.auto_entries,
+                update.evicted_ids.len()
+            ));
+        }
         Err(err) => {
             logger.warn(&format!(
-                "Unable to resolve UID for user '{}' while preparing Secret Service env: {err}",
+                "Adaptive update failed for user '{}': {err}",
                 request.user
             ));
-            return None;
         }
-    };
+    }
+}
 
-    let inspector = LogindInspector::new();
-    match inspector.inspect(uid, request.tty.as_deref()) {
-        Ok(Some(env)) => {
-            let effective_mode = env.effective_session_mode(session_mode);
-            let pairs: Vec<(String, String)> = env
-                .env_pairs(session_mode)
-                .into_iter()
-                .filter(|(key, value)| should_apply_env_override(key, value))
-                .collect();
-            let removals = opposite_session_env_removals(effective_mode);
-            if pairs.is_empty() && removals.is_empty() {
-                logger.debug(
-                    "Logind session found but no Secret Service environment overrides were needed",
-                );
-                None
-            } else {
-                let applied = pairs
-                    .iter()
-                    .map(|(key, _)| key.as_str())
-                    .collect::<Vec<_>>()
-                    .join(",");
-                let removed = removals
-                    .iter()
-                    .map(String::as_str)
-                    .collect::<Vec<_>>()
-                    .join(",");
-                logger.info(&format!(
-                    "Recovered Secret Service session environment from logind for user '{}': {} configured_mode={} selected_mode={} applied={} removed={}",
-                    request.user,
-                    env.summary(),
-                    session_mode_name(session_mode),
-                    effective_mode.as_str(),
-                    applied,
-                    removed,
-                ));
-                Some(HelperEnvOverrides::from_parts(pairs, removals))
-            }
-        }
-        Ok(None) => {
-            logger.warn(&format!(
-                "No active logind session for user '{}' (tty hint {})",
-                request.user,
-                request.tty.as_deref().unwrap_or("-")
+/// Builds the camera checks for this request. Returns `None` when
+/// `device_pin = "required"` and the user has no pin.
+fn device_trust_policy(
+    config: &ResolvedConfig,
+    request: &PamRequest,
+    logger: &mut PamLogger,
+) -> PamResult<Option<DeviceTrustPolicy>> {
+    let mut trust = DeviceTrustPolicy {
+        pinned: None,
+        denied_drivers: config.virtual_camera_drivers.clone(),
+    };
+    if config.device_pin == DevicePinMode::Off {
+        return Ok(Some(trust));
+    }
+    let store = DevicePinStore::new(&config.device_pin_dir);
+    match store.load(&request.user)? {
+        Some(pin) => {
+            logger.debug(&format!(
+                "Camera pinned for '{}': {}",
+                request.user, pin.identity
             ));
-            None
+            trust.pinned = Some(pin.identity);
         }
-        Err(err) => {
+        None if config.device_pin == DevicePinMode::Required => {
             logger.warn(&format!(
-                "Failed to query logind for user '{}': {err}",
+                "No camera pinned for user '{}' and device_pin = \"required\"",
                 request.user
             ));
-            None
+            return Ok(None);
         }
+        None => logger.debug(&format!("No camera pinned for '{}'", request.user)),
     }
+    Ok(Some(trust))
 }
 
-fn lookup_uid(user: &str) -> Result<u32, String> {
-    match User::from_name(user).map_err(|err| format!("failed to resolve user '{user}': {err}"))? {
-        Some(info) => Ok(info.uid.as_raw()),
-        None => Err(format!("user '{user}' not found")),
-    }
-}
-
-fn map_config_error(err: ConfigError) -> AuthError {
-    match err {
-        ConfigError::Read { path, source } => {
-            AuthError::Config(format!("Failed to read {}: {}", path.display(), source))
-        }
-        ConfigError::Parse { path, message } => {
-            AuthError::Config(format!("Failed to parse {}: {}", path.display(), message))
+/// Fails fast when the configured device node does not exist, before the
+/// Secret Service round trip and model loading. Returns the first camera
+/// that is present, which capture will try first.
+fn probe_device(config: &ResolvedConfig) -> PamResult<String> {
+    let mut missing = Vec::new();
+    for device in &config.video_devices {
+        let path = match DeviceLocator::from_option(Some(device.device.clone())).path() {
+            Ok(path) => path,
+            Err(err) if err.device_fault() == Some(DeviceFault::Absent) => {
+                missing.push(err.to_string());
+                continue;
+            }
+            // Let the capture path report anything else, e.g. udev being
+            // unavailable inside a restricted service.
+            Err(_) => return Ok(device.device.clone()),
+        };
+        match fs::metadata(&path) {
+            Err(err) if DeviceFault::from_io(&err) == Some(DeviceFault::Absent) => {
+                missing.push(format!("{}: {err}", path.display()));
+            }
+            _ => return Ok(path.display().to_string()),
         }
     }
+    Err(AuthError::DeviceUnavailable {
+        fault: DeviceFault::Absent,
+        message: missing.join("; "),
+    })
 }
 
-fn env_var_missing(name: &str) -> bool {
-    match env::var_os(name) {
-        Some(value) => value.is_empty(),
-        None => true,
+/// Turns a camera-unavailable error into a quick `PAM_IGNORE` with a short
+/// notice when the matching `device_*_action` is `ignore`. Every other error
+/// is returned unchanged.
+fn apply_device_policy(
+    err: AuthError,
+    config: &ResolvedConfig,
+    logger: &mut PamLogger,
+    messenger: &mut PamConversationMessenger,
+) -> AuthError {
+    let AuthError::DeviceUnavailable { fault, message } = err else {
+        return err;
+    };
+    let (key, action) = match fault {
+        DeviceFault::Absent => ("device_absent_action", config.device_absent_action),
+        DeviceFault::Busy => ("device_busy_action", config.device_busy_action),
+        DeviceFault::PermissionDenied => ("device_denied_action", config.device_denied_action),
+    };
+    match action {
+        DeviceErrorAction::Error => AuthError::DeviceUnavailable { fault, message },
+        DeviceErrorAction::Ignore => {
+            messenger.notify(logger, Message::DeviceUnavailable, None, 0);
+            AuthError::Skipped(format!(
+                "camera {}: {message} ({key} = \"ignore\")",
+                fault.describe()
+            ))
+        }
     }
 }
 
-fn should_apply_env_override(key: &str, value: &str) -> bool {
-    match key {
-        "DISPLAY" | "WAYLAND_DISPLAY" => env_var_missing(key),
-        "XDG_RUNTIME_DIR" => env::var(key).map_or(true, |current| current != value),
-        "DBUS_SESSION_BUS_ADDRESS" => dbus_env_needs_override(value),
-        _ => false,
-    }
+/// Gathers the context of one `pam_sm_authenticate` call for its audit record.
+/// Nothing is written until the configuration enabling the log has loaded.
+struct AuditTrail {
+    started: Instant,
+    log: Option<AuditLog>,
+    similarity_threshold: f64,
+    device: String,
+    /// `mode = "observe"`, which also marks records of attempts that errored.
+    observed: bool,
 }
 
-fn opposite_session_env_removals(mode: EffectiveSessionMode) -> Vec<String> {
-    match mode {
-        EffectiveSessionMode::X11 if env_var_present("WAYLAND_DISPLAY") => {
-            vec!["WAYLAND_DISPLAY".into()]
+impl AuditTrail {
+    fn new() -> Self {
+        let defaults = ResolvedConfig::default();
+        Self {
+            started: Instant::now(),
+            log: None,
+            similarity_threshold: defaults.similarity_threshold,
+            device: defaults.video_device,
+            observed: false,
         }
-        EffectiveSessionMode::Wayland if env_var_present("DISPLAY") => vec!["DISPLAY".into()],
-        _ => Vec::new(),
     }
-}
 
-fn dbus_env_needs_override(target_address: &str) -> bool {
-    match env::var("DBUS_SESSION_BUS_ADDRESS") {
-        Ok(current) => {
-            let trimmed = current.trim();
-            trimmed.is_empty() || trimmed.starts_with("autolaunch:") || trimmed != target_address
-        }
-        Err(_) => true,
+    fn configure(&mut self, config: &ResolvedConfig) {
+        self.log = config
+            .audit_log
+            .as_ref()
+            .map(|path| AuditLog::new(path, config.audit_log_max_bytes, config.audit_log_keep));
+        self.similarity_threshold = config.similarity_threshold;
+        self.device = config.video_device.clone();
+        self.observed = config.mode == AuthMode::Observe;
     }
-}
 
-fn env_var_present(name: &str) -> bool {
-    match env::var_os(name) {
-        Some(value) => !value.is_empty(),
-        None => false,
+    /// Names the camera actually used, which may be a later
+    /// `video_devices` entry than the first.
+    fn set_device(&mut self, device: String) {
+        self.device = device;
     }
-}
 
-fn session_mode_name(mode: SecretServiceSessionMode) -> &'static str {
-    match mode {
-        SecretServiceSessionMode::Auto => "auto",
-        SecretServiceSessionMode::X11 => "x11",
-        SecretServiceSessionMode::Wayland => "wayland",
+    fn record(
+        &self,
+        request: &PamRequest,
+        outcome: AuditOutcome,
+        result: Option<&AuthResult>,
+        detail: Option<String>,
+        logger: &mut PamLogger,
+    ) {
+        let Some(log) = &self.log else {
+            return;
+        };
+        let record = AuditRecord {
+            timestamp: audit_timestamp(),
+            service: request.service.clone(),
+            user: request.user.clone(),
+            tty: request.tty.clone(),
+            outcome,
+            observed: self.observed,
+            failure_reason: result
+                .and_then(|result| result.failure_reason)
+                .map(|reason| reason.as_str().to_string()),
+            detail,
+            frames_captured: result.map_or(0, |result| result.frames_captured),
+            best_similarity: result
+                .map(|result| result.best_similarity)
+                .filter(|similarity| similarity.is_finite()),
+            similarity_threshold: self.similarity_threshold,
+            device: self.device.clone(),
+            duration_ms: self.started.elapsed().as_millis() as u64,
+        };
+        if let Err(err) = log.append(&record) {
+            logger.warn(&format!("Failed to write audit record: {err}"));
+        }
     }
 }
 
-unsafe fn get_user_name(pamh: *mut PamHandle) -> PamResult<String> {
-    if pamh.is_null() {
-        return Err(AuthError::Pam("pam handle was null".into()));
-    }
-    let handle = &*pamh;
-    let mut ptr: *const c_char = ptr::null();
-    let rc = get_user(handle, &mut ptr, ptr::null());
-    if rc != PamReturnCode::SUCCESS {
-        return Err(AuthError::Pam(format!("pam_get_user failed: {rc}")));
+/// Skips face authentication for remote or seatless callers unless
+/// `session_policy` allows them, so the local camera never approves a
+/// login made from elsewhere.
+fn check_session_policy(
+    config: &ResolvedConfig,
+    request: &PamRequest,
+    logger: &mut PamLogger,
+) -> PamResult<()> {
+    let policy = config.session_policy;
+    if policy == SessionPolicy::Any {
+        logger.debug("Session policy 'any'; remote and seat checks disabled");
+        return Ok(());
     }
-    if ptr.is_null() {
-        return Err(AuthError::Pam("pam_get_user returned null".into()));
+
+    let remote_rhost = request.rhost.as_deref().is_some_and(is_remote_host);
+    let session = if remote_rhost {
+        None
+    } else {
+        match LogindInspector::new().session_of_process(std::process::id()) {
+            Ok(session) => session,
+            Err(err) => {
+                logger.warn(&format!(
+                    "Could not look up the calling logind session; relying on PAM_RHOST only: {err}"
+                ));
+                None
+            }
+        }
+    };
+
+    if let Some(reason) = session_skip_reason(policy, request.rhost.as_deref(), session.as_ref()) {
+        return Err(AuthError::Skipped(format!(
+            "{reason} (session_policy = \"{}\")",
+            session_policy_name(policy)
+        )));
     }
-    Ok(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+    let origin = match &session {
+        Some(session) => format!(
+            "session {} on {}",
+            session.session_id,
+            session.seat.as_deref().unwrap_or("no seat")
+        ),
+        None => "no logind session".to_string(),
+    };
+    logger.info(&format!(
+        "Session policy '{}' allows camera use: rhost={} {origin}",
+        session_policy_name(policy),
+        request.rhost.as_deref().unwrap_or("-")
+    ));
+    Ok(())
 }
 
-unsafe fn get_tty_name(pamh: *mut PamHandle) -> Option<String> {
-    if pamh.is_null() {
+fn session_skip_reason(
+    policy: SessionPolicy,
+    rhost: Option<&str>,
+    session: Option<&ProcessSession>,
+) -> Option<String> {
+    if policy == SessionPolicy::Any {
         return None;
     }
-    let handle = &*pamh;
-    let mut ptr: *const c_void = ptr::null();
-    let rc = get_item(handle, PamItemType::TTY, &mut ptr);
-    if rc != PamReturnCode::SUCCESS || ptr.is_null() {
-        return None;
+    if let Some(host) = rhost.filter(|host| is_remote_host(host)) {
+        return Some(format!("remote request from PAM_RHOST '{}'", host.trim()));
+    }
+    let session = session?;
+    if session.remote {
+        let from = session
+            .remote_host
+            .as_deref()
+            .map(|host| format!(" from '{host}'"))
+            .unwrap_or_default();
+        return Some(format!(
+            "logind session {} is remote{from}",
+            session.session_id
+        ));
     }
-    let raw = CStr::from_ptr(ptr as *const c_char).to_string_lossy();
-    let value = raw.trim();
-    if value.is_empty() {
-        None
-    } else {
-        Some(value.to_string())
+    if policy == SessionPolicy::Local && session.seat.is_none() {
+        return Some(format!("logind session {} has no seat", session.session_id));
     }
+    None
 }
 
-unsafe fn get_service_name(pamh: *mut PamHandle) -> PamResult<String> {
-    if pamh.is_null() {
-        return Err(AuthError::Pam("pam handle was null".into()));
+/// Loopback names count as local so `ssh localhost` behaves like a terminal.
+fn is_remote_host(host: &str) -> bool {
+    let host = host.trim();
+    !host.is_empty() && !matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+fn session_policy_name(policy: SessionPolicy) -> &'static str {
+    match policy {
+        SessionPolicy::Local => "local",
+        SessionPolicy::NoRemote => "no_remote",
+        SessionPolicy::Any => "any",
+    }
+}
+
+/// Refuses the attempt before any capture when the user is locked out.
+fn check_faillock(
+    store: &FaillockStore,
+    config: &ResolvedConfig,
+    request: &PamRequest,
+    logger: &mut PamLogger,
+) -> PamResult<Option<AuthResult>> {
+    // An unreadable tally counts as locked, so breaking the file cannot
+    // lift the lockout. Clearing the tally removes the file.
+    let reason = match store.load(&request.user) {
+        Ok(tally) => {
+            let Some(remaining) = tally.remaining_lock(
+                config.faillock_deny,
+                config.faillock_window,
+                SystemTime::now(),
+            ) else {
+                return Ok(None);
+            };
+            format!(
+                "user '{}' locked out after {} failed face attempts; unlocks in {remaining}s or after a password login",
+                request.user, config.faillock_deny
+            )
+        }
+        Err(err) => format!(
+            "user '{}' treated as locked out, faillock tally unreadable ({err}); unlocks once the tally is cleared",
+            request.user
+        ),
+    };
+    match config.faillock_action {
+        FaillockAction::Ignore => Err(AuthError::Skipped(reason)),
+        FaillockAction::Deny => {
+            logger.warn(&format!("Face authentication refused: {reason}"));
+            Ok(Some(AuthResult::failure(
+                FailureReason::LockedOut,
+                similarity_metric(config.similarity_metric).worst(),
+                0,
+            )))
+        }
+    }
+}
+
+/// Clears the tally on success and records failures that reached the camera.
+fn update_faillock(
+    store: &FaillockStore,
+    config: &ResolvedConfig,
+    request: &PamRequest,
+    result: &AuthResult,
+    logger: &mut PamLogger,
+) {
+    if result.success {
+        if let Err(err) = store.reset(&request.user) {
+            logger.warn(&format!("Failed to reset faillock tally: {err}"));
+        }
+        return;
+    }
+    if !result
+        .failure_reason
+        .is_some_and(FailureReason::counts_towards_faillock)
+    {
+        return;
+    }
+
+    let now = SystemTime::now();
+    match store.record_failure(&request.user, config.faillock_window, now) {
+        Ok(tally) => {
+            let recent = tally.recent(config.faillock_window, now);
+            logger.info(&format!(
+                "Recorded failed face attempt {recent}/{} for user '{}'",
+                config.faillock_deny, request.user
+            ));
+            if tally.is_locked(config.faillock_deny, config.faillock_window, now) {
+                logger.warn(&format!(
+                    "User '{}' is now locked out of face authentication for up to {}s",
+                    request.user,
+                    config.faillock_window.as_secs()
+                ));
+            }
+        }
+        Err(err) => logger.warn(&format!("Failed to record faillock tally: {err}")),
+    }
+}
+
+/// Runs the camera on a worker thread while a `PAM_PROMPT_ECHO_OFF` prompt is
+/// open. A face match wins; a typed password cancels the camera and comes
+/// back as [`AuthError::PasswordEntered`]; a bare Enter skips the module.
+fn verify_with_prompt(
+    config: &ResolvedConfig,
+    request: &PamRequest,
+    trust: &DeviceTrustPolicy,
+    enrolled: &[Vec<f64>],
+    logger: &mut PamLogger,
+    messenger: &mut PamConversationMessenger,
+    opened: &mut Option<String>,
+) -> PamResult<VerifyOutcome> {
+    let prompt = messenger
+        .context
+        .render(Message::PasswordPrompt, None, 0)
+        .unwrap_or_default();
+    let mut worker_logger = logger.worker();
+    logger.debug("Opening concurrent password prompt while the camera runs");
+
+    let (answer, face) = race_prompt(
+        move |cancel| {
+            // Hints would interleave with the open prompt, so the worker stays quiet.
+            let mut quiet = PamConversationMessenger::without_callback();
+            let logger = &mut worker_logger;
+            let result = match verify_via_daemon(
+                config,
+                request,
+                trust,
+                enrolled,
+                logger,
+                &mut quiet,
+                Some(cancel),
+                opened,
+            ) {
+                Some(result) => result,
+                None => verify_in_process(
+                    config,
+                    trust,
+                    enrolled,
+                    logger,
+                    &mut quiet,
+                    Some(cancel),
+                    opened,
+                ),
+            };
+            if matches!(&result, Ok(outcome) if outcome.success) {
+                logger.info(
+                    "Face matched while the password prompt is open; PAM cannot close the prompt, so the module returns once it is answered",
+                );
+            }
+            result
+        },
+        || messenger.prompt_echo_off(&prompt),
+    );
+    settle_prompt_race(answer, face, logger)
+}
+
+/// Combines the prompt answer and the camera result of [`race_prompt`].
+fn settle_prompt_race(
+    answer: Result<Option<AuthToken>, String>,
+    face: PamResult<VerifyOutcome>,
+    logger: &mut PamLogger,
+) -> PamResult<VerifyOutcome> {
+    match (answer, face) {
+        (_, Ok(outcome)) if outcome.success => Ok(outcome),
+        (Ok(Some(token)), _) if !token.is_empty() => Err(AuthError::PasswordEntered(token)),
+        (_, face @ Err(AuthError::Core(AppError::Cancelled))) if interrupt::caught().is_some() => {
+            face
+        }
+        (Ok(_), _) => Err(AuthError::Skipped(
+            "password prompt answered without a password; camera cancelled".into(),
+        )),
+        (Err(reason), face) => {
+            logger.warn(&format!(
+                "Concurrent password prompt failed ({reason}); using the camera result"
+            ));
+            face
+        }
+    }
+}
+
+/// Runs `worker` on a scoped thread while `prompt` blocks on the calling
+/// thread. Once the prompt is answered the worker is cancelled; if the prompt
+/// itself fails the worker runs to its deadline.
+fn race_prompt<W, P>(
+    worker: W,
+    prompt: P,
+) -> (Result<Option<AuthToken>, String>, PamResult<VerifyOutcome>)
+where
+    W: FnOnce(&AtomicBool) -> PamResult<VerifyOutcome> + Send,
+    P: FnOnce() -> Result<Option<AuthToken>, String>,
+{
+    let cancel = AtomicBool::new(false);
+    thread::scope(|scope| {
+        let handle = scope.spawn(|| worker(&cancel));
+        let answer = prompt();
+        if answer.is_ok() {
+            cancel.store(true, Ordering::SeqCst);
+        }
+        let face = handle
+            .join()
+            .unwrap_or_else(|_| Err(AuthError::Pam("capture worker panicked".into())));
+        (answer, face)
+    })
+}
+
+/// Hands matching to `chissu-authd` when a trusted daemon is listening.
+/// Returns `None` when the caller should match in-process instead.
+/// `opened` receives the camera the daemon reports it opened.
+#[allow(clippy::too_many_arguments)]
+fn verify_via_daemon(
+    config: &ResolvedConfig,
+    request: &PamRequest,
+    trust: &DeviceTrustPolicy,
+    enrolled: &[Vec<f64>],
+    logger: &mut PamLogger,
+    messenger: &mut PamConversationMessenger,
+    cancel: Option<&AtomicBool>,
+    opened: &mut Option<String>,
+) -> Option<PamResult<VerifyOutcome>> {
+    let client = AuthdClient::new(&config.authd_socket);
+    let authd_request = VerifyRequest {
+        version: PROTOCOL_VERSION,
+        user: request.user.clone(),
+        service: request.service.clone(),
+        devices: config
+            .video_devices
+            .iter()
+            .map(|device| RequestedDevice {
+                video_device: device.device.clone(),
+                pixel_format: device.pixel_format.clone(),
+                warmup_frames: device.warmup_frames,
+            })
+            .collect(),
+        similarity_threshold: config.similarity_threshold,
+        metric: similarity_metric(config.similarity_metric),
+        capture_timeout_millis: config.capture_timeout.as_millis() as u64,
+        frame_interval_millis: config.frame_interval.as_millis() as u64,
+        jitters: config.jitters,
+        liveness: liveness_mode(config.liveness),
+        anti_spoof: anti_spoof_sensitivity(config.anti_spoof),
+        face_selection: face_selection_policy(config),
+        fusion: fusion_policy(config),
+        device_trust: trust.clone(),
+        require_ir: config.require_ir,
+        stream_integrity: config.stream_integrity,
+        enrolled: enrolled.to_vec(),
+    };
+    let result = {
+        let deadline = Instant::now() + config.capture_timeout;
+        let mut observer = PamVerifyObserver::new(logger, messenger)
+            .with_cancel(cancel)
+            .with_progress(config, deadline);
+        let result = client.verify(&authd_request, &mut observer);
+        *opened = observer.device.take();
+        result
+    };
+    match result {
+        Ok(outcome) => {
+            logger.debug(&format!(
+                "Matched via chissu-authd at {}",
+                client.socket().display()
+            ));
+            Some(Ok(outcome))
+        }
+        Err(err @ AuthdClientError::Unavailable { .. }) => {
+            logger.debug(&format!("{err}; matching in-process"));
+            None
+        }
+        Err(AuthdClientError::Device { fault, message }) => {
+            Some(Err(AuthError::DeviceUnavailable { fault, message }))
+        }
+        Err(AuthdClientError::Cancelled) => Some(Err(AuthError::Core(AppError::Cancelled))),
+        Err(err) => Some(Err(AuthError::Daemon(err.to_string()))),
+    }
+}
+
+fn verify_in_process(
+    config: &ResolvedConfig,
+    trust: &DeviceTrustPolicy,
+    enrolled: &[Vec<f64>],
+    logger: &mut PamLogger,
+    messenger: &mut PamConversationMessenger,
+    cancel: Option<&AtomicBool>,
+    opened: &mut Option<String>,
+) -> PamResult<VerifyOutcome> {
+    let capture_configs = build_capture_configs(config);
+
+    let deadline = Instant::now() + config.capture_timeout;
+    // Open the camera before loading the models so a missing or busy device
+    // is reported without the model load delay.
+    let FirstAvailable {
+        opened: mut session,
+        skipped,
+        ..
+    } = match open_first_available(&capture_configs, CaptureSession::open) {
+        Ok(opened) => opened,
+        Err(err) => {
+            logger.error(&format!("Failed to open capture session: {err}"));
+            return Err(AuthError::from_capture(err));
+        }
+    };
+    *opened = Some(session.device().path.clone());
+    let selected = VerifyEvent::DeviceSelected {
+        device: session.device().path.clone(),
+        skipped,
+    };
+    let embedder = build_embedder(config)?;
+    for entry in session.take_logs() {
+        logger.debug(&entry);
+    }
+    logger.debug(&format!(
+        "Capture session open on {} ({} {}x{})",
+        session.device().path,
+        session.format().pixel_format,
+        session.format().width,
+        session.format().height
+    ));
+    if let Err(rejection) = trust.check(Some(session.device())) {
+        logger.warn(&format!(
+            "Refusing camera {}: {rejection}",
+            session.device().path
+        ));
+        return Ok(VerifyOutcome::rejected(
+            VerifyFailure::UntrustedDevice,
+            similarity_metric(config.similarity_metric),
+        ));
+    }
+
+    let settings = VerifySettings {
+        similarity_threshold: config.similarity_threshold,
+        metric: similarity_metric(config.similarity_metric),
+        frame_interval: config.frame_interval,
+        jitters: config.jitters,
+        liveness: liveness_mode(config.liveness),
+        anti_spoof: anti_spoof_sensitivity(config.anti_spoof),
+        selection: face_selection_policy(config),
+        fusion: fusion_policy(config),
+        require_ir: config.require_ir,
+        stream_integrity: config.stream_integrity,
+    };
+    let mut observer = PamVerifyObserver::new(logger, messenger)
+        .with_cancel(cancel)
+        .with_progress(config, deadline);
+    observer.on_event(&selected);
+    verify_live(
+        &mut session,
+        &embedder,
+        enrolled,
+        &settings,
+        deadline,
+        &mut observer,
+    )
+    .map_err(|err| {
+        if matches!(err, AppError::Cancelled) {
+            observer.logger.debug("Capture cancelled");
+        } else {
+            observer
+                .logger
+                .error(&format!("Failed to capture frame: {err}"));
+        }
+        AuthError::from_capture(err)
+    })
+}
+
+/// Mirrors verification progress into syslog and the PAM conversation.
+struct PamVerifyObserver<'a> {
+    logger: &'a mut PamLogger,
+    messenger: &'a mut PamConversationMessenger,
+    retry_hint_sent: bool,
+    liveness_hint_sent: bool,
+    cancel: Option<&'a AtomicBool>,
+    progress: Option<ProgressReporter>,
+    /// The camera named by the last `DeviceSelected` event.
+    device: Option<String>,
+}
+
+impl<'a> PamVerifyObserver<'a> {
+    fn new(logger: &'a mut PamLogger, messenger: &'a mut PamConversationMessenger) -> Self {
+        Self {
+            logger,
+            messenger,
+            retry_hint_sent: false,
+            liveness_hint_sent: false,
+            cancel: None,
+            progress: None,
+            device: None,
+        }
+    }
+
+    fn with_cancel(mut self, cancel: Option<&'a AtomicBool>) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Sends progress messages until `deadline`, unless the service turned
+    /// them off with `progress_messages = false`.
+    fn with_progress(mut self, config: &ResolvedConfig, deadline: Instant) -> Self {
+        self.progress = config
+            .progress_messages
+            .then(|| ProgressReporter::new(config.progress_interval, deadline, Instant::now()));
+        self
+    }
+
+    /// Sends a one-off hint and holds back the next progress message.
+    fn hint(&mut self, message: Message, similarity: Option<f64>, frame: usize) {
+        self.messenger
+            .notify(self.logger, message, similarity, frame);
+        if let Some(progress) = self.progress.as_mut() {
+            progress.sent(message, Instant::now());
+        }
+    }
+}
+
+impl VerifyObserver for PamVerifyObserver<'_> {
+    fn on_event(&mut self, event: &VerifyEvent) {
+        match event {
+            VerifyEvent::DeviceSelected { device, skipped } => {
+                for entry in skipped {
+                    self.logger.info(&format!(
+                        "Skipping camera {}: {}",
+                        entry.device,
+                        entry.fault.describe()
+                    ));
+                }
+                self.logger.info(&format!("Using camera {device}"));
+                self.device = Some(device.clone());
+            }
+            VerifyEvent::FrameCaptured {
+                frame,
+                width,
+                height,
+            } => {
+                self.logger
+                    .debug(&format!("Captured frame {frame} ({width}x{height})"));
+                if let Some(progress) = self.progress.as_mut() {
+                    progress.frame_captured(*width, *height);
+                }
+            }
+            VerifyEvent::FrameAssessed {
+                frame,
+                mean_intensity,
+                face_size,
+            } => {
+                let size = face_size
+                    .map(|size| format!("{size}px"))
+                    .unwrap_or_else(|| "n/a".into());
+                self.logger.debug(&format!(
+                    "Frame {frame}: mean={mean_intensity:.1} face={size}"
+                ));
+                if let Some(progress) = self.progress.as_mut() {
+                    let now = Instant::now();
+                    if let Some(message) = progress.assess(*mean_intensity, *face_size, now) {
+                        self.messenger.context.remaining_secs = Some(progress.remaining_secs(now));
+                        self.messenger.notify(self.logger, message, None, *frame);
+                    }
+                }
+            }
+            VerifyEvent::StreamRejected { frame, anomaly } => {
+                self.logger
+                    .warn(&format!("Refusing stream at frame {frame}: {anomaly}"));
+            }
+            VerifyEvent::IrFormatRejected { pixel_format } => {
+                self.logger.warn(&format!(
+                    "Refusing stream: pixel format {} is not a monochrome IR format",
+                    pixel_format.as_deref().unwrap_or("unknown")
+                ));
+            }
+            VerifyEvent::IrScored {
+                frame,
+                stats,
+                illuminated,
+            } => {
+                let summary = format!(
+                    "mean={:.1} dark={:.3} contrast={:.1}",
+                    stats.mean_intensity, stats.dark_fraction, stats.contrast
+                );
+                if *illuminated {
+                    self.logger
+                        .debug(&format!("Frame {frame} shows IR illumination: {summary}"));
+                } else {
+                    self.logger.info(&format!(
+                        "Frame {frame} shows no IR illumination; not matched: {summary}"
+                    ));
+                }
+            }
+            VerifyEvent::NoFaceDetected { frame } => {
+                self.logger.debug("No faces detected in frame");
+                if !self.retry_hint_sent {
+                    self.hint(Message::NoFaceHint, None, *frame);
+                    self.retry_hint_sent = true;
+                }
+            }
+            VerifyEvent::FacesRejected { frame, rejected } => {
+                for rejection in rejected {
+                    self.logger.info(&format!(
+                        "Frame {frame}: face skipped by selection policy ({})",
+                        rejection.describe()
+                    ));
+                }
+            }
+            VerifyEvent::SpoofScored {
+                frame,
+                scores,
+                verdict,
+            } => {
+                let summary = format!(
+                    "mean={:.1} dark={:.3} texture={:.2} specular={:.3}",
+                    scores.mean_intensity,
+                    scores.dark_fraction,
+                    scores.texture,
+                    scores.specular_fraction
+                );
+                match verdict {
+                    Some(verdict) => self.logger.warn(&format!(
+                        "Frame {frame} rejected by anti-spoof check ({verdict:?}): {summary}"
+                    )),
+                    None => self
+                        .logger
+                        .info(&format!("Frame {frame} anti-spoof scores: {summary}")),
+                }
+            }
+            VerifyEvent::FaceShifted { frame, shift } => {
+                self.logger.info(&format!(
+                    "Frame {frame}: matched face moved by {shift:.2} face widths; fusion window restarted"
+                ));
+            }
+            VerifyEvent::FusionPending {
+                frame,
+                similarity,
+                matching_frames,
+                window,
+            } => {
+                self.logger.debug(&format!(
+                    "Frame {frame} matched (similarity={similarity:.4}); {matching_frames} matching frame(s) in a window of {window}"
+                ));
+            }
+            VerifyEvent::LivenessPending {
+                frame,
+                similarity,
+                eye_aspect_ratio,
+            } => {
+                let ear = eye_aspect_ratio
+                    .map(|ear| format!("{ear:.3}"))
+                    .unwrap_or_else(|| "n/a".into());
+                self.logger.debug(&format!(
+                    "Frame {frame} matched (similarity={similarity:.4}) but liveness is pending (ear={ear})"
+                ));
+                if !self.liveness_hint_sent {
+                    self.hint(Message::LivenessHint, Some(*similarity), *frame);
+                    self.liveness_hint_sent = true;
+                }
+            }
+            VerifyEvent::Matched { frame, similarity } => {
+                self.logger.info(&format!(
+                    "Detected matching embedding (similarity={similarity:.4}) after {frame} frame(s)"
+                ));
+            }
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .is_some_and(|cancel| cancel.load(Ordering::SeqCst))
+            || interrupt::caught().is_some()
+    }
+}
+
+fn liveness_mode(mode: ConfigLivenessMode) -> LivenessMode {
+    match mode {
+        ConfigLivenessMode::Off => LivenessMode::Off,
+        ConfigLivenessMode::Blink => LivenessMode::Blink,
+        ConfigLivenessMode::EyeMotion => LivenessMode::EyeMotion,
+    }
+}
+
+fn face_selection_policy(config: &ResolvedConfig) -> FaceSelectionPolicy {
+    FaceSelectionPolicy {
+        mode: match config.face_selection {
+            ConfigFaceSelection::All => FaceSelectionMode::All,
+            ConfigFaceSelection::Largest => FaceSelectionMode::Largest,
+            ConfigFaceSelection::RejectMultiple => FaceSelectionMode::RejectMultiple,
+        },
+        min_face_size: config.face_min_size,
+        max_center_offset: config.face_max_center_offset,
+    }
+}
+
+fn similarity_metric(metric: ConfigSimilarityMetric) -> SimilarityMetric {
+    match metric {
+        ConfigSimilarityMetric::Cosine => SimilarityMetric::Cosine,
+        ConfigSimilarityMetric::Euclidean => SimilarityMetric::Euclidean,
+        ConfigSimilarityMetric::NormalizedEuclidean => SimilarityMetric::NormalizedEuclidean,
+    }
+}
+
+fn fusion_policy(config: &ResolvedConfig) -> FusionPolicy {
+    FusionPolicy {
+        mode: match config.fusion {
+            ConfigFusion::Single => FusionMode::Single,
+            ConfigFusion::KOfN => FusionMode::KOfN,
+            ConfigFusion::Average => FusionMode::Average,
+        },
+        window: config.fusion_window,
+        required: config.fusion_required,
+        max_face_shift: config.fusion_max_face_shift,
+    }
+}
+
+fn anti_spoof_sensitivity(sensitivity: ConfigAntiSpoof) -> AntiSpoofSensitivity {
+    match sensitivity {
+        ConfigAntiSpoof::Off => AntiSpoofSensitivity::Off,
+        ConfigAntiSpoof::Low => AntiSpoofSensitivity::Low,
+        ConfigAntiSpoof::Medium => AntiSpoofSensitivity::Medium,
+        ConfigAntiSpoof::High => AntiSpoofSensitivity::High,
+    }
+}
+
+fn verify_enrolled_embeddings(embeddings: &[EnrolledEmbedding]) -> PamResult<usize> {
+    let expected = embeddings
+        .first()
+        .ok_or_else(|| AuthError::Config("embedding store unexpectedly empty".into()))?
+        .embedding
+        .len();
+    if expected == 0 {
+        return Err(AuthError::Config(
+            "stored embeddings have zero length".into(),
+        ));
+    }
+    for record in embeddings {
+        if record.embedding.len() != expected {
+            return Err(AuthError::Config(format!(
+                "embedding length mismatch: expected {}, found {}",
+                expected,
+                record.embedding.len()
+            )));
+        }
+    }
+    Ok(expected)
+}
+
+fn build_capture_configs(config: &ResolvedConfig) -> Vec<CaptureConfig> {
+    config
+        .video_devices
+        .iter()
+        .map(|device| CaptureConfig {
+            device: DeviceLocator::from_option(Some(device.device.clone())),
+            pixel_format: device.pixel_format.clone(),
+            width: None,
+            height: None,
+            exposure: None,
+            gain: None,
+            auto_exposure: false,
+            auto_gain: false,
+            warmup_frames: device.warmup_frames,
+            output: None,
+        })
+        .collect()
+}
+
+fn build_embedder(config: &ResolvedConfig) -> PamResult<DlibBackend> {
+    let extractor_config = FaceExtractionConfig {
+        image: PathBuf::new(),
+        landmark_model: config.landmark_model.clone(),
+        encoder_model: config.encoder_model.clone(),
+        output: None,
+        jitters: config.jitters,
+        selection: FaceSelectionPolicy::default(),
+    };
+    let models = EnvModelPathResolver
+        .resolve(&extractor_config)
+        .map_err(AuthError::from)?;
+    DlibBackend::new(&models).map_err(AuthError::from)
+}
+
+fn load_embedding_store(
+    config: &ResolvedConfig,
+    request: &PamRequest,
+    logger: &mut PamLogger,
+    embedding_key: &mut Option<Vec<u8>>,
+    helper_env: Option<&HelperEnvOverrides>,
+) -> PamResult<Vec<EnrolledEmbedding>> {
+    loop {
+        match load_enrolled_embeddings(
+            Some(config.embedding_store_dir.as_path()),
+            &request.user,
+            embedding_key.as_deref(),
+        ) {
+            Ok(embeddings) => return Ok(embeddings),
+            Err(AppError::EncryptedStoreRequiresKey { .. }) => {
+                if embedding_key.is_some() {
+                    return Err(AuthError::SecretServiceUnavailable(
+                        "Secret Service key failed to decrypt embedding store".into(),
+                    ));
+                }
+                match run_secret_service_helper(&request.user, config.capture_timeout, helper_env) {
+                    Ok(HelperResponse::Key(bytes)) => {
+                        logger.info(&format!(
+                            "Secret Service helper returned embedding key ({} bytes) for user '{}' via service '{}' — retrying store load",
+                            bytes.len(),
+                            request.user,
+                            default_service_name(),
+                        ));
+                        *embedding_key = Some(bytes);
+                    }
+                    Ok(HelperResponse::Missing { message }) => {
+                        logger.warn(&format!(
+                            "Embedding key missing for user '{}': {message}",
+                            request.user
+                        ));
+                        return Err(AuthError::SecretServiceUnavailable(message));
+                    }
+                    Err(err) => return Err(map_secret_helper_error(err)),
+                }
+            }
+            Err(err) => return Err(AuthError::Core(err)),
+        }
+    }
+}
+
+fn notify_secret_service_unavailable(
+    reason: &str,
+    logger: &mut PamLogger,
+    messenger: &mut PamConversationMessenger,
+) {
+    logger.info(&format!(
+        "Secret Service unavailable; skipping face authentication: {reason}"
+    ));
+    messenger.notify(logger, Message::SecretServiceUnavailable, None, 0);
+}
+
+fn is_silent(flags: c_int) -> bool {
+    flags & PamFlag::SILENT as c_int != 0
+}
+
+fn map_secret_helper_error(err: SecretHelperError) -> AuthError {
+    match err {
+        SecretHelperError::SecretServiceUnavailable(message) => {
+            AuthError::SecretServiceUnavailable(message)
+        }
+        SecretHelperError::PrivilegeDrop(failure) if failure.is_eperm() => {
+            AuthError::SecretServiceUnavailable(format!(
+                "Secret Service helper {}",
+                failure.message()
+            ))
+        }
+        SecretHelperError::PrivilegeDrop(failure) => {
+            AuthError::Pam(format!("Secret Service helper {}", failure.message()))
+        }
+        SecretHelperError::IpcFailure(message) => {
+            AuthError::Pam(format!("Secret Service helper failed: {message}"))
+        }
+    }
+}
+
+fn log_privilege_drop_context(user: &str, logger: &mut PamLogger) {
+    let Ok(Some(info)) = User::from_name(user) else {
+        return;
+    };
+
+    let current_uid = geteuid();
+    let current_gid = getegid();
+    if current_uid == info.uid && current_gid == info.gid {
+        logger.info(&format!(
+            "Secret Service helper privilege drop skipped for user '{user}': already running as target uid/gid"
+        ));
+    }
+}
+
+fn load_config(
+    override_path: Option<&Path>,
+    service: &str,
+    user: &str,
+) -> PamResult<ResolvedConfigWithSource> {
+    let Some(path) = override_path else {
+        return chissu_config::load_resolved_config_for(service, user).map_err(map_config_error);
+    };
+    let loaded = chissu_config::load_resolved_from_paths_for(
+        &[path.to_path_buf()],
+        Some(service),
+        Some(user),
+    )
+    .map_err(map_config_error)?;
+    if loaded.source.is_none() {
+        return Err(AuthError::Config(format!(
+            "Configuration file {} requested via module arguments does not exist",
+            path.display()
+        )));
+    }
+    Ok(loaded)
+}
+
+fn prepare_helper_env(
+    request: &PamRequest,
+    session_mode: SecretServiceSessionMode,
+    logger: &mut PamLogger,
+) -> Option<HelperEnvOverrides> {
+    let uid = match lookup_uid(&request.user) {
+        Ok(uid) => uid,
+        Err(err) => {
+            logger.warn(&format!(
+                "Unable to resolve UID for user '{}' while preparing Secret Service env: {err}",
+                request.user
+            ));
+            return None;
+        }
+    };
+
+    let inspector = LogindInspector::new();
+    match inspector.inspect(uid, request.tty.as_deref()) {
+        Ok(Some(env)) => {
+            let effective_mode = env.effective_session_mode(session_mode);
+            let pairs: Vec<(String, String)> = env
+                .env_pairs(session_mode)
+                .into_iter()
+                .filter(|(key, value)| should_apply_env_override(key, value))
+                .collect();
+            let removals = opposite_session_env_removals(effective_mode);
+            if pairs.is_empty() && removals.is_empty() {
+                logger.debug(
+                    "Logind session found but no Secret Service environment overrides were needed",
+                );
+                None
+            } else {
+                let applied = pairs
+                    .iter()
+                    .map(|(key, _)| key.as_str())
+                    .collect::<Vec<_>>()
+                    .join(",");
+                let removed = removals
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(",");
+                logger.info(&format!(
+                    "Recovered Secret Service session environment from logind for user '{}': {} configured_mode={} selected_mode={} applied={} removed={}",
+                    request.user,
+                    env.summary(),
+                    session_mode_name(session_mode),
+                    effective_mode.as_str(),
+                    applied,
+                    removed,
+                ));
+                Some(HelperEnvOverrides::from_parts(pairs, removals))
+            }
+        }
+        Ok(None) => {
+            logger.warn(&format!(
+                "No active logind session for user '{}' (tty hint {})",
+                request.user,
+                request.tty.as_deref().unwrap_or("-")
+            ));
+            None
+        }
+        Err(err) => {
+            logger.warn(&format!(
+                "Failed to query logind for user '{}': {err}",
+                request.user
+            ));
+            None
+        }
+    }
+}
+
+fn lookup_uid(user: &str) -> Result<u32, String> {
+    match User::from_name(user).map_err(|err| format!("failed to resolve user '{user}': {err}"))? {
+        Some(info) => Ok(info.uid.as_raw()),
+        None => Err(format!("user '{user}' not found")),
+    }
+}
+
+fn map_config_error(err: ConfigError) -> AuthError {
+    match err {
+        ConfigError::Read { path, source } => {
+            AuthError::Config(format!("Failed to read {}: {}", path.display(), source))
+        }
+        ConfigError::Parse { path, message } => {
+            AuthError::Config(format!("Failed to parse {}: {}", path.display(), message))
+        }
+    }
+}
+
+fn env_var_missing(name: &str) -> bool {
+    match env::var_os(name) {
+        Some(value) => value.is_empty(),
+        None => true,
+    }
+}
+
+fn should_apply_env_override(key: &str, value: &str) -> bool {
+    match key {
+        "DISPLAY" | "WAYLAND_DISPLAY" => env_var_missing(key),
+        "XDG_RUNTIME_DIR" => env::var(key).map_or(true, |current| current != value),
+        "DBUS_SESSION_BUS_ADDRESS" => dbus_env_needs_override(value),
+        _ => false,
+    }
+}
+
+fn opposite_session_env_removals(mode: EffectiveSessionMode) -> Vec<String> {
+    match mode {
+        EffectiveSessionMode::X11 if env_var_present("WAYLAND_DISPLAY") => {
+            vec!["WAYLAND_DISPLAY".into()]
+        }
+        EffectiveSessionMode::Wayland if env_var_present("DISPLAY") => vec!["DISPLAY".into()],
+        _ => Vec::new(),
+    }
+}
+
+fn dbus_env_needs_override(target_address: &str) -> bool {
+    match env::var("DBUS_SESSION_BUS_ADDRESS") {
+        Ok(current) => {
+            let trimmed = current.trim();
+            trimmed.is_empty() || trimmed.starts_with("autolaunch:") || trimmed != target_address
+        }
+        Err(_) => true,
+    }
+}
+
+fn env_var_present(name: &str) -> bool {
+    match env::var_os(name) {
+        Some(value) => !value.is_empty(),
+        None => false,
+    }
+}
+
+fn session_mode_name(mode: SecretServiceSessionMode) -> &'static str {
+    match mode {
+        SecretServiceSessionMode::Auto => "auto",
+        SecretServiceSessionMode::X11 => "x11",
+        SecretServiceSessionMode::Wayland => "wayland",
+    }
+}
+
+/// Parses `argv` the same way for every entry point. Invalid arguments are a
+/// misconfigured stack and return `PAM_SYSTEM_ERR`.
+///
+/// # Safety
+/// `argv` must point to `argc` valid, NUL-terminated strings (or be null).
+unsafe fn parse_module_args(
+    argc: c_int,
+    argv: *const *const c_char,
+    logger: &mut PamLogger,
+) -> Result<ModuleArgs, PamReturnCode> {
+    match ModuleArgs::from_raw(argc, argv) {
+        Ok(args) => {
+            if args.debug {
+                logger.enable_debug();
+            }
+            Ok(args)
+        }
+        Err(err) => {
+            logger.error(&format!("Invalid module arguments: {err}"));
+            Err(PamReturnCode::SYSTEM_ERR)
+        }
+    }
+}
+
+unsafe fn get_user_name(pamh: *mut PamHandle) -> PamResult<String> {
+    if pamh.is_null() {
+        return Err(AuthError::Pam("pam handle was null".into()));
+    }
+    let handle = &*pamh;
+    let mut ptr: *const c_char = ptr::null();
+    let rc = get_user(handle, &mut ptr, ptr::null());
+    if rc != PamReturnCode::SUCCESS {
+        return Err(AuthError::Pam(format!("pam_get_user failed: {rc}")));
+    }
+    if ptr.is_null() {
+        return Err(AuthError::Pam("pam_get_user returned null".into()));
+    }
+    Ok(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+}
+
+unsafe fn get_tty_name(pamh: *mut PamHandle) -> Option<String> {
+    get_string_item(pamh, PamItemType::TTY)
+}
+
+unsafe fn get_remote_host(pamh: *mut PamHandle) -> Option<String> {
+    get_string_item(pamh, PamItemType::RHOST)
+}
+
+unsafe fn get_string_item(pamh: *mut PamHandle, item: PamItemType) -> Option<String> {
+    if pamh.is_null() {
+        return None;
+    }
+    let handle = &*pamh;
+    let mut ptr: *const c_void = ptr::null();
+    let rc = get_item(handle, item, &mut ptr);
+    if rc != PamReturnCode::SUCCESS || ptr.is_null() {
+        return None;
+    }
+    let raw = CStr::from_ptr(ptr as *const c_char).to_string_lossy();
+    let value = raw.trim();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+/// Stores a password typed at the concurrent prompt as `PAM_AUTHTOK`, where
+/// `try_first_pass`/`use_first_pass` modules such as `pam_unix` pick it up.
+unsafe fn set_authtok(pamh: *mut PamHandle, token: &AuthToken) -> PamResult<()> {
+    if pamh.is_null() {
+        return Err(AuthError::Pam("pam handle was null".into()));
+    }
+    let Ok(value) = CString::new(token.0.clone()) else {
+        return Err(AuthError::Pam("password contained a null byte".into()));
+    };
+    let rc = set_item(
+        &mut *pamh,
+        PamItemType::AUTHTOK,
+        &*(value.as_ptr() as *const c_void),
+    );
+    // PAM keeps its own copy of the item.
+    let mut bytes = value.into_bytes();
+    wipe(&mut bytes);
+    if rc != PamReturnCode::SUCCESS {
+        return Err(AuthError::Pam(format!(
+            "pam_set_item(PAM_AUTHTOK) failed: {rc}"
+        )));
+    }
+    Ok(())
+}
+
+unsafe fn get_service_name(pamh: *mut PamHandle) -> PamResult<String> {
+    if pamh.is_null() {
+        return Err(AuthError::Pam("pam handle was null".into()));
+    }
+    let handle = &*pamh;
+    let mut ptr: *const std::ffi::c_void = ptr::null();
+    let rc = get_item(handle, PamItemType::SERVICE, &mut ptr);
+    if rc != PamReturnCode::SUCCESS {
+        return Err(AuthError::Pam(format!(
+            "pam_get_item(PAM_SERVICE) failed: {rc}"
+        )));
+    }
+    if ptr.is_null() {
+        return Err(AuthError::Pam("PAM service item was null".into()));
+    }
+    let cstr = CStr::from_ptr(ptr as *const c_char);
+    Ok(cstr.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chissu_face_core::faces::{best_similarity_against, BoundingBox};
+    use secret_helper::{HelperError, PrivilegeDropStage};
+    use serial_test::serial;
+    use std::ffi::CStr;
+    use std::io::Write;
+    use std::sync::{Mutex, OnceLock};
+    use std::time::Duration;
+    use tempfile::NamedTempFile;
+
+    static CONV_LOG: OnceLock<Mutex<Vec<(PamMessageStyle, String)>>> = OnceLock::new();
+
+    fn conversation_log() -> &'static Mutex<Vec<(PamMessageStyle, String)>> {
+        CONV_LOG.get_or_init(|| Mutex::new(Vec::new()))
+    }
+
+    extern "C" fn recording_conv(
+        num_msg: c_int,
+        msg: *mut *mut PamMessage,
+        resp: *mut *mut PamResponse,
+        _data: *mut c_void,
+    ) -> c_int {
+        assert_eq!(num_msg, 1);
+        unsafe {
+            let message_ptr = *msg;
+            assert!(!message_ptr.is_null());
+            let style = PamMessageStyle::from((*message_ptr).msg_style as i32);
+            let text = CStr::from_ptr((*message_ptr).msg)
+                .to_string_lossy()
+                .into_owned();
+            conversation_log().lock().unwrap().push((style, text));
+            if !resp.is_null() {
+                *resp = ptr::null_mut();
+            }
+        }
+        PamReturnCode::SUCCESS as c_int
+    }
+
+    #[test]
+    fn resolved_config_defaults() {
+        let loaded = ResolvedConfig::default();
+        assert_eq!(
+            loaded.similarity_threshold,
+            chissu_config::DEFAULT_SIMILARITY_THRESHOLD
+        );
+        assert_eq!(
+            loaded.capture_timeout,
+            Duration::from_secs(chissu_config::DEFAULT_TIMEOUT_SECS)
+        );
+        assert_eq!(
+            loaded.frame_interval,
+            Duration::from_millis(chissu_config::DEFAULT_INTERVAL_MILLIS)
+        );
+        assert_eq!(loaded.video_device, chissu_config::DEFAULT_VIDEO_DEVICE);
+        assert_eq!(loaded.pixel_format, chissu_config::DEFAULT_PIXEL_FORMAT);
+        assert_eq!(
+            loaded.embedding_store_dir,
+            PathBuf::from(chissu_config::DEFAULT_STORE_DIR)
+        );
+        assert!(loaded.require_secret_service);
+    }
+
+    #[test]
+    fn verify_enrolled_embeddings_detects_mismatch() {
+        let embeddings = vec![
+            EnrolledEmbedding {
+                id: "a".into(),
+                embedding: vec![0.1, 0.2, 0.3],
+                bounding_box: BoundingBox {
+                    left: 0,
+                    top: 0,
+                    right: 1,
+                    bottom: 1,
+                },
+                source: "input.json".into(),
+                created_at: "2025-01-01T00:00:00Z".into(),
+                auto_added: false,
+            },
+            EnrolledEmbedding {
+                id: "b".into(),
+                embedding: vec![0.1, 0.2],
+                bounding_box: BoundingBox {
+                    left: 0,
+                    top: 0,
+                    right: 1,
+                    bottom: 1,
+                },
+                source: "input.json".into(),
+                created_at: "2025-01-01T00:00:00Z".into(),
+                auto_added: false,
+            },
+        ];
+
+        let err = verify_enrolled_embeddings(&embeddings).unwrap_err();
+        assert!(matches!(err, AuthError::Config(msg) if msg.contains("embedding length mismatch")));
+    }
+
+    #[test]
+    fn best_similarity_reports_peak_value() {
+        let store = vec![
+            EnrolledEmbedding {
+                id: "a".into(),
+                embedding: vec![1.0, 0.0, 0.0],
+                bounding_box: BoundingBox {
+                    left: 0,
+                    top: 0,
+                    right: 1,
+                    bottom: 1,
+                },
+                source: "a.json".into(),
+                created_at: "2025-01-01T00:00:00Z".into(),
+                auto_added: false,
+            },
+            EnrolledEmbedding {
+                id: "b".into(),
+                embedding: vec![0.0, 1.0, 0.0],
+                bounding_box: BoundingBox {
+                    left: 0,
+                    top: 0,
+                    right: 1,
+                    bottom: 1,
+                },
+                source: "b.json".into(),
+                created_at: "2025-01-01T00:00:00Z".into(),
+                auto_added: false,
+            },
+        ];
+
+        let store: Vec<Vec<f64>> = store.into_iter().map(|record| record.embedding).collect();
+
+        let similarity =
+            best_similarity_against(&[1.0, 0.0, 0.0], &store, SimilarityMetric::Cosine).unwrap();
+        assert!((similarity - 1.0).abs() < 1e-6);
+
+        let similarity =
+            best_similarity_against(&[0.0, 0.0, 1.0], &store, SimilarityMetric::Cosine).unwrap();
+        assert!(similarity.is_finite());
+        assert!(similarity < 0.5);
+    }
+
+    #[test]
+    fn verify_outcome_maps_liveness_failure() {
+        let result = AuthResult::from(VerifyOutcome {
+            success: false,
+            best_similarity: 0.97,
+            frames_captured: 12,
+            failure: Some(VerifyFailure::LivenessNotConfirmed),
+            frame_scores: Vec::new(),
+            matched_face: None,
+        });
+        assert!(!result.success);
+        assert!(matches!(
+            result.failure_reason,
+            Some(FailureReason::LivenessNotConfirmed)
+        ));
+        assert_eq!(
+            liveness_mode(ConfigLivenessMode::Blink),
+            LivenessMode::Blink
+        );
+    }
+
+    #[test]
+    fn verify_outcome_maps_spoof_failure() {
+        let result = AuthResult::from(VerifyOutcome {
+            success: false,
+            best_similarity: 0.95,
+            frames_captured: 4,
+            failure: Some(VerifyFailure::SpoofSuspected),
+            frame_scores: Vec::new(),
+            matched_face: None,
+        });
+        assert!(matches!(
+            result.failure_reason,
+            Some(FailureReason::SpoofSuspected)
+        ));
+        assert_eq!(
+            anti_spoof_sensitivity(ConfigAntiSpoof::High),
+            AntiSpoofSensitivity::High
+        );
+    }
+
+    #[test]
+    fn try_read_config_parses_threshold() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "similarity_threshold = 0.8\nvideo_device = \"/dev/video5\""
+        )
+        .unwrap();
+
+        let loaded = chissu_config::load_from_paths(&[file.path().to_path_buf()])
+            .unwrap()
+            .unwrap();
+        let raw = loaded.into_contents();
+        let resolved = ResolvedConfig::from_raw(raw);
+        assert_eq!(resolved.similarity_threshold, 0.8);
+        assert_eq!(resolved.video_device, "/dev/video5");
+    }
+
+    #[test]
+    fn try_read_config_disables_secret_service_when_requested() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "require_secret_service = false").unwrap();
+        let loaded = chissu_config::load_from_paths(&[file.path().to_path_buf()])
+            .unwrap()
+            .unwrap();
+        let raw = loaded.into_contents();
+        let resolved = ResolvedConfig::from_raw(raw);
+        assert!(!resolved.require_secret_service);
+    }
+
+    #[test]
+    fn authenticate_user_skips_services_disabled_in_config() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[service.sshd]\nenabled = false").unwrap();
+        let module_args = ModuleArgs {
+            config: Some(file.path().to_path_buf()),
+            ..ModuleArgs::default()
+        };
+        let request = PamRequest {
+            service: "sshd".into(),
+            user: "alice".into(),
+            tty: None,
+            rhost: None,
+        };
+        let mut logger = PamLogger::new("sshd");
+        let mut messenger = PamConversationMessenger::without_callback();
+
+        let err = authenticate_user(
+            &request,
+            &module_args,
+            &mut AuditTrail::new(),
+            &mut logger,
+            &mut messenger,
+        )
+        .unwrap_err();
+        assert!(matches!(err, AuthError::Skipped(reason) if reason.contains("sshd")));
+    }
+
+    #[test]
+    fn audit_trail_records_the_fallback_camera_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit_path = dir.path().join("audit.jsonl");
+        let present = dir.path().join("video2");
+        fs::write(&present, b"").unwrap();
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "require_secret_service = false\nsession_policy = \"any\"\ndevice_pin = \"off\"\nembedding_store_dir = \"{}\"\naudit_log = \"{}\"\n[[video_devices]]\ndevice = \"{}\"\n[[video_devices]]\ndevice = \"{}\"",
+            dir.path().join("store").display(),
+            audit_path.display(),
+            dir.path().join("video0").display(),
+            present.display()
+        )
+        .unwrap();
+        let module_args = ModuleArgs {
+            config: Some(file.path().to_path_buf()),
+            ..ModuleArgs::default()
+        };
+        let request = PamRequest {
+            service: "sudo".into(),
+            user: "alice".into(),
+            tty: None,
+            rhost: None,
+        };
+        let mut logger = PamLogger::new("sudo");
+        let mut messenger = PamConversationMessenger::without_callback();
+        let mut audit = AuditTrail::new();
+
+        let result = authenticate_user(
+            &request,
+            &module_args,
+            &mut audit,
+            &mut logger,
+            &mut messenger,
+        )
+        .unwrap();
+        assert!(matches!(
+            result.failure_reason,
+            Some(FailureReason::EmbeddingsMissing)
+        ));
+        audit.record(
+            &request,
+            AuditOutcome::Failure,
+            Some(&result),
+            None,
+            &mut logger,
+        );
+
+        let (records, _) = AuditLog::new(&audit_path, u64::MAX, 0)
+            .read(&Default::default())
+            .unwrap();
+        assert_eq!(records[0].device, present.display().to_string());
+    }
+
+    #[test]
+    fn audit_trail_records_configured_context() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "audit_log = \"{}\"\nvideo_device = \"/dev/video2\"\n[service.sshd]\nenabled = false",
+            path.display()
+        )
+        .unwrap();
+        let module_args = ModuleArgs {
+            config: Some(file.path().to_path_buf()),
+            threshold: Some(0.8),
+            ..ModuleArgs::default()
+        };
+        let request = PamRequest {
+            service: "sshd".into(),
+            user: "alice".into(),
+            tty: Some("pts/1".into()),
+            rhost: None,
+        };
+        let mut logger = PamLogger::new("sshd");
+        let mut messenger = PamConversationMessenger::without_callback();
+        let mut audit = AuditTrail::new();
+
+        let err = authenticate_user(
+            &request,
+            &module_args,
+            &mut audit,
+            &mut logger,
+            &mut messenger,
+        )
+        .unwrap_err();
+        audit.record(
+            &request,
+            AuditOutcome::Ignored,
+            None,
+            Some(err.to_string()),
+            &mut logger,
+        );
+        let failed = AuthResult::failure(FailureReason::NoFaceDetected, f64::NEG_INFINITY, 7);
+        audit.record(
+            &request,
+            AuditOutcome::Failure,
+            Some(&failed),
+            None,
+            &mut logger,
+        );
+
+        let log = AuditLog::new(&path, u64::MAX, 0);
+        let (records, _) = log.read(&Default::default()).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].outcome, AuditOutcome::Ignored);
+        assert!(records[0].detail.as_deref().unwrap().contains("disabled"));
+        assert_eq!(records[1].tty.as_deref(), Some("pts/1"));
+        assert_eq!(records[1].device, "/dev/video2");
+        assert_eq!(records[1].similarity_threshold, 0.8);
+        assert_eq!(records[1].frames_captured, 7);
+        assert_eq!(records[1].best_similarity, None);
+        assert_eq!(
+            records[1].failure_reason.as_deref(),
+            Some("no_face_detected")
+        );
+    }
+
+    #[test]
+    fn observed_results_are_logged_with_a_marker() {
+        let mut failed = AuthResult::failure(FailureReason::ThresholdNotReached, 0.71, 3);
+        failed.frame_scores = vec![Some(0.71), None, Some(0.64)];
+        assert_eq!(
+            failed.observed_summary(),
+            "[observe] decision=failure reason=threshold_not_reached frames=3 best_similarity=0.7100, scores=[0.7100, -, 0.6400]; returning PAM_IGNORE"
+        );
+        assert!(AuthResult::success(0.95, 1)
+            .observed_summary()
+            .starts_with("[observe] decision=success frames=1"));
+    }
+
+    #[test]
+    fn observe_mode_turns_errors_into_observed_ignores() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit_path = dir.path().join("audit.jsonl");
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "require_secret_service = false\nsession_policy = \"any\"\nvideo_device = \"{}\"\ndevice_absent_action = \"error\"\naudit_log = \"{}\"\n[service.sudo]\nmode = \"observe\"",
+            dir.path().join("video9").display(),
+            audit_path.display()
+        )
+        .unwrap();
+        let module_args = ModuleArgs {
+            config: Some(file.path().to_path_buf()),
+            ..ModuleArgs::default()
+        };
+        let request = |service: &str| PamRequest {
+            service: service.into(),
+            user: "alice".into(),
+            tty: None,
+            rhost: None,
+        };
+        let mut logger = PamLogger::new("sudo");
+        let mut messenger = PamConversationMessenger::without_callback();
+
+        let enforced = authenticate_user(
+            &request("login"),
+            &module_args,
+            &mut AuditTrail::new(),
+            &mut logger,
+            &mut messenger,
+        )
+        .unwrap_err();
+        assert!(matches!(enforced, AuthError::DeviceUnavailable { .. }));
+
+        let mut audit = AuditTrail::new();
+        let observed = authenticate_user(
+            &request("sudo"),
+            &module_args,
+            &mut audit,
+            &mut logger,
+            &mut messenger,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            &observed,
+            AuthError::Observed(inner) if matches!(**inner, AuthError::DeviceUnavailable { .. })
+        ));
+        audit.record(
+            &request("sudo"),
+            AuditOutcome::Error,
+            None,
+            None,
+            &mut logger,
+        );
+        let record = fs::read_to_string(&audit_path).unwrap();
+        assert!(record.contains("\"observed\":true"), "{record}");
+
+        let interrupted = observed_error(AuthError::Interrupted {
+            signal: Signal::SIGTERM,
+            action: InterruptAction::Abort,
+        });
+        assert!(matches!(
+            interrupted,
+            AuthError::Interrupted {
+                action: InterruptAction::Ignore,
+                ..
+            }
+        ));
+        assert!(matches!(
+            observed_error(AuthError::Skipped("disabled".into())),
+            AuthError::Skipped(_)
+        ));
+    }
+
+    #[test]
+    fn session_skip_reason_covers_rhost_remote_and_seatless_sessions() {
+        let session = |remote: bool, seat: Option<&str>| ProcessSession {
+            session_id: "4".into(),
+            remote,
+            remote_host: remote.then(|| "10.0.0.7".into()),
+            seat: seat.map(Into::into),
+        };
+        let local = session(false, Some("seat0"));
+        let ssh = session(true, None);
+        let container = session(false, None);
+
+        let reason = session_skip_reason(SessionPolicy::Local, Some("203.0.113.9"), Some(&local));
+        assert!(reason.unwrap().contains("PAM_RHOST '203.0.113.9'"));
+        assert_eq!(
+            session_skip_reason(SessionPolicy::Local, Some("localhost"), Some(&local)),
+            None
+        );
+        assert_eq!(
+            session_skip_reason(SessionPolicy::Local, Some(""), None),
+            None
+        );
+
+        let reason = session_skip_reason(SessionPolicy::NoRemote, None, Some(&ssh));
+        assert!(reason.unwrap().contains("remote from '10.0.0.7'"));
+        let reason = session_skip_reason(SessionPolicy::Local, None, Some(&container));
+        assert!(reason.unwrap().contains("no seat"));
+        assert_eq!(
+            session_skip_reason(SessionPolicy::NoRemote, None, Some(&container)),
+            None
+        );
+        assert_eq!(
+            session_skip_reason(SessionPolicy::Any, Some("203.0.113.9"), Some(&ssh)),
+            None
+        );
+    }
+
+    #[test]
+    fn authenticate_user_skips_remote_rhost_before_camera() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "require_secret_service = false").unwrap();
+        let module_args = ModuleArgs {
+            config: Some(file.path().to_path_buf()),
+            ..ModuleArgs::default()
+        };
+        let request = PamRequest {
+            service: "sshd".into(),
+            user: "alice".into(),
+            tty: Some("ssh".into()),
+            rhost: Some("198.51.100.4".into()),
+        };
+        let mut logger = PamLogger::new("sshd");
+        let mut messenger = PamConversationMessenger::without_callback();
+
+        let err = authenticate_user(
+            &request,
+            &module_args,
+            &mut AuditTrail::new(),
+            &mut logger,
+            &mut messenger,
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, AuthError::Skipped(reason) if reason.contains("198.51.100.4") && reason.contains("local"))
+        );
+    }
+
+    fn faillock_request(dir: &Path, action: &str) -> (NamedTempFile, ModuleArgs, PamRequest) {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "require_secret_service = false\nsession_policy = \"any\"\nfaillock_deny = 2\nfaillock_action = \"{action}\"\nfaillock_dir = \"{}\"",
+            dir.display()
+        )
+        .unwrap();
+        let module_args = ModuleArgs {
+            config: Some(file.path().to_path_buf()),
+            ..ModuleArgs::default()
+        };
+        let request = PamRequest {
+            service: "sudo".into(),
+            user: "alice".into(),
+            tty: None,
+            rhost: None,
+        };
+        (file, module_args, request)
+    }
+
+    #[test]
+    fn authenticate_user_skips_camera_while_locked_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FaillockStore::new(dir.path());
+        for _ in 0..2 {
+            store
+                .record_failure("alice", Duration::from_secs(900), SystemTime::now())
+                .unwrap();
+        }
+        let mut logger = PamLogger::new("sudo");
+        let mut messenger = PamConversationMessenger::without_callback();
+
+        let (_file, module_args, request) = faillock_request(dir.path(), "ignore");
+        let err = authenticate_user(
+            &request,
+            &module_args,
+            &mut AuditTrail::new(),
+            &mut logger,
+            &mut messenger,
+        )
+        .unwrap_err();
+        assert!(matches!(err, AuthError::Skipped(reason) if reason.contains("locked out")));
+
+        let (_file, module_args, request) = faillock_request(dir.path(), "deny");
+        let result = authenticate_user(
+            &request,
+            &module_args,
+            &mut AuditTrail::new(),
+            &mut logger,
+            &mut messenger,
+        )
+        .unwrap();
+        assert!(!result.success);
+        assert!(matches!(
+            result.failure_reason,
+            Some(FailureReason::LockedOut)
+        ));
+    }
+
+    #[test]
+    fn unreadable_faillock_tally_counts_as_locked_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FaillockStore::new(dir.path());
+        fs::write(store.tally_path("alice").unwrap(), b"not json").unwrap();
+        let mut logger = PamLogger::new("sudo");
+        let mut messenger = PamConversationMessenger::without_callback();
+
+        let (_file, module_args, request) = faillock_request(dir.path(), "ignore");
+        let err = authenticate_user(
+            &request,
+            &module_args,
+            &mut AuditTrail::new(),
+            &mut logger,
+            &mut messenger,
+        )
+        .unwrap_err();
+        assert!(matches!(err, AuthError::Skipped(reason) if reason.contains("tally unreadable")));
+
+        let (_file, module_args, request) = faillock_request(dir.path(), "deny");
+        let result = authenticate_user(
+            &request,
+            &module_args,
+            &mut AuditTrail::new(),
+            &mut logger,
+            &mut messenger,
+        )
+        .unwrap();
+        assert!(matches!(
+            result.failure_reason,
+            Some(FailureReason::LockedOut)
+        ));
+    }
+
+    #[test]
+    fn update_faillock_counts_camera_failures_and_resets_on_success() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FaillockStore::new(dir.path());
+        let config = ResolvedConfig {
+            faillock_deny: 3,
+            ..ResolvedConfig::default()
+        };
+        let request = PamRequest {
+            service: "sudo".into(),
+            user: "bob".into(),
+            tty: None,
+            rhost: None,
+        };
+        let mut logger = PamLogger::new("sudo");
+
+        for reason in [
+            FailureReason::ThresholdNotReached,
+            FailureReason::EmbeddingsMissing,
+            FailureReason::SpoofSuspected,
+        ] {
+            let result = AuthResult::failure(reason, 0.1, 3);
+            update_faillock(&store, &config, &request, &result, &mut logger);
+        }
+        assert_eq!(store.load("bob").unwrap().failures.len(), 2);
+
+        update_faillock(
+            &store,
+            &config,
+            &request,
+            &AuthResult::success(0.95, 1),
+            &mut logger,
+        );
+        assert!(store.load("bob").unwrap().failures.is_empty());
     }
-    let handle = &*pamh;
-    let mut ptr: *const std::ffi::c_void = ptr::null();
-    let rc = get_item(handle, PamItemType::SERVICE, &mut ptr);
-    if rc != PamReturnCode::SUCCESS {
-        return Err(AuthError::Pam(format!(
-            "pam_get_item(PAM_SERVICE) failed: {rc}"
-        )));
+
+    #[test]
+    #[serial]
+    fn silent_messenger_drops_messages_but_still_prompts() {
+        conversation_log().lock().unwrap().clear();
+        let mut messenger = PamConversationMessenger::from_callback(recording_conv);
+        messenger.silence();
+        let mut logger = PamLogger::new("test-service");
+
+        messenger.send_text_info(&mut logger, "Face authentication succeeded");
+        messenger.notify(&mut logger, Message::NoFaceDetected, None, 3);
+        assert!(conversation_log().lock().unwrap().is_empty());
+        assert!(messenger.can_prompt());
+
+        let config = ResolvedConfig {
+            confirm_match: true,
+            ..ResolvedConfig::default()
+        };
+        let mut silent = PamConversationMessenger::from_callback(enter_conv);
+        silent.silence();
+        assert!(confirm_match(&config, &outcome(true), &mut logger, &mut silent).unwrap());
     }
-    if ptr.is_null() {
-        return Err(AuthError::Pam("PAM service item was null".into()));
+
+    #[test]
+    #[serial]
+    fn messenger_emits_text_and_error_messages() {
+        conversation_log().lock().unwrap().clear();
+        let mut messenger = PamConversationMessenger::from_callback(recording_conv);
+        let mut logger = PamLogger::new("test-service");
+
+        messenger.send_text_info(&mut logger, "Face authentication succeeded");
+        messenger.send_error_msg(&mut logger, "Face authentication failed");
+
+        let entries = conversation_log().lock().unwrap().clone();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].0, PamMessageStyle::TEXT_INFO);
+        assert!(entries[0].1.contains("succeeded"));
+        assert_eq!(entries[1].0, PamMessageStyle::ERROR_MSG);
+        assert!(entries[1].1.contains("failed"));
     }
-    let cstr = CStr::from_ptr(ptr as *const c_char);
-    Ok(cstr.to_string_lossy().into_owned())
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use chissu_face_core::faces::BoundingBox;
-    use secret_helper::{HelperError, PrivilegeDropStage};
-    use serial_test::serial;
-    use std::ffi::CStr;
-    use std::io::Write;
-    use std::sync::{Mutex, OnceLock};
-    use tempfile::NamedTempFile;
+    #[test]
+    #[serial]
+    fn device_policy_turns_unavailable_camera_into_skip() {
+        conversation_log().lock().unwrap().clear();
+        let mut messenger = PamConversationMessenger::from_callback(recording_conv);
+        let mut logger = PamLogger::new("sudo");
+        let config = ResolvedConfig {
+            device_denied_action: DeviceErrorAction::Error,
+            ..ResolvedConfig::default()
+        };
+        let busy = || {
+            AuthError::from_capture(AppError::Io(std::io::Error::from_raw_os_error(libc::EBUSY)))
+        };
 
-    static CONV_LOG: OnceLock<Mutex<Vec<(PamMessageStyle, String)>>> = OnceLock::new();
+        let err = apply_device_policy(busy(), &config, &mut logger, &mut messenger);
+        assert!(
+            matches!(err, AuthError::Skipped(ref reason) if reason.contains("busy") && reason.contains("device_busy_action"))
+        );
+        let entries = conversation_log().lock().unwrap().clone();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, PamMessageStyle::TEXT_INFO);
+        assert_eq!(entries[0].1, Message::DeviceUnavailable.default_template());
+
+        let denied = AuthError::from_capture(AppError::DeviceOpen {
+            device: "/dev/video2".into(),
+            source: std::io::Error::from_raw_os_error(libc::EACCES),
+        });
+        let err = apply_device_policy(denied, &config, &mut logger, &mut messenger);
+        assert!(matches!(
+            err,
+            AuthError::DeviceUnavailable {
+                fault: DeviceFault::PermissionDenied,
+                ..
+            }
+        ));
 
-    fn conversation_log() -> &'static Mutex<Vec<(PamMessageStyle, String)>> {
-        CONV_LOG.get_or_init(|| Mutex::new(Vec::new()))
+        let generic = AuthError::from_capture(AppError::FrameProcessing("bad frame".into()));
+        let err = apply_device_policy(generic, &config, &mut logger, &mut messenger);
+        assert!(matches!(err, AuthError::Core(_)));
+        assert_eq!(conversation_log().lock().unwrap().len(), 1);
     }
 
-    extern "C" fn recording_conv(
+    #[test]
+    fn probe_reports_missing_device_node() {
+        let dir = tempfile::tempdir().unwrap();
+        let device = |path: &Path| chissu_config::VideoDevice {
+            device: path.display().to_string(),
+            pixel_format: "GREY".into(),
+            warmup_frames: 0,
+        };
+        let mut config = ResolvedConfig {
+            video_devices: vec![device(&dir.path().join("video9"))],
+            ..ResolvedConfig::default()
+        };
+        assert!(matches!(
+            probe_device(&config),
+            Err(AuthError::DeviceUnavailable {
+                fault: DeviceFault::Absent,
+                ..
+            })
+        ));
+
+        // A present fallback is enough, and is the camera reported.
+        config.video_devices.push(device(dir.path()));
+        assert_eq!(
+            probe_device(&config).unwrap(),
+            dir.path().display().to_string()
+        );
+    }
+
+    extern "C" fn password_conv(
         num_msg: c_int,
         msg: *mut *mut PamMessage,
         resp: *mut *mut PamResponse,
@@ -903,175 +3293,226 @@ mod tests {
     ) -> c_int {
         assert_eq!(num_msg, 1);
         unsafe {
-            let message_ptr = *msg;
-            assert!(!message_ptr.is_null());
-            let style = PamMessageStyle::from((*message_ptr).msg_style as i32);
-            let text = CStr::from_ptr((*message_ptr).msg)
-                .to_string_lossy()
-                .into_owned();
-            conversation_log().lock().unwrap().push((style, text));
-            if !resp.is_null() {
-                *resp = ptr::null_mut();
-            }
+            assert_eq!((**msg).msg_style, PamMessageStyle::PROMPT_ECHO_OFF as c_int);
+            let response = libc::calloc(1, std::mem::size_of::<PamResponse>()) as *mut PamResponse;
+            (*response).resp = libc::strdup(c"hunter2".as_ptr());
+            *resp = response;
         }
         PamReturnCode::SUCCESS as c_int
     }
 
+    fn outcome(success: bool) -> VerifyOutcome {
+        VerifyOutcome {
+            success,
+            best_similarity: if success { 0.97 } else { 0.4 },
+            frames_captured: 3,
+            failure: (!success).then_some(VerifyFailure::ThresholdNotReached),
+            frame_scores: Vec::new(),
+            matched_face: None,
+        }
+    }
+
+    /// Spins until cancelled, like a capture that never matches.
+    fn until_cancelled(cancel: &AtomicBool) -> PamResult<VerifyOutcome> {
+        while !cancel.load(Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        Err(AuthError::Core(AppError::Cancelled))
+    }
+
     #[test]
-    fn resolved_config_defaults() {
-        let loaded = ResolvedConfig::default();
-        assert_eq!(
-            loaded.similarity_threshold,
-            chissu_config::DEFAULT_SIMILARITY_THRESHOLD
-        );
-        assert_eq!(
-            loaded.capture_timeout,
-            Duration::from_secs(chissu_config::DEFAULT_TIMEOUT_SECS)
-        );
-        assert_eq!(
-            loaded.frame_interval,
-            Duration::from_millis(chissu_config::DEFAULT_INTERVAL_MILLIS)
-        );
-        assert_eq!(loaded.video_device, chissu_config::DEFAULT_VIDEO_DEVICE);
-        assert_eq!(loaded.pixel_format, chissu_config::DEFAULT_PIXEL_FORMAT);
-        assert_eq!(
-            loaded.embedding_store_dir,
-            PathBuf::from(chissu_config::DEFAULT_STORE_DIR)
-        );
-        assert!(loaded.require_secret_service);
+    fn prompt_returns_the_typed_password() {
+        let mut messenger = PamConversationMessenger::from_callback(password_conv);
+        let token = messenger.prompt_echo_off("Password: ").unwrap().unwrap();
+        assert_eq!(token.0, b"hunter2");
+        assert_eq!(format!("{token:?}"), "AuthToken(<redacted>)");
     }
 
     #[test]
-    fn verify_enrolled_embeddings_detects_mismatch() {
-        let embeddings = vec![
-            EnrolledEmbedding {
-                id: "a".into(),
-                embedding: vec![0.1, 0.2, 0.3],
-                bounding_box: BoundingBox {
-                    left: 0,
-                    top: 0,
-                    right: 1,
-                    bottom: 1,
+    fn typed_password_cancels_the_camera() {
+        let (answer, face) =
+            race_prompt(until_cancelled, || Ok(Some(AuthToken(b"hunter2".to_vec()))));
+        assert_eq!(answer.unwrap().unwrap().0, b"hunter2");
+        assert!(matches!(face, Err(AuthError::Core(AppError::Cancelled))));
+    }
+
+    #[test]
+    fn face_match_first_wins_whatever_closes_the_prompt() {
+        let mut logger = PamLogger::new("sudo");
+        let answers: [fn() -> Result<Option<AuthToken>, String>; 3] = [
+            || Ok(None),
+            || Ok(Some(AuthToken(b"late".to_vec()))),
+            || Err("CONV_ERR".into()),
+        ];
+        for reply in answers {
+            let (matched, wait) = std::sync::mpsc::channel();
+            let (answer, face) = race_prompt(
+                move |_| {
+                    matched.send(()).unwrap();
+                    Ok(outcome(true))
                 },
-                source: "input.json".into(),
-                created_at: "2025-01-01T00:00:00Z".into(),
-            },
-            EnrolledEmbedding {
-                id: "b".into(),
-                embedding: vec![0.1, 0.2],
-                bounding_box: BoundingBox {
-                    left: 0,
-                    top: 0,
-                    right: 1,
-                    bottom: 1,
+                // The prompt is only answered after the face has matched.
+                || {
+                    wait.recv().unwrap();
+                    reply()
                 },
-                source: "input.json".into(),
-                created_at: "2025-01-01T00:00:00Z".into(),
-            },
-        ];
-
-        let err = verify_enrolled_embeddings(&embeddings).unwrap_err();
-        assert!(matches!(err, AuthError::Config(msg) if msg.contains("embedding length mismatch")));
+            );
+            let settled = settle_prompt_race(answer, face, &mut logger).unwrap();
+            assert!(settled.success);
+        }
     }
 
     #[test]
-    fn best_similarity_reports_peak_value() {
-        let store = vec![
-            EnrolledEmbedding {
-                id: "a".into(),
-                embedding: vec![1.0, 0.0, 0.0],
-                bounding_box: BoundingBox {
-                    left: 0,
-                    top: 0,
-                    right: 1,
-                    bottom: 1,
-                },
-                source: "a.json".into(),
-                created_at: "2025-01-01T00:00:00Z".into(),
+    fn face_result_survives_a_late_answer_and_failed_prompts() {
+        let (answer, face) = race_prompt(
+            |_| Ok(outcome(true)),
+            || {
+                std::thread::sleep(Duration::from_millis(20));
+                Ok(None)
             },
-            EnrolledEmbedding {
-                id: "b".into(),
-                embedding: vec![0.0, 1.0, 0.0],
-                bounding_box: BoundingBox {
-                    left: 0,
-                    top: 0,
-                    right: 1,
-                    bottom: 1,
-                },
-                source: "b.json".into(),
-                created_at: "2025-01-01T00:00:00Z".into(),
+        );
+        assert!(matches!(answer, Ok(None)));
+        assert!(face.unwrap().success);
+
+        // A failed prompt does not cancel the camera.
+        let (answer, face) = race_prompt(
+            |cancel| {
+                std::thread::sleep(Duration::from_millis(20));
+                assert!(!cancel.load(Ordering::SeqCst));
+                Ok(outcome(false))
             },
-        ];
+            || Err("CONV_ERR".into()),
+        );
+        assert!(answer.is_err());
+        assert!(!face.unwrap().success);
+    }
 
-        let similarity = best_similarity_against_store(&[1.0, 0.0, 0.0], &store);
-        assert!((similarity - 1.0).abs() < 1e-6);
+    /// Answers a `PAM_PROMPT_ECHO_ON` prompt with `text` after a short delay.
+    unsafe fn reply_echo_on(
+        msg: *mut *mut PamMessage,
+        resp: *mut *mut PamResponse,
+        text: &CStr,
+    ) -> c_int {
+        assert_eq!((**msg).msg_style, PamMessageStyle::PROMPT_ECHO_ON as c_int);
+        std::thread::sleep(Duration::from_millis(5));
+        let response = libc::calloc(1, std::mem::size_of::<PamResponse>()) as *mut PamResponse;
+        (*response).resp = libc::strdup(text.as_ptr());
+        *resp = response;
+        PamReturnCode::SUCCESS as c_int
+    }
 
-        let similarity = best_similarity_against_store(&[0.0, 0.0, 1.0], &store);
-        assert!(similarity.is_finite());
-        assert!(similarity < 0.5);
+    extern "C" fn enter_conv(
+        _num_msg: c_int,
+        msg: *mut *mut PamMessage,
+        resp: *mut *mut PamResponse,
+        _data: *mut c_void,
+    ) -> c_int {
+        unsafe { reply_echo_on(msg, resp, c"") }
     }
 
-    #[test]
-    fn try_read_config_parses_threshold() {
-        let mut file = NamedTempFile::new().unwrap();
-        writeln!(
-            file,
-            "similarity_threshold = 0.8\nvideo_device = \"/dev/video5\""
-        )
-        .unwrap();
+    extern "C" fn typed_conv(
+        _num_msg: c_int,
+        msg: *mut *mut PamMessage,
+        resp: *mut *mut PamResponse,
+        _data: *mut c_void,
+    ) -> c_int {
+        unsafe { reply_echo_on(msg, resp, c"yes") }
+    }
 
-        let loaded = chissu_config::load_from_paths(&[file.path().to_path_buf()])
-            .unwrap()
-            .unwrap();
-        let raw = loaded.into_contents();
-        let resolved = ResolvedConfig::from_raw(raw);
-        assert_eq!(resolved.similarity_threshold, 0.8);
-        assert_eq!(resolved.video_device, "/dev/video5");
+    extern "C" fn slow_enter_conv(
+        _num_msg: c_int,
+        msg: *mut *mut PamMessage,
+        resp: *mut *mut PamResponse,
+        _data: *mut c_void,
+    ) -> c_int {
+        std::thread::sleep(Duration::from_secs(5));
+        unsafe { reply_echo_on(msg, resp, c"") }
     }
 
     #[test]
-    fn try_read_config_disables_secret_service_when_requested() {
-        let mut file = NamedTempFile::new().unwrap();
-        writeln!(file, "require_secret_service = false").unwrap();
-        let loaded = chissu_config::load_from_paths(&[file.path().to_path_buf()])
-            .unwrap()
-            .unwrap();
-        let raw = loaded.into_contents();
-        let resolved = ResolvedConfig::from_raw(raw);
-        assert!(!resolved.require_secret_service);
+    fn confirm_match_needs_a_timely_enter() {
+        let mut logger = PamLogger::new("sudo");
+        let mut config = ResolvedConfig {
+            confirm_match: true,
+            ..ResolvedConfig::default()
+        };
+        let mut messenger = PamConversationMessenger::from_callback(enter_conv);
+        assert!(confirm_match(&config, &outcome(true), &mut logger, &mut messenger).unwrap());
+
+        // Typed text is not a confirmation, and silent conversations cannot confirm.
+        let mut typed = PamConversationMessenger::from_callback(typed_conv);
+        assert!(matches!(
+            confirm_match(&config, &outcome(true), &mut logger, &mut typed),
+            Err(AuthError::Skipped(_))
+        ));
+        let mut silent = PamConversationMessenger::without_callback();
+        assert!(matches!(
+            confirm_match(&config, &outcome(true), &mut logger, &mut silent),
+            Err(AuthError::Skipped(_))
+        ));
+
+        // A late Enter is refused at the deadline, without waiting for it.
+        config.confirm_timeout = Duration::from_millis(50);
+        let mut slow = PamConversationMessenger::from_callback(slow_enter_conv);
+        let started = Instant::now();
+        assert!(!confirm_match(&config, &outcome(true), &mut logger, &mut slow).unwrap());
+        assert!(started.elapsed() < Duration::from_secs(2));
     }
 
     #[test]
     #[serial]
-    fn messenger_emits_text_and_error_messages() {
+    fn secret_service_unavailable_prompt_is_concise() {
         conversation_log().lock().unwrap().clear();
         let mut messenger = PamConversationMessenger::from_callback(recording_conv);
         let mut logger = PamLogger::new("test-service");
 
-        messenger.send_text_info(&mut logger, "Face authentication succeeded");
-        messenger.send_error_msg(&mut logger, "Face authentication failed");
+        notify_secret_service_unavailable("locked", &mut logger, &mut messenger);
 
         let entries = conversation_log().lock().unwrap().clone();
-        assert_eq!(entries.len(), 2);
-        assert_eq!(entries[0].0, PamMessageStyle::TEXT_INFO);
-        assert!(entries[0].1.contains("succeeded"));
-        assert_eq!(entries[1].0, PamMessageStyle::ERROR_MSG);
-        assert!(entries[1].1.contains("failed"));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, PamMessageStyle::ERROR_MSG);
+        assert_eq!(
+            entries[0].1,
+            Message::SecretServiceUnavailable.default_template()
+        );
     }
 
     #[test]
     #[serial]
-    fn secret_service_unavailable_prompt_is_concise() {
+    fn messenger_applies_configured_templates() {
         conversation_log().lock().unwrap().clear();
         let mut messenger = PamConversationMessenger::from_callback(recording_conv);
-        let mut logger = PamLogger::new("test-service");
+        let mut logger = PamLogger::new("gdm-password");
+        messenger.set_context(MessageContext::new("alice", "gdm-password"));
+        let mut config = ResolvedConfig::default();
+        config.messages.success = Some("Hi {user} ({similarity})".into());
+        config.messages.no_face_hint = Some(String::new());
+        messenger.configure(&config);
 
-        notify_secret_service_unavailable("locked", &mut logger, &mut messenger);
+        messenger.notify(&mut logger, Message::NoFaceHint, None, 1);
+        messenger.notify(&mut logger, Message::Success, Some(0.93), 4);
+        messenger.notify(&mut logger, Message::ThresholdNotReached, Some(0.5), 9);
 
         let entries = conversation_log().lock().unwrap().clone();
-        assert_eq!(entries.len(), 1);
-        assert_eq!(entries[0].0, PamMessageStyle::ERROR_MSG);
-        assert_eq!(entries[0].1, SECRET_SERVICE_FALLBACK_PROMPT);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0],
+            (PamMessageStyle::TEXT_INFO, "Hi alice (0.9300)".to_string())
+        );
+        assert_eq!(entries[1].0, PamMessageStyle::ERROR_MSG);
+        assert_eq!(
+            entries[1].1,
+            Message::ThresholdNotReached.default_template()
+        );
+    }
+
+    #[test]
+    fn silent_flag_is_detected() {
+        assert!(is_silent(PamFlag::SILENT as c_int));
+        assert!(is_silent(
+            PamFlag::SILENT as c_int | PamFlag::DISALLOW_NULL_AUTHTOK as c_int
+        ));
+        assert!(!is_silent(PamFlag::DISALLOW_NULL_AUTHTOK as c_int));
     }
 
     #[test]
@@ -100,6 +100,57 @@ impl LogindInspector {
 
         Ok(Some(SessionEnvironment::from_record(record, runtime_dir)))
     }
+
+    /// Looks up the logind session that contains `pid`. Returns `None` when
+    /// the process does not belong to a session, e.g. `sshd` before login.
+    pub fn session_of_process(
+        &self,
+        pid: u32,
+    ) -> Result<Option<ProcessSession>, LogindInspectorError> {
+        let connection = Connection::system()?;
+        let manager = Proxy::new(
+            &connection,
+            "org.freedesktop.login1",
+            "/org/freedesktop/login1",
+            "org.freedesktop.login1.Manager",
+        )?;
+        let path: OwnedObjectPath = match manager.call("GetSessionByPID", &(pid,)) {
+            Ok(path) => path,
+            Err(zbus::Error::MethodError(name, _, _))
+                if name.as_str() == "org.freedesktop.login1.NoSessionForPID" =>
+            {
+                return Ok(None);
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let proxy = Proxy::new(
+            &connection,
+            "org.freedesktop.login1",
+            path.as_ref(),
+            "org.freedesktop.login1.Session",
+        )?;
+
+        Ok(Some(ProcessSession {
+            session_id: proxy
+                .get_property::<String>("Id")
+                .unwrap_or_else(|_| "unknown".into()),
+            remote: proxy.get_property::<bool>("Remote")?,
+            remote_host: normalize_string(proxy.get_property::<String>("RemoteHost").ok()),
+            seat: proxy
+                .get_property::<(String, OwnedObjectPath)>("Seat")
+                .ok()
+                .and_then(|(seat, _)| normalize_string(Some(seat))),
+        }))
+    }
+}
+
+/// Where the PAM application's own logind session comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessSession {
+    pub session_id: String,
+    pub remote: bool,
+    pub remote_host: Option<String>,
+    pub seat: Option<String>,
 }
 
 #[derive(Debug, Error)]
@@ -0,0 +1,276 @@
+use chissu_config::MessageTemplates;
+
+const SECRET_SERVICE_FALLBACK_PROMPT: &str =
+    "Face authentication unavailable. Falling back to password.";
+
+/// Every conversation message the module can send.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    Success,
+    NoFaceHint,
+    LivenessHint,
+    EmbeddingsMissing,
+    NoFaceDetected,
+    ThresholdNotReached,
+    LivenessNotConfirmed,
+    SpoofSuspected,
+    LockedOut,
+    SecretServiceUnavailable,
+    DeviceUnavailable,
+    UntrustedDevice,
+    NotInfrared,
+    FrozenStream,
+    ConfirmationTimeout,
+    /// The `PAM_PROMPT_ECHO_ON` text asking to confirm a face match.
+    ConfirmPrompt,
+    /// The `PAM_PROMPT_ECHO_OFF` text of the concurrent password prompt.
+    PasswordPrompt,
+    /// Progress while no face is in view, with `{remaining}` seconds.
+    Countdown,
+    FaceFound,
+    MoveCloser,
+    TooDark,
+}
+
+impl Message {
+    pub fn default_template(self) -> &'static str {
+        match self {
+            Message::Success => {
+                "Face authentication succeeded for user '{user}' via service '{service}'."
+            }
+            Message::NoFaceHint => "No face detected yet; align with the camera while we retry...",
+            Message::LivenessHint => "Face recognized; blink to confirm...",
+            Message::EmbeddingsMissing => {
+                "Face authentication unavailable: no enrolled embeddings for '{user}'."
+            }
+            Message::NoFaceDetected => "No face detected before timeout; stay in frame and retry.",
+            Message::ThresholdNotReached => {
+                "Face detected but similarity below threshold; please retry."
+            }
+            Message::LivenessNotConfirmed => {
+                "Face recognized but no blink detected; blink naturally and retry."
+            }
+            Message::SpoofSuspected => {
+                "Face authentication rejected: the camera image does not look live."
+            }
+            Message::LockedOut => {
+                "Too many failed face attempts; face authentication is locked for now."
+            }
+            Message::SecretServiceUnavailable => SECRET_SERVICE_FALLBACK_PROMPT,
+            Message::DeviceUnavailable => "Camera unavailable; skipping face authentication.",
+            Message::UntrustedDevice => {
+                "Face authentication rejected: this camera is not the one enrolled for '{user}'."
+            }
+            Message::NotInfrared => {
+                "Face authentication rejected: the camera is not delivering an infrared image."
+            }
+            Message::FrozenStream => {
+                "Face authentication rejected: the camera stream looks frozen or replayed."
+            }
+            Message::ConfirmationTimeout => {
+                "Face recognized but not confirmed in time; please retry."
+            }
+            Message::ConfirmPrompt => "Face recognized — press Enter to continue",
+            Message::PasswordPrompt => "Password (or look at the camera): ",
+            Message::Countdown => "Looking for your face... {remaining}s left",
+            Message::FaceFound => "Face found, verifying...",
+            Message::MoveCloser => "Face too far away; move closer to the camera.",
+            Message::TooDark => "Image too dark; face the camera or add light.",
+        }
+    }
+
+    /// Success, the liveness prompt, progress messages and the camera notice
+    /// are informational; the rest are errors.
+    pub fn is_error(self) -> bool {
+        !matches!(
+            self,
+            Message::Success
+                | Message::LivenessHint
+                | Message::DeviceUnavailable
+                | Message::Countdown
+                | Message::FaceFound
+                | Message::MoveCloser
+                | Message::TooDark
+        )
+    }
+
+    fn configured(self, templates: &MessageTemplates) -> Option<&str> {
+        let template = match self {
+            Message::Success => &templates.success,
+            Message::NoFaceHint => &templates.no_face_hint,
+            Message::LivenessHint => &templates.liveness_hint,
+            Message::EmbeddingsMissing => &templates.embeddings_missing,
+            Message::NoFaceDetected => &templates.no_face_detected,
+            Message::ThresholdNotReached => &templates.threshold_not_reached,
+            Message::LivenessNotConfirmed => &templates.liveness_not_confirmed,
+            Message::SpoofSuspected => &templates.spoof_suspected,
+            Message::LockedOut => &templates.locked_out,
+            Message::SecretServiceUnavailable => &templates.secret_service_unavailable,
+            Message::DeviceUnavailable => &templates.device_unavailable,
+            Message::UntrustedDevice => &templates.untrusted_device,
+            Message::NotInfrared => &templates.not_infrared,
+            Message::FrozenStream => &templates.frozen_stream,
+            Message::ConfirmationTimeout => &templates.confirmation_timeout,
+            Message::ConfirmPrompt => &templates.confirm_prompt,
+            Message::PasswordPrompt => &templates.password_prompt,
+            Message::Countdown => &templates.countdown,
+            Message::FaceFound => &templates.face_found,
+            Message::MoveCloser => &templates.move_closer,
+            Message::TooDark => &templates.too_dark,
+        };
+        template.as_deref()
+    }
+}
+
+/// Values substituted into templates for the current request.
+#[derive(Debug, Clone, Default)]
+pub struct MessageContext {
+    pub user: String,
+    pub service: String,
+    pub threshold: Option<f64>,
+    /// Whole seconds left before the capture deadline, while it runs.
+    pub remaining_secs: Option<u64>,
+    pub templates: MessageTemplates,
+}
+
+impl MessageContext {
+    pub fn new(user: &str, service: &str) -> Self {
+        Self {
+            user: user.to_string(),
+            service: service.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Expands the configured or default template for `message`. Returns
+    /// `None` when the template is empty, i.e. the message is turned off.
+    ///
+    /// Placeholders: `{user}`, `{service}`, `{similarity}`, `{threshold}`,
+    /// `{frames}` and `{remaining}`. Values that are not known yet render as
+    /// `n/a`; unknown placeholders are left as written.
+    pub fn render(
+        &self,
+        message: Message,
+        similarity: Option<f64>,
+        frames: usize,
+    ) -> Option<String> {
+        let template = message
+            .configured(&self.templates)
+            .unwrap_or_else(|| message.default_template());
+        if template.is_empty() {
+            return None;
+        }
+
+        let decimal = |value: Option<f64>| {
+            value
+                .filter(|value| value.is_finite())
+                .map_or_else(|| "n/a".to_string(), |value| format!("{value:.4}"))
+        };
+        let mut rendered = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            rendered.push_str(&rest[..open]);
+            let after = &rest[open + 1..];
+            let Some(close) = after.find('}') else {
+                rest = &rest[open..];
+                break;
+            };
+            let value = match &after[..close] {
+                "user" => Some(self.user.clone()),
+                "service" => Some(self.service.clone()),
+                "similarity" => Some(decimal(similarity)),
+                "threshold" => Some(decimal(self.threshold)),
+                "frames" => Some(frames.to_string()),
+                "remaining" => Some(
+                    self.remaining_secs
+                        .map_or_else(|| "n/a".to_string(), |secs| secs.to_string()),
+                ),
+                _ => None,
+            };
+            match value {
+                Some(value) => {
+                    rendered.push_str(&value);
+                    rest = &after[close + 1..];
+                }
+                None => {
+                    rendered.push('{');
+                    rest = after;
+                }
+            }
+        }
+        rendered.push_str(rest);
+        Some(rendered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> MessageContext {
+        MessageContext {
+            threshold: Some(0.9),
+            ..MessageContext::new("alice", "sudo")
+        }
+    }
+
+    #[test]
+    fn defaults_expand_placeholders() {
+        assert_eq!(
+            context().render(Message::Success, Some(0.95), 3).unwrap(),
+            "Face authentication succeeded for user 'alice' via service 'sudo'."
+        );
+        assert_eq!(
+            context()
+                .render(Message::SecretServiceUnavailable, None, 0)
+                .unwrap(),
+            SECRET_SERVICE_FALLBACK_PROMPT
+        );
+    }
+
+    #[test]
+    fn configured_templates_override_or_silence_messages() {
+        let mut context = context();
+        context.templates.threshold_not_reached =
+            Some("{user}: {similarity} < {threshold} after {frames} frames {unknown} {".into());
+        context.templates.success = Some(String::new());
+
+        assert_eq!(
+            context
+                .render(Message::ThresholdNotReached, Some(0.81234), 7)
+                .unwrap(),
+            "alice: 0.8123 < 0.9000 after 7 frames {unknown} {"
+        );
+        assert_eq!(
+            context
+                .render(Message::ThresholdNotReached, Some(f64::NEG_INFINITY), 0)
+                .unwrap(),
+            "alice: n/a < 0.9000 after 0 frames {unknown} {"
+        );
+        assert_eq!(context.render(Message::Success, Some(0.95), 1), None);
+    }
+
+    #[test]
+    fn countdown_renders_remaining_seconds() {
+        let mut context = context();
+        assert_eq!(
+            context.render(Message::Countdown, None, 0).unwrap(),
+            "Looking for your face... n/as left"
+        );
+        context.remaining_secs = Some(4);
+        assert_eq!(
+            context.render(Message::Countdown, None, 2).unwrap(),
+            "Looking for your face... 4s left"
+        );
+        assert!(!Message::Countdown.is_error());
+    }
+
+    #[test]
+    fn substituted_values_are_not_expanded_again() {
+        let context = MessageContext::new("{service}", "login");
+        assert_eq!(
+            context.render(Message::EmbeddingsMissing, None, 0).unwrap(),
+            "Face authentication unavailable: no enrolled embeddings for '{service}'."
+        );
+    }
+}
@@ -0,0 +1,137 @@
+//! Progress messages sent while the camera is capturing.
+//!
+//! Greeters such as GDM show `PAM_TEXT_INFO` inline, so a short status line
+//! tells the user the module is still looking and what gets in its way. Each
+//! assessed frame maps to one message; messages go out at most once per
+//! `progress_interval`, and a hint that did not change is not repeated.
+
+use std::time::{Duration, Instant};
+
+use crate::messages::Message;
+
+/// Mean intensity (0-255) below which a frame counts as too dark.
+const TOO_DARK_MEAN: f64 = 40.0;
+
+/// Face size, as a fraction of the shorter frame side, below which the user
+/// is asked to move closer.
+const MOVE_CLOSER_FRACTION: f64 = 0.2;
+
+pub(crate) struct ProgressReporter {
+    interval: Duration,
+    deadline: Instant,
+    frame_size: Option<(u32, u32)>,
+    last_sent: Instant,
+    last_message: Option<Message>,
+}
+
+impl ProgressReporter {
+    /// The first message is due one interval after `now`, which leaves room
+    /// for the one-off hints sent on the first frames.
+    pub(crate) fn new(interval: Duration, deadline: Instant, now: Instant) -> Self {
+        Self {
+            interval,
+            deadline,
+            frame_size: None,
+            last_sent: now,
+            last_message: None,
+        }
+    }
+
+    pub(crate) fn frame_captured(&mut self, width: u32, height: u32) {
+        self.frame_size = Some((width, height));
+    }
+
+    /// The message to send for an assessed frame, if one is due.
+    pub(crate) fn assess(
+        &mut self,
+        mean_intensity: f64,
+        face_size: Option<u32>,
+        now: Instant,
+    ) -> Option<Message> {
+        if now.saturating_duration_since(self.last_sent) < self.interval {
+            return None;
+        }
+        let message = progress_message(mean_intensity, face_size, self.frame_size);
+        if message != Message::Countdown && self.last_message == Some(message) {
+            return None;
+        }
+        self.sent(message, now);
+        Some(message)
+    }
+
+    /// Records a message sent outside [`assess`](Self::assess) so the next
+    /// progress message keeps its distance.
+    pub(crate) fn sent(&mut self, message: Message, now: Instant) {
+        self.last_sent = now;
+        self.last_message = Some(message);
+    }
+
+    /// Whole seconds left before the capture deadline, rounded up.
+    pub(crate) fn remaining_secs(&self, now: Instant) -> u64 {
+        let remaining = self.deadline.saturating_duration_since(now);
+        remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0)
+    }
+}
+
+/// Picks the hint for one frame: lighting first, then distance, then whether
+/// a face is in view at all.
+fn progress_message(
+    mean_intensity: f64,
+    face_size: Option<u32>,
+    frame_size: Option<(u32, u32)>,
+) -> Message {
+    if mean_intensity < TOO_DARK_MEAN {
+        return Message::TooDark;
+    }
+    let Some(face_size) = face_size else {
+        return Message::Countdown;
+    };
+    let too_small = frame_size.is_some_and(|(width, height)| {
+        f64::from(face_size) < f64::from(width.min(height)) * MOVE_CLOSER_FRACTION
+    });
+    if too_small {
+        Message::MoveCloser
+    } else {
+        Message::FaceFound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_map_to_hints() {
+        let frame = Some((640, 480));
+        assert_eq!(progress_message(12.0, Some(200), frame), Message::TooDark);
+        assert_eq!(progress_message(90.0, None, frame), Message::Countdown);
+        assert_eq!(progress_message(90.0, Some(60), frame), Message::MoveCloser);
+        assert_eq!(progress_message(90.0, Some(180), frame), Message::FaceFound);
+        assert_eq!(progress_message(90.0, Some(60), None), Message::FaceFound);
+    }
+
+    #[test]
+    fn messages_are_rate_limited_and_not_repeated() {
+        let start = Instant::now();
+        let interval = Duration::from_secs(2);
+        let mut progress = ProgressReporter::new(interval, start + Duration::from_secs(10), start);
+        progress.frame_captured(640, 480);
+
+        assert_eq!(progress.assess(90.0, None, start), None);
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        assert_eq!(progress.assess(90.0, None, at(2)), Some(Message::Countdown));
+        assert_eq!(progress.remaining_secs(at(2)), 8);
+        assert_eq!(progress.assess(90.0, None, at(3)), None);
+        assert_eq!(progress.assess(90.0, None, at(4)), Some(Message::Countdown));
+        assert_eq!(progress.assess(20.0, None, at(6)), Some(Message::TooDark));
+        assert_eq!(progress.assess(20.0, None, at(8)), None);
+
+        progress.sent(Message::LivenessHint, at(9));
+        assert_eq!(progress.assess(90.0, Some(200), at(10)), None);
+        assert_eq!(
+            progress.assess(90.0, Some(200), at(11)),
+            Some(Message::FaceFound)
+        );
+        assert_eq!(progress.remaining_secs(at(11)), 0);
+    }
+}